@@ -0,0 +1,60 @@
+-- Tables of the workshop management database
+-- (Expects an empty database, tables that were created by hand are not adopted)
+
+CREATE TABLE client (
+    id INT NOT NULL AUTO_INCREMENT,
+    firstname VARCHAR(255) NOT NULL,
+    lastname VARCHAR(255) NOT NULL,
+    phone VARCHAR(64) NOT NULL,
+    PRIMARY KEY (id)
+) ENGINE = InnoDB;
+
+CREATE TABLE material_type (
+    id INT NOT NULL AUTO_INCREMENT,
+    name VARCHAR(255) NOT NULL,
+    description TEXT NOT NULL,
+    PRIMARY KEY (id)
+) ENGINE = InnoDB;
+
+CREATE TABLE material (
+    id INT NOT NULL AUTO_INCREMENT,
+    name VARCHAR(255) NOT NULL,
+    description TEXT NOT NULL,
+    type_id INT NOT NULL,
+    amount INT NOT NULL,
+    costs FLOAT NOT NULL,
+    threshold_value INT NOT NULL,
+    PRIMARY KEY (id)
+) ENGINE = InnoDB;
+
+CREATE TABLE project (
+    id INT NOT NULL AUTO_INCREMENT,
+    name VARCHAR(255) NOT NULL,
+    client_id INT NOT NULL,
+    description TEXT NOT NULL,
+    startpoint FLOAT NOT NULL,
+    endpoint FLOAT NOT NULL,
+    estimated_costs FLOAT NOT NULL,
+    estimated_hours FLOAT NOT NULL,
+    costs FLOAT NOT NULL,
+    PRIMARY KEY (id)
+) ENGINE = InnoDB;
+
+CREATE TABLE project_material (
+    id INT NOT NULL AUTO_INCREMENT,
+    project_id INT NOT NULL,
+    material_id INT NOT NULL,
+    amount INT NOT NULL,
+    PRIMARY KEY (id)
+) ENGINE = InnoDB;
+
+-- The references are added separately so the tables can be created in any order
+ALTER TABLE material
+    ADD CONSTRAINT fk_material_type FOREIGN KEY (type_id) REFERENCES material_type (id);
+
+ALTER TABLE project
+    ADD CONSTRAINT fk_project_client FOREIGN KEY (client_id) REFERENCES client (id);
+
+ALTER TABLE project_material
+    ADD CONSTRAINT fk_project_material_project FOREIGN KEY (project_id) REFERENCES project (id),
+    ADD CONSTRAINT fk_project_material_material FOREIGN KEY (material_id) REFERENCES material (id);
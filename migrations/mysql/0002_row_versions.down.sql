@@ -0,0 +1,5 @@
+ALTER TABLE project_material DROP COLUMN version;
+ALTER TABLE project DROP COLUMN version;
+ALTER TABLE material DROP COLUMN version;
+ALTER TABLE material_type DROP COLUMN version;
+ALTER TABLE client DROP COLUMN version;
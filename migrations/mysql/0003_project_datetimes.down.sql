@@ -0,0 +1,8 @@
+ALTER TABLE project ADD COLUMN startpoint_seconds FLOAT NULL, ADD COLUMN endpoint_seconds FLOAT NULL;
+UPDATE project SET
+    startpoint_seconds = TIMESTAMPDIFF(SECOND, '1970-01-01 00:00:00', startpoint),
+    endpoint_seconds = TIMESTAMPDIFF(SECOND, '1970-01-01 00:00:00', endpoint);
+ALTER TABLE project DROP COLUMN startpoint, DROP COLUMN endpoint;
+ALTER TABLE project
+    CHANGE COLUMN startpoint_seconds startpoint FLOAT NOT NULL,
+    CHANGE COLUMN endpoint_seconds endpoint FLOAT NOT NULL;
@@ -0,0 +1,10 @@
+-- The start and end of the projects as UTC timestamps (The old values are seconds since 1970)
+
+ALTER TABLE project ADD COLUMN startpoint_at DATETIME NULL, ADD COLUMN endpoint_at DATETIME NULL;
+UPDATE project SET
+    startpoint_at = DATE_ADD('1970-01-01 00:00:00', INTERVAL ROUND(startpoint) SECOND),
+    endpoint_at = DATE_ADD('1970-01-01 00:00:00', INTERVAL ROUND(endpoint) SECOND);
+ALTER TABLE project DROP COLUMN startpoint, DROP COLUMN endpoint;
+ALTER TABLE project
+    CHANGE COLUMN startpoint_at startpoint DATETIME NOT NULL,
+    CHANGE COLUMN endpoint_at endpoint DATETIME NOT NULL;
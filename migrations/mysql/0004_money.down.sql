@@ -0,0 +1,8 @@
+ALTER TABLE project
+    DROP COLUMN costs_currency,
+    MODIFY COLUMN costs FLOAT NOT NULL,
+    DROP COLUMN estimated_costs_currency,
+    MODIFY COLUMN estimated_costs FLOAT NOT NULL;
+ALTER TABLE material
+    DROP COLUMN costs_currency,
+    MODIFY COLUMN costs FLOAT NOT NULL;
@@ -0,0 +1,10 @@
+-- Costs as exact decimal amounts with their ISO 4217 currency (The existing costs are rounded to cents in EUR)
+
+ALTER TABLE material
+    MODIFY COLUMN costs DECIMAL(12, 2) NOT NULL,
+    ADD COLUMN costs_currency CHAR(3) NOT NULL DEFAULT 'EUR';
+ALTER TABLE project
+    MODIFY COLUMN estimated_costs DECIMAL(12, 2) NOT NULL,
+    ADD COLUMN estimated_costs_currency CHAR(3) NOT NULL DEFAULT 'EUR',
+    MODIFY COLUMN costs DECIMAL(12, 2) NOT NULL,
+    ADD COLUMN costs_currency CHAR(3) NOT NULL DEFAULT 'EUR';
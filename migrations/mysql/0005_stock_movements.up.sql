@@ -0,0 +1,15 @@
+-- The ledger of the stock, every change of material.amount is booked as a movement
+
+CREATE TABLE stock_movement (
+    id INT NOT NULL AUTO_INCREMENT,
+    material_id INT NOT NULL,
+    kind VARCHAR(16) NOT NULL,
+    quantity INT NOT NULL,
+    reason VARCHAR(255) NOT NULL,
+    user_id VARCHAR(255) NOT NULL,
+    created_at DATETIME NOT NULL,
+    amount_after INT NOT NULL,
+    PRIMARY KEY (id),
+    INDEX stock_movement_material (material_id, created_at),
+    CONSTRAINT fk_stock_movement_material FOREIGN KEY (material_id) REFERENCES material (id)
+) ENGINE = InnoDB;
@@ -0,0 +1,6 @@
+DROP TABLE purchase_order_line;
+DROP TABLE purchase_order;
+ALTER TABLE material
+    DROP FOREIGN KEY fk_material_supplier,
+    DROP COLUMN preferred_supplier_id;
+DROP TABLE supplier;
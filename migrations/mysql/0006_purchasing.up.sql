@@ -0,0 +1,40 @@
+-- Suppliers and the purchase orders of materials (A material can name the supplier it is usually ordered from)
+
+CREATE TABLE supplier (
+    id INT NOT NULL AUTO_INCREMENT,
+    name VARCHAR(255) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    phone VARCHAR(64) NOT NULL,
+    version INT NOT NULL DEFAULT 1,
+    PRIMARY KEY (id)
+) ENGINE = InnoDB;
+
+ALTER TABLE material
+    ADD COLUMN preferred_supplier_id INT NULL,
+    ADD CONSTRAINT fk_material_supplier FOREIGN KEY (preferred_supplier_id) REFERENCES supplier (id);
+
+CREATE TABLE purchase_order (
+    id INT NOT NULL AUTO_INCREMENT,
+    supplier_id INT NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    note TEXT NOT NULL,
+    created_at DATETIME NOT NULL,
+    version INT NOT NULL DEFAULT 1,
+    PRIMARY KEY (id),
+    INDEX purchase_order_status (status),
+    CONSTRAINT fk_purchase_order_supplier FOREIGN KEY (supplier_id) REFERENCES supplier (id)
+) ENGINE = InnoDB;
+
+CREATE TABLE purchase_order_line (
+    id INT NOT NULL AUTO_INCREMENT,
+    purchase_order_id INT NOT NULL,
+    material_id INT NOT NULL,
+    quantity INT NOT NULL,
+    received_quantity INT NOT NULL DEFAULT 0,
+    unit_price DECIMAL(12, 2) NOT NULL,
+    unit_price_currency CHAR(3) NOT NULL DEFAULT 'EUR',
+    version INT NOT NULL DEFAULT 1,
+    PRIMARY KEY (id),
+    CONSTRAINT fk_purchase_order_line_order FOREIGN KEY (purchase_order_id) REFERENCES purchase_order (id),
+    CONSTRAINT fk_purchase_order_line_material FOREIGN KEY (material_id) REFERENCES material (id)
+) ENGINE = InnoDB;
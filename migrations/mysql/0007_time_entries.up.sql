@@ -0,0 +1,17 @@
+-- The hours worked on the projects, an entry without ended_at is a running timer
+
+CREATE TABLE time_entry (
+    id INT NOT NULL AUTO_INCREMENT,
+    project_id INT NOT NULL,
+    user_id VARCHAR(255) NOT NULL,
+    started_at DATETIME NOT NULL,
+    ended_at DATETIME NULL,
+    minutes INT NOT NULL DEFAULT 0,
+    note TEXT NOT NULL,
+    billable BOOLEAN NOT NULL DEFAULT TRUE,
+    version INT NOT NULL DEFAULT 1,
+    PRIMARY KEY (id),
+    INDEX time_entry_project (project_id, started_at),
+    INDEX time_entry_user (user_id, ended_at),
+    CONSTRAINT fk_time_entry_project FOREIGN KEY (project_id) REFERENCES project (id)
+) ENGINE = InnoDB;
@@ -0,0 +1,4 @@
+DROP TABLE project_transition;
+ALTER TABLE project
+    DROP INDEX project_status,
+    DROP COLUMN status;
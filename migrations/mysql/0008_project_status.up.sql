@@ -0,0 +1,18 @@
+-- The lifecycle of the projects and the history of their transitions (Existing projects start as inquiries)
+
+ALTER TABLE project
+    ADD COLUMN status VARCHAR(32) NOT NULL DEFAULT 'inquiry',
+    ADD INDEX project_status (status);
+
+CREATE TABLE project_transition (
+    id INT NOT NULL AUTO_INCREMENT,
+    project_id INT NOT NULL,
+    from_status VARCHAR(32) NOT NULL,
+    to_status VARCHAR(32) NOT NULL,
+    user_id VARCHAR(255) NOT NULL,
+    note TEXT NOT NULL,
+    created_at DATETIME NOT NULL,
+    PRIMARY KEY (id),
+    INDEX project_transition_project (project_id, created_at),
+    CONSTRAINT fk_project_transition_project FOREIGN KEY (project_id) REFERENCES project (id)
+) ENGINE = InnoDB;
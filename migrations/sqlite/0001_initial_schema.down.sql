@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS project_material;
+DROP TABLE IF EXISTS project;
+DROP TABLE IF EXISTS material;
+DROP TABLE IF EXISTS material_type;
+DROP TABLE IF EXISTS client;
@@ -0,0 +1,43 @@
+-- Tables of the workshop management database
+
+CREATE TABLE client (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    firstname TEXT NOT NULL,
+    lastname TEXT NOT NULL,
+    phone TEXT NOT NULL
+);
+
+CREATE TABLE material_type (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL
+);
+
+CREATE TABLE material (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    type_id INTEGER NOT NULL REFERENCES material_type (id),
+    amount INTEGER NOT NULL,
+    costs REAL NOT NULL,
+    threshold_value INTEGER NOT NULL
+);
+
+CREATE TABLE project (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    client_id INTEGER NOT NULL REFERENCES client (id),
+    description TEXT NOT NULL,
+    startpoint REAL NOT NULL,
+    endpoint REAL NOT NULL,
+    estimated_costs REAL NOT NULL,
+    estimated_hours REAL NOT NULL,
+    costs REAL NOT NULL
+);
+
+CREATE TABLE project_material (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES project (id),
+    material_id INTEGER NOT NULL REFERENCES material (id),
+    amount INTEGER NOT NULL
+);
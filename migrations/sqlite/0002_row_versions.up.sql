@@ -0,0 +1,7 @@
+-- The row version of every table (Counts up with every update, used for the ETags)
+
+ALTER TABLE client ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE material_type ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE material ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE project ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE project_material ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,9 @@
+ALTER TABLE project ADD COLUMN startpoint_seconds REAL NOT NULL DEFAULT 0;
+ALTER TABLE project ADD COLUMN endpoint_seconds REAL NOT NULL DEFAULT 0;
+UPDATE project SET
+    startpoint_seconds = CAST(strftime('%s', startpoint) AS REAL),
+    endpoint_seconds = CAST(strftime('%s', endpoint) AS REAL);
+ALTER TABLE project DROP COLUMN startpoint;
+ALTER TABLE project DROP COLUMN endpoint;
+ALTER TABLE project RENAME COLUMN startpoint_seconds TO startpoint;
+ALTER TABLE project RENAME COLUMN endpoint_seconds TO endpoint;
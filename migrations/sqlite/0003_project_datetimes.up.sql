@@ -0,0 +1,11 @@
+-- The start and end of the projects as UTC timestamps (The old values are seconds since 1970)
+
+ALTER TABLE project ADD COLUMN startpoint_at TEXT NOT NULL DEFAULT '';
+ALTER TABLE project ADD COLUMN endpoint_at TEXT NOT NULL DEFAULT '';
+UPDATE project SET
+    startpoint_at = datetime(CAST(ROUND(startpoint) AS INTEGER), 'unixepoch'),
+    endpoint_at = datetime(CAST(ROUND(endpoint) AS INTEGER), 'unixepoch');
+ALTER TABLE project DROP COLUMN startpoint;
+ALTER TABLE project DROP COLUMN endpoint;
+ALTER TABLE project RENAME COLUMN startpoint_at TO startpoint;
+ALTER TABLE project RENAME COLUMN endpoint_at TO endpoint;
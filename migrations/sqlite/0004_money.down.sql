@@ -0,0 +1,15 @@
+ALTER TABLE project DROP COLUMN costs_currency;
+ALTER TABLE project DROP COLUMN estimated_costs_currency;
+ALTER TABLE project ADD COLUMN estimated_costs_real REAL NOT NULL DEFAULT 0;
+ALTER TABLE project ADD COLUMN costs_real REAL NOT NULL DEFAULT 0;
+UPDATE project SET estimated_costs_real = estimated_costs, costs_real = costs;
+ALTER TABLE project DROP COLUMN estimated_costs;
+ALTER TABLE project DROP COLUMN costs;
+ALTER TABLE project RENAME COLUMN estimated_costs_real TO estimated_costs;
+ALTER TABLE project RENAME COLUMN costs_real TO costs;
+
+ALTER TABLE material DROP COLUMN costs_currency;
+ALTER TABLE material ADD COLUMN costs_real REAL NOT NULL DEFAULT 0;
+UPDATE material SET costs_real = costs;
+ALTER TABLE material DROP COLUMN costs;
+ALTER TABLE material RENAME COLUMN costs_real TO costs;
@@ -0,0 +1,17 @@
+-- Costs as exact decimal amounts with their ISO 4217 currency (The existing costs are rounded to cents in EUR)
+
+ALTER TABLE material ADD COLUMN costs_decimal DECIMAL(12, 2) NOT NULL DEFAULT 0;
+UPDATE material SET costs_decimal = ROUND(costs, 2);
+ALTER TABLE material DROP COLUMN costs;
+ALTER TABLE material RENAME COLUMN costs_decimal TO costs;
+ALTER TABLE material ADD COLUMN costs_currency TEXT NOT NULL DEFAULT 'EUR';
+
+ALTER TABLE project ADD COLUMN estimated_costs_decimal DECIMAL(12, 2) NOT NULL DEFAULT 0;
+ALTER TABLE project ADD COLUMN costs_decimal DECIMAL(12, 2) NOT NULL DEFAULT 0;
+UPDATE project SET estimated_costs_decimal = ROUND(estimated_costs, 2), costs_decimal = ROUND(costs, 2);
+ALTER TABLE project DROP COLUMN estimated_costs;
+ALTER TABLE project DROP COLUMN costs;
+ALTER TABLE project RENAME COLUMN estimated_costs_decimal TO estimated_costs;
+ALTER TABLE project RENAME COLUMN costs_decimal TO costs;
+ALTER TABLE project ADD COLUMN estimated_costs_currency TEXT NOT NULL DEFAULT 'EUR';
+ALTER TABLE project ADD COLUMN costs_currency TEXT NOT NULL DEFAULT 'EUR';
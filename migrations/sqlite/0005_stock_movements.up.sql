@@ -0,0 +1,14 @@
+-- The ledger of the stock, every change of material.amount is booked as a movement
+
+CREATE TABLE stock_movement (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    material_id INTEGER NOT NULL REFERENCES material (id),
+    kind TEXT NOT NULL,
+    quantity INTEGER NOT NULL,
+    reason TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    amount_after INTEGER NOT NULL
+);
+
+CREATE INDEX stock_movement_material ON stock_movement (material_id, created_at);
@@ -0,0 +1,4 @@
+DROP TABLE purchase_order_line;
+DROP TABLE purchase_order;
+ALTER TABLE material DROP COLUMN preferred_supplier_id;
+DROP TABLE supplier;
@@ -0,0 +1,33 @@
+-- Suppliers and the purchase orders of materials (A material can name the supplier it is usually ordered from)
+
+CREATE TABLE supplier (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    email TEXT NOT NULL,
+    phone TEXT NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+ALTER TABLE material ADD COLUMN preferred_supplier_id INTEGER REFERENCES supplier (id);
+
+CREATE TABLE purchase_order (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    supplier_id INTEGER NOT NULL REFERENCES supplier (id),
+    status TEXT NOT NULL,
+    note TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE INDEX purchase_order_status ON purchase_order (status);
+
+CREATE TABLE purchase_order_line (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    purchase_order_id INTEGER NOT NULL REFERENCES purchase_order (id),
+    material_id INTEGER NOT NULL REFERENCES material (id),
+    quantity INTEGER NOT NULL,
+    received_quantity INTEGER NOT NULL DEFAULT 0,
+    unit_price DECIMAL(12, 2) NOT NULL,
+    unit_price_currency TEXT NOT NULL DEFAULT 'EUR',
+    version INTEGER NOT NULL DEFAULT 1
+);
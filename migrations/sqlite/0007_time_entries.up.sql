@@ -0,0 +1,17 @@
+-- The hours worked on the projects, an entry without ended_at is a running timer
+
+CREATE TABLE time_entry (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES project (id),
+    user_id TEXT NOT NULL,
+    started_at TEXT NOT NULL,
+    ended_at TEXT,
+    minutes INTEGER NOT NULL DEFAULT 0,
+    note TEXT NOT NULL,
+    billable INTEGER NOT NULL DEFAULT 1,
+    version INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE INDEX time_entry_project ON time_entry (project_id, started_at);
+
+CREATE INDEX time_entry_user ON time_entry (user_id, ended_at);
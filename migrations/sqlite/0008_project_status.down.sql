@@ -0,0 +1,3 @@
+DROP TABLE project_transition;
+DROP INDEX project_status;
+ALTER TABLE project DROP COLUMN status;
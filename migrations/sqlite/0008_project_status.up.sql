@@ -0,0 +1,17 @@
+-- The lifecycle of the projects and the history of their transitions (Existing projects start as inquiries)
+
+ALTER TABLE project ADD COLUMN status TEXT NOT NULL DEFAULT 'inquiry';
+
+CREATE INDEX project_status ON project (status);
+
+CREATE TABLE project_transition (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES project (id),
+    from_status TEXT NOT NULL,
+    to_status TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    note TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX project_transition_project ON project_transition (project_id, created_at);
@@ -12,51 +12,77 @@ mod tests;
 
 // Import the necessary modules
 use actix_cors::Cors;
-use actix_web::{middleware, App, HttpServer};
+use actix_web::{middleware, web::Data, App, HttpServer};
 
 // Import the controller module
+use clap::Parser;
+use utils::auth::{self, Authorizer};
+use utils::config::{Args, Command, Config};
 use utils::controller;
+use utils::rate_limit::{self, RateLimiter};
+use utils::repository::Repository;
+use utils::request_id;
+use utils::store::migrations;
 
 #[actix_rt::main] // Macro to start the actix runtime
 async fn main() -> std::io::Result<()> {
-    let port = "8580".to_string();
+    // Load the configuration (Refuse to start with an invalid one)
+    let args = Args::parse();
+    let config = match Config::load(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    // Create the repository and its connection pool once so every worker shares it
+    let repo = match Repository::new(&config) {
+        Ok(repo) => Data::new(repo),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    // Either run the migrate command or make sure the schema matches before serving
+    let result = match &args.command {
+        Some(Command::Migrate { action }) => migrations::run(repo.store(), action),
+        None => migrations::check(repo.store()),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    if args.command.is_some() {
+        return Ok(());
+    }
 
-    // Create a new HttpServer and bind it to the specified port
-    HttpServer::new(|| {
+    // The token verification and the role table are shared by all workers as well
+    let authorizer = Data::new(Authorizer::new(&config));
+    // One set of buckets for all workers, otherwise every worker would allow the full limit
+    let rate_limiter = Data::new(RateLimiter::new(&config.rate_limit));
+
+    // Create a new HttpServer and bind it to the configured address
+    HttpServer::new(move || {
         App::new()
+            // Share the repository with the controllers
+            .app_data(repo.clone())
+            .app_data(authorizer.clone())
+            .app_data(rate_limiter.clone())
+            // Check the token and the permission of every route
+            .wrap(middleware::from_fn(auth::middleware))
+            // Limit the requests per user or client IP (Runs before the token check, so rejected requests count as well)
+            .wrap(middleware::from_fn(rate_limit::middleware))
+            // Give every request an id (Sent back as X-Request-Id and in every error body)
+            .wrap(middleware::from_fn(request_id::middleware))
             // Enable logger
             .wrap(middleware::Logger::default())
             // Enable CORS
             .wrap(Cors::permissive())
             // Define the routes for the server
-            .service(controller::get_project)
-            .service(controller::get_project_by_id)
-            .service(controller::create_project)
-            .service(controller::update_project)
-            .service(controller::delete_project)
-            .service(controller::get_material_types)
-            .service(controller::get_material_type_by_id)
-            .service(controller::create_material_type)
-            .service(controller::update_material_type)
-            .service(controller::delete_material_type)
-            .service(controller::get_materials)
-            .service(controller::get_material_by_id)
-            .service(controller::create_material)
-            .service(controller::update_material)
-            .service(controller::delete_material)
-            .service(controller::get_project_materials)
-            .service(controller::get_project_material_by_id)
-            .service(controller::create_project_material)
-            .service(controller::update_project_material)
-            .service(controller::delete_project_material)
-            .service(controller::get_clients)
-            .service(controller::get_client_by_id)
-            .service(controller::create_client)
-            .service(controller::update_client)
-            .service(controller::delete_client)
+            .configure(controller::configure)
     })
-    // Bind the server to the specified port
-    .bind(format!("0.0.0.0:{port}", port = port))?
+    // Bind the server to the configured address
+    .bind(config.bind_address())?
     .run()
     .await
 }
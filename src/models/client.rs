@@ -13,4 +13,28 @@ pub struct Client {
     pub firstname: String,
     pub lastname: String,
     pub phone: String,
+    // Set by the database, counts up with every update (Sent as the ETag)
+    #[serde(default)]
+    pub version: i32,
+}
+
+// A new client (The id is assigned by the database)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewClient {
+    pub firstname: String,
+    pub lastname: String,
+    pub phone: String,
+}
+
+impl NewClient {
+    // The stored client with the assigned id
+    pub fn with_id(self, id: i32) -> Client {
+        Client {
+            id,
+            firstname: self.firstname,
+            lastname: self.lastname,
+            phone: self.phone,
+            version: 1,
+        }
+    }
 }
@@ -0,0 +1,85 @@
+/*
+
+    Description: This file contains the models of the costing reports. The costs of a project are computed from its
+                 project materials and logged hours and compared with its estimates (See utils/costing.rs).
+
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::money::Money;
+
+// A project material with the costs of its material
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct MaterialCosts {
+    pub project_material_id: i32,
+    pub material_id: i32,
+    pub name: String,
+    pub amount: i32,
+    // The costs of the material for one piece
+    pub unit_costs: Money,
+    pub costs: Money,
+}
+
+// The logged hours times the labour rate of the configuration
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct LabourCosts {
+    pub hours: f64,
+    pub rate: Money,
+    pub costs: Money,
+}
+
+// Actual against estimated costs (A positive difference is over the budget)
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct CostVariance {
+    pub estimated: Money,
+    pub actual: Money,
+    pub difference: Money,
+    // The difference in percent of the estimate (None if nothing was estimated)
+    pub percent: Option<f64>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct HoursVariance {
+    pub estimated: f64,
+    pub logged: f64,
+    pub difference: f64,
+    pub percent: Option<f64>,
+}
+
+// GET /Projects/{id}/Costing
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ProjectCosting {
+    pub project_id: i32,
+    pub name: String,
+    pub materials: Vec<MaterialCosts>,
+    pub material_costs: Money,
+    pub labour: LabourCosts,
+    pub total_costs: Money,
+    // The costs typed into the project
+    pub recorded_costs: Money,
+    pub cost_variance: CostVariance,
+    pub hours_variance: HoursVariance,
+}
+
+// One row of the budget variance report
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ProjectVariance {
+    pub project_id: i32,
+    pub name: String,
+    pub cost_variance: CostVariance,
+    pub hours_variance: HoursVariance,
+}
+
+// GET /Reports/BudgetVariance
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct BudgetVariance {
+    pub projects: Vec<ProjectVariance>,
+    // The sums of the listed projects, one per currency
+    pub totals: Vec<CostVariance>,
+    // Projects with costs in another currency than their estimate (They cannot be compared)
+    pub mixed_currency: Vec<i32>,
+    // The number of all matching projects (Sent in the X-Total-Count header)
+    #[serde(skip)]
+    pub total: u64,
+}
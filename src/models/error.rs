@@ -2,16 +2,56 @@
 
     Author: Justin Kosten
     Description: This file contains the model for the Error object. This object is used to represent an error in the actix-web server.
+                 Every error is answered with its HTTP status and a JSON body the frontend can match on.
 
 */
 
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
 use serde::{Deserialize, Serialize};
 
+use crate::utils::request_id;
+
+// The machine readable error codes that are not derived from the status code
+pub const DUPLICATE_KEY: &str = "duplicate_key";
+pub const FOREIGN_KEY_VIOLATION: &str = "foreign_key_violation";
+pub const REFERENCED_BY_OTHERS: &str = "referenced_by_others";
+pub const INVALID_TOKEN: &str = "invalid_token";
+pub const ID_MISMATCH: &str = "id_mismatch";
+pub const VALIDATION_FAILED: &str = "validation_failed";
+pub const INSUFFICIENT_STOCK: &str = "insufficient_stock";
+pub const INVALID_STATUS: &str = "invalid_status";
+pub const CURRENCY_MISMATCH: &str = "currency_mismatch";
+pub const TIMER_RUNNING: &str = "timer_running";
+pub const TRANSITION_BLOCKED: &str = "transition_blocked";
+
 // Define the Error struct
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Error {
     pub message: String,
     pub code: i32,
+    // Stable machine readable code (e.g. not_found, duplicate_key)
+    pub kind: String,
+    // The invalid fields of a request body (Only set by the validation)
+    #[serde(default)]
+    pub fields: Vec<FieldError>,
+}
+
+// One invalid field and why it is invalid
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+// The JSON body of an error response
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ErrorBody {
+    pub code: String,
+    pub status: u16,
+    pub message: String,
+    pub request_id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
 }
 
 // Implement new for the Error struct so that we can create a new error
@@ -19,11 +59,51 @@ impl Error {
     pub fn new(message: String, code: i32) -> Error {
         Error {
             message,
-            code
+            code,
+            kind: default_kind(code).to_string(),
+            fields: Vec::new(),
+        }
+    }
+
+    // Create an error with a more specific machine readable code than the status code gives
+    pub fn with_kind(message: String, code: i32, kind: &str) -> Error {
+        Error {
+            message,
+            code,
+            kind: kind.to_string(),
+            fields: Vec::new(),
+        }
+    }
+
+    // 422 with the list of invalid fields
+    pub fn with_fields(message: String, fields: Vec<FieldError>) -> Error {
+        Error {
+            message,
+            code: 422,
+            kind: VALIDATION_FAILED.to_string(),
+            fields,
         }
     }
 }
 
+// The machine readable code of every status code we answer with
+fn default_kind(code: i32) -> &'static str {
+    match code {
+        400 => "bad_request",
+        401 => "unauthorized",
+        403 => "forbidden",
+        404 => "not_found",
+        409 => "conflict",
+        412 => "precondition_failed",
+        413 => "payload_too_large",
+        422 => "unprocessable_entity",
+        429 => "too_many_requests",
+        502 => "bad_gateway",
+        503 => "service_unavailable",
+        _ => "internal_error",
+    }
+}
+
 // Implement the Display trait for the Error struct so that we can print the error message
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -34,9 +114,33 @@ impl std::fmt::Display for Error {
 // Implement the Default behavior for the Error struct
 impl Default for Error {
     fn default() -> Error {
-        Error {
-            message: "An error occurred".to_string(),
-            code: 500
+        Error::new("An error occurred".to_string(), 500)
+    }
+}
+
+// Turn the error into a response with the matching status code
+impl ResponseError for Error {
+    fn status_code(&self) -> StatusCode {
+        u16::try_from(self.code)
+            .ok()
+            .and_then(|code| StatusCode::from_u16(code).ok())
+            .filter(|status| status.is_client_error() || status.is_server_error())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        let request_id = request_id::current().unwrap_or_default();
+        // Server errors are logged with the request id so they can be found from a bug report
+        if status.is_server_error() {
+            eprintln!("[{}] {}", request_id, self);
         }
+        HttpResponse::build(status).json(ErrorBody {
+            code: self.kind.clone(),
+            status: status.as_u16(),
+            message: self.message.clone(),
+            request_id,
+            fields: self.fields.clone(),
+        })
     }
-}
\ No newline at end of file
+}
@@ -7,6 +7,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::models::money::Money;
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Material {
     pub id: i32,
@@ -14,6 +16,49 @@ pub struct Material {
     pub description: String,
     pub type_id: i32,
     pub amount: i32,
-    pub costs: f32,
+    pub costs: Money,
+    pub threshold_value: i32,
+    // The supplier it is reordered from (None if there is none yet)
+    #[serde(default)]
+    pub preferred_supplier_id: Option<i32>,
+    // Set by the database, counts up with every update (Sent as the ETag)
+    #[serde(default)]
+    pub version: i32,
+}
+
+impl Material {
+    // Below the threshold the storekeeper set (A threshold of 0 only alerts if the material was backordered)
+    pub fn is_low_on_stock(&self) -> bool {
+        self.amount < self.threshold_value
+    }
+}
+
+// A new material (The id is assigned by the database)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewMaterial {
+    pub name: String,
+    pub description: String,
+    pub type_id: i32,
+    pub amount: i32,
+    pub costs: Money,
     pub threshold_value: i32,
-}
\ No newline at end of file
+    #[serde(default)]
+    pub preferred_supplier_id: Option<i32>,
+}
+
+impl NewMaterial {
+    // The stored material with the assigned id
+    pub fn with_id(self, id: i32) -> Material {
+        Material {
+            id,
+            name: self.name,
+            description: self.description,
+            type_id: self.type_id,
+            amount: self.amount,
+            costs: self.costs,
+            threshold_value: self.threshold_value,
+            preferred_supplier_id: self.preferred_supplier_id,
+            version: 1,
+        }
+    }
+}
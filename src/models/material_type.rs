@@ -12,4 +12,26 @@ pub struct MaterialType {
     pub id: i32,
     pub name: String,
     pub description: String,
+    // Set by the database, counts up with every update (Sent as the ETag)
+    #[serde(default)]
+    pub version: i32,
+}
+
+// A new material type (The id is assigned by the database)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewMaterialType {
+    pub name: String,
+    pub description: String,
+}
+
+impl NewMaterialType {
+    // The stored material type with the assigned id
+    pub fn with_id(self, id: i32) -> MaterialType {
+        MaterialType {
+            id,
+            name: self.name,
+            description: self.description,
+            version: 1,
+        }
+    }
 }
@@ -1,6 +1,17 @@
 pub mod client;
+pub mod costing;
 pub mod error;
 pub mod material;
+pub mod money;
 pub mod project;
 pub mod project_material;
-pub mod material_type;
\ No newline at end of file
+pub mod project_transition;
+pub mod purchase_order;
+pub mod purchase_order_line;
+pub mod material_type;
+pub mod search;
+pub mod stock_alert;
+pub mod stock_movement;
+pub mod supplier;
+pub mod time_entry;
+pub mod timestamp;
\ No newline at end of file
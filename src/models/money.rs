@@ -0,0 +1,180 @@
+/*
+
+    Description: This file contains the money type of the costs. An amount is kept as whole cents with its ISO 4217
+                 currency, so it is never rounded like a float. In JSON it is a string like "12.50 EUR" (Numbers
+                 are still read for the old clients).
+
+*/
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+// Used if an amount is sent without a currency
+pub const DEFAULT_CURRENCY: Currency = Currency(*b"EUR");
+// Largest amount of the DECIMAL(12, 2) columns
+pub const MAX_CENTS: i64 = 999_999_999_999;
+
+// ISO 4217 code like EUR (Three capital letters)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.0).unwrap()
+    }
+}
+
+impl FromStr for Currency {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Currency, String> {
+        match text.as_bytes() {
+            code @ [_, _, _] if code.iter().all(u8::is_ascii_uppercase) => {
+                Ok(Currency([code[0], code[1], code[2]]))
+            }
+            _ => Err(format!("{} is not an ISO 4217 currency code", text)),
+        }
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Money {
+    pub cents: i64,
+    pub currency: Currency,
+}
+
+impl Money {
+    pub fn new(cents: i64, currency: Currency) -> Money {
+        Money { cents, currency }
+    }
+
+    // The amount without the currency, e.g. 12.50 (How the databases get it)
+    pub fn amount(&self) -> String {
+        let sign = if self.cents < 0 { "-" } else { "" };
+        let cents = self.cents.unsigned_abs();
+        format!("{}{}.{:02}", sign, cents / 100, cents % 100)
+    }
+
+    // The sum of two amounts (None if the currencies differ or it does not fit)
+    pub fn checked_add(&self, other: Money) -> Option<Money> {
+        if other.currency != self.currency {
+            return None;
+        }
+        Some(Money::new(
+            self.cents.checked_add(other.cents)?,
+            self.currency,
+        ))
+    }
+
+    pub fn checked_sub(&self, other: Money) -> Option<Money> {
+        self.checked_add(Money::new(other.cents.checked_neg()?, other.currency))
+    }
+
+    // The amount times a quantity, e.g. the costs of 4 boards
+    pub fn checked_mul(&self, quantity: i64) -> Option<Money> {
+        Some(Money::new(self.cents.checked_mul(quantity)?, self.currency))
+    }
+
+    // Old clients send the costs as numbers
+    pub fn from_f64(amount: f64, currency: Currency) -> Result<Money, String> {
+        let cents = (amount * 100.0).round();
+        if !cents.is_finite() || cents.abs() > i64::MAX as f64 {
+            return Err(format!("{} is not a valid amount", amount));
+        }
+        Ok(Money::new(cents as i64, currency))
+    }
+}
+
+// 12, 12.5, 12.50 or -3.00 in cents (At most two decimal places)
+pub fn parse_amount(text: &str) -> Result<i64, String> {
+    let invalid = || format!("{} is not a valid amount", text);
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    let (units, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if units.is_empty()
+        || fraction.len() > 2
+        || !units
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+        || (digits.contains('.') && fraction.is_empty())
+    {
+        return Err(invalid());
+    }
+    let fraction: i64 = format!("{:0<2}", fraction).parse().unwrap();
+    let cents = units
+        .parse::<i64>()
+        .ok()
+        .and_then(|units| units.checked_mul(100))
+        .and_then(|cents| cents.checked_add(fraction))
+        .ok_or_else(invalid)?;
+    Ok(if negative { -cents } else { cents })
+}
+
+// "12.50 EUR" or "12.50" (In the default currency)
+impl FromStr for Money {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Money, String> {
+        let text = text.trim();
+        let (amount, currency) = match text.split_once(' ') {
+            Some((amount, currency)) => (amount, currency.trim().parse()?),
+            None => (text, DEFAULT_CURRENCY),
+        };
+        Ok(Money::new(parse_amount(amount)?, currency))
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.amount(), self.currency)
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+        deserializer.deserialize_any(MoneyVisitor)
+    }
+}
+
+struct MoneyVisitor;
+
+impl de::Visitor<'_> for MoneyVisitor {
+    type Value = Money;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an amount like \"12.50 EUR\"")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Money, E> {
+        value.parse().map_err(E::custom)
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Money, E> {
+        Money::from_f64(value, DEFAULT_CURRENCY).map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Money, E> {
+        Money::from_f64(value as f64, DEFAULT_CURRENCY).map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Money, E> {
+        Money::from_f64(value as f64, DEFAULT_CURRENCY).map_err(E::custom)
+    }
+}
@@ -5,17 +5,136 @@
 
 */
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::models::money::Money;
+use crate::models::timestamp;
+
+// Where a project is in its lifecycle (Changed with POST /Projects/{id}/Transitions)
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectStatus {
+    // Asked for, nothing is offered yet
+    #[default]
+    Inquiry,
+    // An offer was sent to the client
+    Quoted,
+    Accepted,
+    InProgress,
+    OnHold,
+    Completed,
+    Invoiced,
+    Cancelled,
+}
+
+impl ProjectStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProjectStatus::Inquiry => "inquiry",
+            ProjectStatus::Quoted => "quoted",
+            ProjectStatus::Accepted => "accepted",
+            ProjectStatus::InProgress => "in_progress",
+            ProjectStatus::OnHold => "on_hold",
+            ProjectStatus::Completed => "completed",
+            ProjectStatus::Invoiced => "invoiced",
+            ProjectStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<ProjectStatus> {
+        match text {
+            "inquiry" => Some(ProjectStatus::Inquiry),
+            "quoted" => Some(ProjectStatus::Quoted),
+            "accepted" => Some(ProjectStatus::Accepted),
+            "in_progress" => Some(ProjectStatus::InProgress),
+            "on_hold" => Some(ProjectStatus::OnHold),
+            "completed" => Some(ProjectStatus::Completed),
+            "invoiced" => Some(ProjectStatus::Invoiced),
+            "cancelled" => Some(ProjectStatus::Cancelled),
+            _ => None,
+        }
+    }
+
+    // The transition graph (Invoiced and cancelled projects are final)
+    pub fn can_change_to(&self, next: ProjectStatus) -> bool {
+        use ProjectStatus::*;
+        matches!(
+            (self, next),
+            (Inquiry, Quoted)
+                | (Inquiry, Cancelled)
+                // A quote can be revised
+                | (Quoted, Inquiry)
+                | (Quoted, Accepted)
+                | (Quoted, Cancelled)
+                | (Accepted, InProgress)
+                | (Accepted, OnHold)
+                | (Accepted, Cancelled)
+                | (InProgress, OnHold)
+                | (InProgress, Completed)
+                | (InProgress, Cancelled)
+                | (OnHold, InProgress)
+                | (OnHold, Cancelled)
+                // A completed project can be reopened until it is invoiced
+                | (Completed, InProgress)
+                | (Completed, Invoiced)
+        )
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Project {
     pub id: i32,
     pub name: String,
     pub client_id: i32,
     pub description: String,
-    pub startpoint: f32,
-    pub endpoint: f32,
-    pub estimated_costs: f32,
+    // ISO-8601 in JSON, stored in UTC
+    #[serde(with = "timestamp")]
+    pub startpoint: DateTime<Utc>,
+    #[serde(with = "timestamp")]
+    pub endpoint: DateTime<Utc>,
+    pub estimated_costs: Money,
+    pub estimated_hours: f32,
+    pub costs: Money,
+    // Only changed by a transition (The status of a PUT or PATCH body is ignored)
+    #[serde(default)]
+    pub status: ProjectStatus,
+    // Set by the database, counts up with every update (Sent as the ETag)
+    #[serde(default)]
+    pub version: i32,
+}
+
+// A new project (The id is assigned by the database)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewProject {
+    pub name: String,
+    pub client_id: i32,
+    pub description: String,
+    // ISO-8601 in JSON, stored in UTC
+    #[serde(with = "timestamp")]
+    pub startpoint: DateTime<Utc>,
+    #[serde(with = "timestamp")]
+    pub endpoint: DateTime<Utc>,
+    pub estimated_costs: Money,
     pub estimated_hours: f32,
-    pub costs: f32,
+    pub costs: Money,
+}
+
+impl NewProject {
+    // The stored project with the assigned id (Every project starts as an inquiry)
+    pub fn with_id(self, id: i32) -> Project {
+        Project {
+            id,
+            name: self.name,
+            client_id: self.client_id,
+            description: self.description,
+            startpoint: self.startpoint,
+            endpoint: self.endpoint,
+            estimated_costs: self.estimated_costs,
+            estimated_hours: self.estimated_hours,
+            costs: self.costs,
+            status: ProjectStatus::Inquiry,
+            version: 1,
+        }
+    }
 }
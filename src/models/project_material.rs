@@ -13,4 +13,28 @@ pub struct ProjectMaterial {
     pub project_id: i32,
     pub material_id: i32,
     pub amount: i32,
+    // Set by the database, counts up with every update (Sent as the ETag)
+    #[serde(default)]
+    pub version: i32,
+}
+
+// A new project material (The id is assigned by the database)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewProjectMaterial {
+    pub project_id: i32,
+    pub material_id: i32,
+    pub amount: i32,
+}
+
+impl NewProjectMaterial {
+    // The stored project material with the assigned id
+    pub fn with_id(self, id: i32) -> ProjectMaterial {
+        ProjectMaterial {
+            id,
+            project_id: self.project_id,
+            material_id: self.material_id,
+            amount: self.amount,
+            version: 1,
+        }
+    }
 }
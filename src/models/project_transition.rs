@@ -0,0 +1,54 @@
+/*
+
+    Description: This file contains the model for the ProjectTransition object. Every change of the status of a
+                 project is kept, so the history tells who moved it when.
+
+*/
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::project::{Project, ProjectStatus};
+use crate::models::timestamp;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ProjectTransition {
+    pub id: i32,
+    pub project_id: i32,
+    pub from_status: ProjectStatus,
+    pub to_status: ProjectStatus,
+    // Keycloak user id of the user who moved the project
+    pub user_id: String,
+    pub note: String,
+    #[serde(with = "timestamp")]
+    pub created_at: DateTime<Utc>,
+}
+
+// POST /Projects/{id}/Transitions (The project is the one of the path)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Transition {
+    pub status: ProjectStatus,
+    #[serde(default)]
+    pub note: String,
+}
+
+impl Transition {
+    // The history row of moving the project with the assigned id
+    pub fn recorded(
+        self,
+        id: i32,
+        project: &Project,
+        user_id: &str,
+        created_at: DateTime<Utc>,
+    ) -> ProjectTransition {
+        ProjectTransition {
+            id,
+            project_id: project.id,
+            from_status: project.status,
+            to_status: self.status,
+            user_id: user_id.to_string(),
+            note: self.note,
+            created_at,
+        }
+    }
+}
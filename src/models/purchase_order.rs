@@ -0,0 +1,120 @@
+/*
+
+    Description: This file contains the model for the PurchaseOrder object. A purchase order is written as a draft,
+                 sent to its supplier and received line by line (See purchase_order_line.rs).
+
+*/
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::purchase_order_line::PurchaseOrderLine;
+use crate::models::timestamp;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PurchaseOrderStatus {
+    // Still being written, only drafts can change their lines
+    Draft,
+    // Ordered from the supplier
+    Sent,
+    // Some of the lines were delivered
+    PartiallyReceived,
+    // Everything was delivered
+    Received,
+    Cancelled,
+}
+
+impl PurchaseOrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PurchaseOrderStatus::Draft => "draft",
+            PurchaseOrderStatus::Sent => "sent",
+            PurchaseOrderStatus::PartiallyReceived => "partially_received",
+            PurchaseOrderStatus::Received => "received",
+            PurchaseOrderStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<PurchaseOrderStatus> {
+        match text {
+            "draft" => Some(PurchaseOrderStatus::Draft),
+            "sent" => Some(PurchaseOrderStatus::Sent),
+            "partially_received" => Some(PurchaseOrderStatus::PartiallyReceived),
+            "received" => Some(PurchaseOrderStatus::Received),
+            "cancelled" => Some(PurchaseOrderStatus::Cancelled),
+            _ => None,
+        }
+    }
+
+    // The changes a client can make (The received states are only reached by receiving the lines)
+    pub fn can_change_to(&self, next: PurchaseOrderStatus) -> bool {
+        use PurchaseOrderStatus::*;
+        *self == next
+            || matches!(
+                (self, next),
+                (Draft, Sent) | (Draft, Cancelled) | (Sent, Cancelled)
+            )
+    }
+
+    // Ordered and not completely delivered yet (Its lines can be received)
+    pub fn is_receivable(&self) -> bool {
+        matches!(
+            self,
+            PurchaseOrderStatus::Sent | PurchaseOrderStatus::PartiallyReceived
+        )
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PurchaseOrder {
+    pub id: i32,
+    pub supplier_id: i32,
+    pub status: PurchaseOrderStatus,
+    #[serde(default)]
+    pub note: String,
+    // Set when the order is created
+    #[serde(with = "timestamp")]
+    pub created_at: DateTime<Utc>,
+    // Set by the database, counts up with every update (Sent as the ETag)
+    #[serde(default)]
+    pub version: i32,
+}
+
+// A new purchase order, it always starts as a draft (The id is assigned by the database)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewPurchaseOrder {
+    pub supplier_id: i32,
+    #[serde(default)]
+    pub note: String,
+}
+
+impl NewPurchaseOrder {
+    // The stored purchase order with the assigned id
+    pub fn with_id(self, id: i32, created_at: DateTime<Utc>) -> PurchaseOrder {
+        PurchaseOrder {
+            id,
+            supplier_id: self.supplier_id,
+            status: PurchaseOrderStatus::Draft,
+            note: self.note,
+            created_at,
+            version: 1,
+        }
+    }
+}
+
+// A purchase order with its lines (As drafted by the reorder suggestion)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DraftedOrder {
+    #[serde(flatten)]
+    pub purchase_order: PurchaseOrder,
+    pub lines: Vec<PurchaseOrderLine>,
+}
+
+// The drafts of the reorder suggestion, one per supplier
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct Reorder {
+    pub purchase_orders: Vec<DraftedOrder>,
+    // Low on stock, but nobody to order them from (The ids of the materials)
+    pub without_supplier: Vec<i32>,
+}
@@ -0,0 +1,62 @@
+/*
+
+    Description: This file contains the model for the PurchaseOrderLine object. A line orders a quantity of one
+                 material, receiving it books the delivered quantity into the stock.
+
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::money::Money;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PurchaseOrderLine {
+    pub id: i32,
+    pub purchase_order_id: i32,
+    pub material_id: i32,
+    pub quantity: i32,
+    // Only changed by receiving the line
+    #[serde(default)]
+    pub received_quantity: i32,
+    pub unit_price: Money,
+    // Set by the database, counts up with every update (Sent as the ETag)
+    #[serde(default)]
+    pub version: i32,
+}
+
+impl PurchaseOrderLine {
+    // What is still to be delivered
+    pub fn open_quantity(&self) -> i32 {
+        self.quantity - self.received_quantity
+    }
+}
+
+// A new purchase order line (The id is assigned by the database)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewPurchaseOrderLine {
+    pub purchase_order_id: i32,
+    pub material_id: i32,
+    pub quantity: i32,
+    pub unit_price: Money,
+}
+
+impl NewPurchaseOrderLine {
+    // The stored purchase order line with the assigned id
+    pub fn with_id(self, id: i32) -> PurchaseOrderLine {
+        PurchaseOrderLine {
+            id,
+            purchase_order_id: self.purchase_order_id,
+            material_id: self.material_id,
+            quantity: self.quantity,
+            received_quantity: 0,
+            unit_price: self.unit_price,
+            version: 1,
+        }
+    }
+}
+
+// A delivery of a line (POST /PurchaseOrderLines/5/Receive)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Receipt {
+    pub quantity: i32,
+}
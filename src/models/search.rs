@@ -0,0 +1,31 @@
+/*
+
+    Description: This file contains the models for the search results. A hit is a client, project or material that
+                 matches the search text, the hits are grouped by their type and ordered by their score.
+
+*/
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    // client, project or material
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub id: i32,
+    // What the frontend shows (e.g. the name of the client and the phone number)
+    pub title: String,
+    pub subtitle: String,
+    // Higher is better
+    pub score: u32,
+    // The fields that matched the search text
+    pub matched: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct SearchResults {
+    pub query: String,
+    pub clients: Vec<SearchHit>,
+    pub projects: Vec<SearchHit>,
+    pub materials: Vec<SearchHit>,
+}
@@ -0,0 +1,42 @@
+/*
+
+    Description: This file contains the model for the StockAlert object. It is raised when a stock change takes a
+                 material below its threshold_value and sent to the configured notifiers.
+
+*/
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::material::Material;
+use crate::models::timestamp;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct StockAlert {
+    pub material_id: i32,
+    pub name: String,
+    pub amount: i32,
+    pub threshold_value: i32,
+    #[serde(with = "timestamp")]
+    pub raised_at: DateTime<Utc>,
+}
+
+impl StockAlert {
+    pub fn new(material: &Material, raised_at: DateTime<Utc>) -> StockAlert {
+        StockAlert {
+            material_id: material.id,
+            name: material.name.clone(),
+            amount: material.amount,
+            threshold_value: material.threshold_value,
+            raised_at,
+        }
+    }
+
+    // One line for the log and the subject of the mail
+    pub fn summary(&self) -> String {
+        format!(
+            "Low stock of {} (Material {}): {} left, threshold {}",
+            self.name, self.material_id, self.amount, self.threshold_value
+        )
+    }
+}
@@ -0,0 +1,142 @@
+/*
+
+    Description: This file contains the model for the StockMovement object. Every change of the stock of a material is
+                 booked as a movement, so the ledger tells where the material went.
+
+*/
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::timestamp;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MovementKind {
+    // Delivered by a supplier
+    Receive,
+    // Used up (e.g. for a project)
+    Consume,
+    // Correction after counting the stock
+    Adjust,
+    // Brought back unused
+    Return,
+}
+
+impl MovementKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MovementKind::Receive => "receive",
+            MovementKind::Consume => "consume",
+            MovementKind::Adjust => "adjust",
+            MovementKind::Return => "return",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<MovementKind> {
+        match text {
+            "receive" => Some(MovementKind::Receive),
+            "consume" => Some(MovementKind::Consume),
+            "adjust" => Some(MovementKind::Adjust),
+            "return" => Some(MovementKind::Return),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct StockMovement {
+    pub id: i32,
+    pub material_id: i32,
+    pub kind: MovementKind,
+    // As booked, positive except for an adjust that lowers the stock
+    pub quantity: i32,
+    pub reason: String,
+    // Keycloak user id of the user who booked it
+    pub user_id: String,
+    #[serde(with = "timestamp")]
+    pub created_at: DateTime<Utc>,
+    // The stock of the material after the movement
+    pub amount_after: i32,
+}
+
+// A new movement as the client sends it (The material is the one of the path)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewStockMovement {
+    pub kind: MovementKind,
+    pub quantity: i32,
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl NewStockMovement {
+    // How much the stock changes (A consume of 3 is -3)
+    pub fn change(&self) -> i32 {
+        match self.kind {
+            MovementKind::Consume => -self.quantity,
+            _ => self.quantity,
+        }
+    }
+
+    // Material taken for a project is consumed, material given back is returned
+    pub fn for_project(project_id: i32, change: i32) -> NewStockMovement {
+        NewStockMovement {
+            kind: if change < 0 {
+                MovementKind::Consume
+            } else {
+                MovementKind::Return
+            },
+            quantity: change.abs(),
+            reason: format!("Project {}", project_id),
+        }
+    }
+
+    // Delivered material of a purchase order is received
+    pub fn for_purchase_order(purchase_order_id: i32, quantity: i32) -> NewStockMovement {
+        NewStockMovement {
+            kind: MovementKind::Receive,
+            quantity,
+            reason: format!("Purchase order {}", purchase_order_id),
+        }
+    }
+
+    // The stored movement (The user comes from the token, the stock from the material)
+    pub fn booked(
+        self,
+        id: i32,
+        material_id: i32,
+        booking: &Booking,
+        amount_after: i32,
+    ) -> StockMovement {
+        StockMovement {
+            id,
+            material_id,
+            kind: self.kind,
+            quantity: self.quantity,
+            reason: self.reason,
+            user_id: booking.user_id.clone(),
+            created_at: booking.created_at,
+            amount_after,
+        }
+    }
+}
+
+// Who books the movements of a request and when
+#[derive(Debug, Clone)]
+pub struct Booking {
+    // Keycloak user id
+    pub user_id: String,
+    pub created_at: DateTime<Utc>,
+    // Material that is not in stock yet may be taken anyway (The stock becomes negative until it is received)
+    pub backorder: bool,
+}
+
+impl Booking {
+    pub fn new(user_id: String, backorder: bool) -> Booking {
+        Booking {
+            user_id,
+            created_at: timestamp::now(),
+            backorder,
+        }
+    }
+}
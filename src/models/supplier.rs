@@ -0,0 +1,41 @@
+/*
+
+    Description: This file contains the model for the Supplier object. This object is used to represent a supplier
+                 the materials are ordered from.
+
+*/
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Supplier {
+    pub id: i32,
+    pub name: String,
+    // Where the purchase orders are sent to (May be empty)
+    pub email: String,
+    pub phone: String,
+    // Set by the database, counts up with every update (Sent as the ETag)
+    #[serde(default)]
+    pub version: i32,
+}
+
+// A new supplier (The id is assigned by the database)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewSupplier {
+    pub name: String,
+    pub email: String,
+    pub phone: String,
+}
+
+impl NewSupplier {
+    // The stored supplier with the assigned id
+    pub fn with_id(self, id: i32) -> Supplier {
+        Supplier {
+            id,
+            name: self.name,
+            email: self.email,
+            phone: self.phone,
+            version: 1,
+        }
+    }
+}
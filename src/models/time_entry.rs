@@ -0,0 +1,170 @@
+/*
+
+    Description: This file contains the model for the TimeEntry object. A time entry is the work of one employee on a
+                 project, either submitted afterwards or recorded with a timer that is started and stopped.
+
+*/
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::timestamp;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TimeEntry {
+    pub id: i32,
+    pub project_id: i32,
+    // Keycloak user id of the employee (Taken from the token, it cannot be changed)
+    #[serde(default)]
+    pub user_id: String,
+    #[serde(with = "timestamp")]
+    pub started_at: DateTime<Utc>,
+    // None while the timer runs
+    #[serde(default, with = "timestamp::option")]
+    pub ended_at: Option<DateTime<Utc>>,
+    // Set from started_at and ended_at (0 while the timer runs)
+    #[serde(default)]
+    pub minutes: i32,
+    #[serde(default)]
+    pub note: String,
+    #[serde(default = "billable")]
+    pub billable: bool,
+    // Set by the database, counts up with every update (Sent as the ETag)
+    #[serde(default)]
+    pub version: i32,
+}
+
+impl TimeEntry {
+    pub fn is_running(&self) -> bool {
+        self.ended_at.is_none()
+    }
+
+    // Stop the entry at the time (The minutes are counted again)
+    pub fn finished(self, ended_at: Option<DateTime<Utc>>) -> TimeEntry {
+        TimeEntry {
+            minutes: ended_at.map_or(0, |ended_at| minutes(self.started_at, ended_at)),
+            ended_at,
+            ..self
+        }
+    }
+}
+
+// Work is billed to the client unless it is marked otherwise
+fn billable() -> bool {
+    true
+}
+
+// Whole minutes between the times (Rounded, a timer stopped after 20 seconds has 0 minutes)
+pub fn minutes(started_at: DateTime<Utc>, ended_at: DateTime<Utc>) -> i32 {
+    ((ended_at - started_at).num_seconds() as f64 / 60.0).round() as i32
+}
+
+// A submitted time entry, it has an end or a duration in minutes (The user comes from the token)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewTimeEntry {
+    pub project_id: i32,
+    #[serde(with = "timestamp")]
+    pub started_at: DateTime<Utc>,
+    #[serde(default, with = "timestamp::option")]
+    pub ended_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub duration: Option<i32>,
+    #[serde(default)]
+    pub note: String,
+    #[serde(default = "billable")]
+    pub billable: bool,
+}
+
+impl NewTimeEntry {
+    // The stored time entry with the assigned id (A duration sets the end)
+    pub fn with_id(self, id: i32, user_id: &str) -> TimeEntry {
+        let ended_at = self.ended_at.or_else(|| {
+            self.started_at
+                .checked_add_signed(Duration::minutes(self.duration? as i64))
+        });
+        TimeEntry {
+            id,
+            project_id: self.project_id,
+            user_id: user_id.to_string(),
+            started_at: self.started_at,
+            ended_at: None,
+            minutes: 0,
+            note: self.note,
+            billable: self.billable,
+            version: 1,
+        }
+        .finished(ended_at)
+    }
+}
+
+// POST /TimeEntries/Start (The timer starts now)
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Timer {
+    pub project_id: i32,
+    #[serde(default)]
+    pub note: String,
+    #[serde(default = "billable")]
+    pub billable: bool,
+}
+
+impl Timer {
+    pub fn started(self, id: i32, user_id: &str, started_at: DateTime<Utc>) -> TimeEntry {
+        TimeEntry {
+            id,
+            project_id: self.project_id,
+            user_id: user_id.to_string(),
+            started_at,
+            ended_at: None,
+            minutes: 0,
+            note: self.note,
+            billable: self.billable,
+            version: 1,
+        }
+    }
+}
+
+// The logged time of a project or an employee (Only stopped entries count)
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct TimeSummary {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    pub entries: i64,
+    pub minutes: i64,
+    pub billable_minutes: i64,
+    pub hours: f64,
+    pub billable_hours: f64,
+}
+
+impl TimeSummary {
+    pub fn new(
+        project_id: Option<i32>,
+        user_id: Option<String>,
+        entries: i64,
+        minutes: i64,
+        billable_minutes: i64,
+    ) -> TimeSummary {
+        TimeSummary {
+            project_id,
+            user_id,
+            entries,
+            minutes,
+            billable_minutes,
+            hours: hours(minutes),
+            billable_hours: hours(billable_minutes),
+        }
+    }
+}
+
+// Hours with two decimal places
+pub fn hours(minutes: i64) -> f64 {
+    (minutes as f64 / 60.0 * 100.0).round() / 100.0
+}
+
+// What a summary is grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeGroup {
+    Project,
+    User,
+}
@@ -0,0 +1,98 @@
+/*
+
+    Description: This file contains the JSON format of the timestamps (Used with #[serde(with = "timestamp")]). They
+                 are written as ISO-8601 in UTC and read with any offset, as a plain date (Midnight UTC) or as the
+                 seconds since 1970 the old clients still send.
+
+*/
+
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Timelike, Utc};
+use serde::{de, Deserializer, Serializer};
+
+pub fn serialize<S: Serializer>(value: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    deserializer.deserialize_any(TimestampVisitor)
+}
+
+// 2026-10-18T08:30:00+02:00 or 2026-10-18 (The databases only keep whole seconds)
+pub fn parse(text: &str) -> Result<DateTime<Utc>, String> {
+    let text = text.trim();
+    let timestamp = match DateTime::parse_from_rfc3339(text) {
+        Ok(timestamp) => timestamp.with_timezone(&Utc),
+        Err(_) => NaiveDate::parse_from_str(text, "%Y-%m-%d")
+            .map_err(|_| format!("{} is not an ISO-8601 date or timestamp", text))?
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc(),
+    };
+    Ok(whole_seconds(timestamp))
+}
+
+// The current time in whole seconds (Like the databases store it)
+pub fn now() -> DateTime<Utc> {
+    whole_seconds(Utc::now())
+}
+
+fn from_seconds(seconds: f64) -> Result<DateTime<Utc>, String> {
+    DateTime::from_timestamp(seconds.round() as i64, 0)
+        .ok_or_else(|| format!("{} seconds are out of range", seconds))
+}
+
+fn whole_seconds(timestamp: DateTime<Utc>) -> DateTime<Utc> {
+    timestamp.with_nanosecond(0).unwrap_or(timestamp)
+}
+
+// A timestamp that may be missing (Used with #[serde(default, with = "timestamp::option")], null is None)
+pub mod option {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<DateTime<Utc>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DateTime<Utc>>, D::Error> {
+        #[derive(Deserialize)]
+        struct Timestamp(#[serde(with = "super")] DateTime<Utc>);
+        Ok(Option::<Timestamp>::deserialize(deserializer)?.map(|timestamp| timestamp.0))
+    }
+}
+
+struct TimestampVisitor;
+
+impl de::Visitor<'_> for TimestampVisitor {
+    type Value = DateTime<Utc>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an ISO-8601 timestamp like 2026-10-18T08:30:00Z")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<DateTime<Utc>, E> {
+        parse(value).map_err(E::custom)
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<DateTime<Utc>, E> {
+        from_seconds(value).map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<DateTime<Utc>, E> {
+        from_seconds(value as f64).map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<DateTime<Utc>, E> {
+        from_seconds(value as f64).map_err(E::custom)
+    }
+}
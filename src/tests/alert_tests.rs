@@ -0,0 +1,362 @@
+/*
+
+    Description: This file contains the tests for the low stock alerts and their notifiers. The webhook and the mail
+                 relay are faked on random local ports.
+    How to run: cargo test
+
+*/
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+    use actix_web::{web, App, HttpMessage, HttpResponse, HttpServer};
+    use serde_json::json;
+
+    use crate::models::error::Error;
+    use crate::models::material::Material;
+    use crate::models::stock_alert::StockAlert;
+    use crate::utils::alerts::{
+        Alerter, Delivery, Notifier, SmtpNotifier, StockChange, WebhookNotifier,
+    };
+    use crate::utils::auth::Principal;
+    use crate::utils::config::SmtpConfig;
+    use crate::utils::controller;
+    use crate::utils::repository::Repository;
+    use crate::utils::store::memory::MemoryStore;
+
+    // Keeps the alerts it got (Or fails every delivery)
+    #[derive(Clone, Default)]
+    struct Recorder {
+        alerts: Arc<Mutex<Vec<StockAlert>>>,
+        fail: bool,
+    }
+
+    impl Notifier for Recorder {
+        fn name(&self) -> &'static str {
+            "recorder"
+        }
+
+        fn notify<'a>(&'a self, alert: &'a StockAlert) -> Delivery<'a> {
+            Box::pin(async move {
+                if self.fail {
+                    return Err(Error::new("Unreachable".to_string(), 502));
+                }
+                self.alerts.lock().unwrap().push(alert.clone());
+                Ok(())
+            })
+        }
+    }
+
+    impl Recorder {
+        fn amounts(&self) -> Vec<i32> {
+            self.alerts
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|alert| alert.amount)
+                .collect()
+        }
+    }
+
+    fn material(amount: i32, threshold_value: i32) -> Material {
+        Material {
+            amount,
+            threshold_value,
+            ..Material::test_data()
+        }
+    }
+
+    // The material with the amount before and after a change
+    fn change(before: i32, after: i32, threshold_value: i32) -> [StockChange; 1] {
+        [StockChange {
+            before: material(before, threshold_value),
+            after: material(after, threshold_value),
+        }]
+    }
+
+    fn alert() -> StockAlert {
+        StockAlert {
+            material_id: 1,
+            name: "Oak board".to_string(),
+            amount: 1,
+            threshold_value: 5,
+            raised_at: "2026-10-18T08:30:00Z".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_materials_are_reported_once_until_restocked() {
+        let alerter = Alerter::default();
+        assert!(alerter.check(&change(6, 5, 5)).is_empty());
+        let alerts = alerter.check(&change(5, 4, 5));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(
+            (
+                alerts[0].material_id,
+                alerts[0].amount,
+                alerts[0].threshold_value
+            ),
+            (1, 4, 5)
+        );
+        // Still low, it was already reported
+        assert!(alerter.check(&change(4, 2, 5)).is_empty());
+        // Restocked and low again
+        assert!(alerter.check(&change(2, 5, 5)).is_empty());
+        assert_eq!(alerter.check(&change(5, 3, 5)).len(), 1);
+        // Without a threshold only a backorder is low
+        let other = |before, after| {
+            let [change] = change(before, after, 0);
+            let id = |material| Material { id: 2, ..material };
+            [StockChange {
+                before: id(change.before),
+                after: id(change.after),
+            }]
+        };
+        assert!(alerter.check(&other(1, 0)).is_empty());
+        assert_eq!(alerter.check(&other(0, -1)).len(), 1);
+    }
+
+    #[test]
+    fn test_only_crossing_the_threshold_is_reported() {
+        // After a restart nothing is known about the reports, a material that stays low is not reported again
+        let alerter = Alerter::default();
+        assert!(alerter.check(&change(3, 2, 5)).is_empty());
+        assert!(alerter.check(&change(2, 6, 5)).is_empty());
+        assert_eq!(alerter.check(&change(6, 4, 5)).len(), 1);
+        // A threshold raised above the amount reports the material
+        let alerter = Alerter::default();
+        let raised = [StockChange {
+            before: material(4, 3),
+            after: material(4, 5),
+        }];
+        assert_eq!(alerter.check(&raised).len(), 1);
+        assert!(alerter.check(&change(4, 3, 5)).is_empty());
+        // A threshold lowered below the amount forgets the report
+        let lowered = [StockChange {
+            before: material(3, 5),
+            after: material(3, 2),
+        }];
+        assert!(alerter.check(&lowered).is_empty());
+        assert_eq!(alerter.check(&change(2, 1, 2)).len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_failed_deliveries_do_not_stop_the_others() {
+        let recorder = Recorder::default();
+        let failing = Recorder {
+            fail: true,
+            ..Recorder::default()
+        };
+        let alerter = Alerter::with_notifiers(vec![Box::new(failing), Box::new(recorder.clone())]);
+        alerter.send(&[alert()]).await;
+        assert_eq!(recorder.amounts(), vec![1]);
+    }
+
+    // A request that books stock for a user (The auth middleware is not part of these tests)
+    macro_rules! book {
+        ($app:expr, $uri:expr, $body:expr) => {{
+            let req = TestRequest::post().uri($uri).set_json($body).to_request();
+            req.extensions_mut().insert(Principal {
+                subject: "user-1".to_string(),
+                roles: vec!["warehouse".to_string()],
+            });
+            call_service(&$app, req).await
+        }};
+    }
+
+    #[actix_rt::test]
+    async fn test_stock_changes_raise_alerts() {
+        let recorder = Recorder::default();
+        let repo = Repository::with_store(Arc::new(MemoryStore::new()))
+            .with_alerter(Alerter::with_notifiers(vec![Box::new(recorder.clone())]));
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(repo))
+                .configure(controller::configure),
+        )
+        .await;
+        let body = json!({"name": "Wood", "description": ""});
+        call_service(
+            &app,
+            TestRequest::post()
+                .uri("/MaterialTypes")
+                .set_json(&body)
+                .to_request(),
+        )
+        .await;
+        let body = json!({
+            "name": "Oak board",
+            "description": "",
+            "type_id": 1,
+            "amount": 10,
+            "costs": "5.00 EUR",
+            "threshold_value": 5
+        });
+        call_service(
+            &app,
+            TestRequest::post()
+                .uri("/Materials")
+                .set_json(&body)
+                .to_request(),
+        )
+        .await;
+        let low_stock = || TestRequest::get().uri("/Materials/LowStock").to_request();
+        let materials: Vec<Material> = read_body_json(call_service(&app, low_stock()).await).await;
+        assert!(materials.is_empty());
+        for quantity in [4, 2, 1] {
+            let body = json!({"kind": "consume", "quantity": quantity});
+            assert_eq!(book!(app, "/Materials/1/Movements", &body).status(), 201);
+        }
+        // The alerts are sent in the background
+        actix_rt::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(recorder.amounts(), vec![4]);
+        let res = call_service(&app, low_stock()).await;
+        assert_eq!(res.headers().get("X-Total-Count").unwrap(), "1");
+        let materials: Vec<Material> = read_body_json(res).await;
+        assert_eq!(materials[0].amount, 3);
+        // Restocked, the next time it is reported again
+        let body = json!({"kind": "receive", "quantity": 10});
+        book!(app, "/Materials/1/Movements", &body);
+        let body = json!({"kind": "consume", "quantity": 9});
+        book!(app, "/Materials/1/Movements", &body);
+        actix_rt::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(recorder.amounts(), vec![4, 4]);
+        // A lowered threshold forgets the report, raising it again reports the material without a movement
+        for threshold_value in [3, 6] {
+            let body = json!({"threshold_value": threshold_value});
+            let req = TestRequest::patch()
+                .uri("/Materials/1")
+                .set_json(&body)
+                .to_request();
+            assert_eq!(call_service(&app, req).await.status(), 200);
+        }
+        actix_rt::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(recorder.amounts(), vec![4, 4, 4]);
+    }
+
+    // Keeps the JSON bodies it gets, /fail always answers 500
+    async fn hook(
+        bodies: web::Data<Mutex<Vec<serde_json::Value>>>,
+        body: web::Json<serde_json::Value>,
+    ) -> HttpResponse {
+        bodies.lock().unwrap().push(body.into_inner());
+        HttpResponse::NoContent().finish()
+    }
+
+    #[actix_rt::test]
+    async fn test_webhook_notifier() {
+        let bodies = web::Data::new(Mutex::new(Vec::<serde_json::Value>::new()));
+        let data = bodies.clone();
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(data.clone())
+                .route("/hook", web::post().to(hook))
+                .route("/fail", web::post().to(HttpResponse::InternalServerError))
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let url = format!("http://{}", server.addrs()[0]);
+        actix_rt::spawn(server.run());
+        let timeout = Duration::from_secs(5);
+        let webhook = WebhookNotifier::new(format!("{}/hook", url), timeout);
+        webhook.notify(&alert()).await.unwrap();
+        assert_eq!(
+            bodies.lock().unwrap()[0],
+            json!({
+                "material_id": 1,
+                "name": "Oak board",
+                "amount": 1,
+                "threshold_value": 5,
+                "raised_at": "2026-10-18T08:30:00Z"
+            })
+        );
+        let webhook = WebhookNotifier::new(format!("{}/fail", url), timeout);
+        assert_eq!(webhook.notify(&alert()).await.unwrap_err().code, 502);
+    }
+
+    // A mail relay that accepts one mail and returns everything the client sent
+    fn fake_relay(reject_recipients: bool) -> (u16, std::thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let relay = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut lines = Vec::new();
+            let mut data = false;
+            stream.write_all(b"220 relay ready\r\n").unwrap();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end_matches("\r\n").to_string();
+                let reply: &[u8] = if data {
+                    data = line != ".";
+                    if data {
+                        b""
+                    } else {
+                        b"250 queued\r\n"
+                    }
+                } else if line.starts_with("EHLO") {
+                    b"250-relay\r\n250 8BITMIME\r\n"
+                } else if line.starts_with("RCPT") && reject_recipients {
+                    b"550 no such user\r\n"
+                } else if line == "DATA" {
+                    data = true;
+                    b"354 go ahead\r\n"
+                } else if line == "QUIT" {
+                    b"221 bye\r\n"
+                } else {
+                    b"250 OK\r\n"
+                };
+                stream.write_all(reply).unwrap();
+                lines.push(line);
+            }
+            lines
+        });
+        (port, relay)
+    }
+
+    fn smtp(port: u16) -> SmtpNotifier {
+        SmtpNotifier::new(
+            SmtpConfig {
+                host: "127.0.0.1".to_string(),
+                port,
+                from: "wms@workshop.local".to_string(),
+                to: vec!["store@workshop.local".to_string()],
+            },
+            Duration::from_secs(5),
+        )
+    }
+
+    #[actix_rt::test]
+    async fn test_smtp_notifier() {
+        let (port, relay) = fake_relay(false);
+        let alert = StockAlert {
+            name: "Bürste".to_string(),
+            ..alert()
+        };
+        smtp(port).notify(&alert).await.unwrap();
+        let lines = relay.join().unwrap();
+        assert_eq!(lines[1], "MAIL FROM:<wms@workshop.local>");
+        assert_eq!(lines[2], "RCPT TO:<store@workshop.local>");
+        assert!(lines.contains(&"To: <store@workshop.local>".to_string()));
+        // The name is not ASCII, so the subject is encoded
+        assert!(lines.contains(
+            &"Subject: =?UTF-8?Q?Low_stock_of_B=C3=BCrste_=28Material_1=29=3A_1_left=2C_threshold_5?="
+                .to_string()
+        ));
+        assert!(lines.contains(&"Amount: 1".to_string()));
+        assert_eq!(lines.last().unwrap(), "QUIT");
+        // A recipient that is refused fails the delivery
+        let (port, relay) = fake_relay(true);
+        assert_eq!(smtp(port).notify(&alert).await.unwrap_err().code, 502);
+        drop(relay);
+    }
+}
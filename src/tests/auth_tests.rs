@@ -0,0 +1,313 @@
+/*
+
+    Description: This file contains the tests for the authorization of the routes. The tokens are checked by a fake
+                 verifier, so neither Keycloak nor a database is needed.
+    How to run: cargo test
+
+*/
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use actix_web::{get, middleware, test, web::Data, App, HttpResponse};
+    use serde_json::json;
+
+    use crate::models::client::Client;
+    use crate::models::error::{Error, ErrorBody};
+    use crate::models::material::Material;
+    use crate::models::material_type::MaterialType;
+    use crate::models::project::Project;
+    use crate::models::project_material::ProjectMaterial;
+    use crate::models::time_entry::NewTimeEntry;
+    use crate::utils::auth::{self, Authorizer, Principal, TokenVerifier, Verification};
+    use crate::utils::config::Config;
+    use crate::utils::controller;
+    use crate::utils::policy::{self, Policy, ROUTES};
+    use crate::utils::repository::Repository;
+    use crate::utils::request_id;
+    use crate::utils::store::memory::MemoryStore;
+    use crate::utils::store::WorkshopStore;
+
+    // The token is the comma separated list of roles, "invalid" is rejected like Keycloak would
+    struct FakeVerifier;
+
+    impl TokenVerifier for FakeVerifier {
+        fn verify<'a>(&'a self, token: &'a str) -> Verification<'a> {
+            Box::pin(async move {
+                if token == "invalid" {
+                    return Err(Error::new("Invalid token".to_string(), 401));
+                }
+                Ok(Principal {
+                    subject: format!("user-{}", token),
+                    roles: token.split(',').map(str::to_string).collect(),
+                })
+            })
+        }
+    }
+
+    fn authorizer(roles: &BTreeMap<String, Vec<String>>) -> Data<Authorizer> {
+        Data::new(Authorizer::with_verifier(
+            Box::new(FakeVerifier),
+            Policy::new(roles),
+        ))
+    }
+
+    // The server with all routes on an empty in-memory store
+    macro_rules! app {
+        ($roles:expr) => {
+            app!($roles, MemoryStore::new())
+        };
+        ($roles:expr, $store:expr) => {
+            test::init_service(
+                App::new()
+                    .app_data(Data::new(Repository::with_store(Arc::new($store))))
+                    .app_data(authorizer($roles))
+                    .wrap(middleware::from_fn(auth::middleware))
+                    .wrap(middleware::from_fn(request_id::middleware))
+                    .configure(controller::configure)
+                    .service(unlisted),
+            )
+            .await
+        };
+    }
+
+    // A route that was forgotten in the permission table
+    #[get("/Unlisted")]
+    async fn unlisted() -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    // Only for the Principal extractor (Borrows the permission of GET /Projects/{id})
+    #[get("/Projects/{id}")]
+    async fn whoami(principal: Principal) -> HttpResponse {
+        HttpResponse::Ok().body(principal.subject)
+    }
+
+    // What every role may do per area ('r' read, 'w' write), written down independently of the policy
+    const MATRIX: &[(&str, [&str; 7])] = &[
+        // role, [projects, clients, materials, inventory (The stock movements), purchasing, reports, time]
+        ("admin", ["rw", "rw", "rw", "rw", "rw", "r", "rw"]),
+        ("manager", ["rw", "rw", "r", "r", "rw", "r", "rw"]),
+        ("warehouse", ["r", "", "rw", "rw", "rw", "", "rw"]),
+        ("worker", ["r", "", "r", "rw", "r", "", "rw"]),
+        ("viewer", ["r", "r", "r", "r", "r", "r", "r"]),
+        ("guest", ["", "", "", "", "", "", ""]),
+    ];
+
+    // The area of a route and the body a write request needs
+    fn area(pattern: &str) -> (usize, serde_json::Value) {
+        if pattern.starts_with("/Search") {
+            // Needs projects:read, the other groups are filtered by the handler
+            (0, serde_json::Value::Null)
+        } else if pattern.ends_with("/Costing") || pattern.starts_with("/Reports") {
+            (5, serde_json::Value::Null)
+        } else if pattern.starts_with("/ProjectMaterials") {
+            (
+                0,
+                serde_json::to_value(ProjectMaterial::test_data()).unwrap(),
+            )
+        } else if pattern.starts_with("/Projects") {
+            (0, serde_json::to_value(Project::test_data()).unwrap())
+        } else if pattern.starts_with("/Clients") {
+            (1, serde_json::to_value(Client::test_data()).unwrap())
+        } else if pattern.ends_with("/Movements") {
+            (3, json!({"kind": "receive", "quantity": 1}))
+        } else if pattern.ends_with("/Receive") {
+            // Receiving a delivery changes the stock
+            (3, json!({"quantity": 1}))
+        } else if pattern.starts_with("/Suppliers") {
+            (
+                4,
+                json!({"id": 1, "name": "Timber Ltd", "email": "", "phone": ""}),
+            )
+        } else if pattern.starts_with("/PurchaseOrderLines") {
+            (
+                4,
+                json!({"id": 1, "purchase_order_id": 1, "material_id": 1, "quantity": 1, "unit_price": "1.00 EUR"}),
+            )
+        } else if pattern.starts_with("/PurchaseOrders") {
+            (
+                4,
+                json!({"id": 1, "supplier_id": 1, "status": "draft", "created_at": "2026-10-18T08:30:00Z"}),
+            )
+        } else if pattern.starts_with("/TimeEntries") {
+            (
+                6,
+                json!({"id": 1, "project_id": 1, "started_at": "2026-10-18T08:00:00Z", "ended_at": "2026-10-18T08:30:00Z"}),
+            )
+        } else if pattern.starts_with("/MaterialTypes") {
+            (2, serde_json::to_value(MaterialType::test_data()).unwrap())
+        } else {
+            (2, serde_json::to_value(Material::test_data()).unwrap())
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_role_matrix() {
+        let app = app!(&BTreeMap::new());
+        for (role, access) in MATRIX {
+            for (method, pattern, _) in ROUTES {
+                let (index, body) = area(pattern);
+                let access = access[index];
+                let needed = if *method == "GET" { 'r' } else { 'w' };
+                let req = test::TestRequest::default()
+                    .method(method.parse().unwrap())
+                    .uri(&pattern.replace("{id}", "1"))
+                    .insert_header(("Authorization", format!("Bearer {}", role)))
+                    .set_json(body)
+                    .to_request();
+                let status = test::call_service(&app, req).await.status();
+                if access.contains(needed) {
+                    // Allowed requests reach the store (That may still answer 404 or 422 on the empty store)
+                    assert!(
+                        status != 401 && status != 403,
+                        "{} {} {} was denied with {}",
+                        role,
+                        method,
+                        pattern,
+                        status
+                    );
+                } else {
+                    assert_eq!(
+                        status, 403,
+                        "{} {} {} was not denied",
+                        role, method, pattern
+                    );
+                }
+            }
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_missing_and_invalid_token() {
+        let app = app!(&BTreeMap::new());
+        let req = test::TestRequest::get().uri("/Projects").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 401);
+        let req = test::TestRequest::get()
+            .uri("/Projects")
+            .insert_header(("Authorization", "Bearer invalid"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 401);
+        // The error body still gets the request id
+        let body: ErrorBody = test::read_body_json(res).await;
+        assert!(!body.request_id.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_routes_are_denied_by_default() {
+        let app = app!(&BTreeMap::new());
+        // Even an admin cannot use a route that is not in the permission table
+        let req = test::TestRequest::get()
+            .uri("/Unlisted")
+            .insert_header(("Authorization", "Bearer admin"))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 403);
+        // A path without a route is not found, no matter who asks
+        let req = test::TestRequest::get().uri("/Nothing").to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 404);
+    }
+
+    #[actix_rt::test]
+    async fn test_principal_is_passed_to_handlers() {
+        let app = test::init_service(
+            App::new()
+                .app_data(authorizer(&BTreeMap::new()))
+                .wrap(middleware::from_fn(auth::middleware))
+                .service(whoami),
+        )
+        .await;
+        let req = test::TestRequest::get()
+            .uri("/Projects/1")
+            .insert_header(("Authorization", "Bearer viewer,worker"))
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "user-viewer,worker");
+    }
+
+    // The roles of the configuration replace the built-in roles
+    #[actix_rt::test]
+    async fn test_configured_roles() {
+        let config = Config::from_toml(
+            r#"
+            [roles]
+            foreman = ["projects:read", "projects:write"]
+            "#,
+        )
+        .unwrap();
+        policy::validate_roles(&config.roles).unwrap();
+        let app = app!(&config.roles);
+        let req = test::TestRequest::post()
+            .uri("/Projects")
+            .insert_header(("Authorization", "Bearer foreman"))
+            .set_json(Project::test_data())
+            .to_request();
+        // Allowed, the store rejects the project because the client does not exist
+        assert_eq!(test::call_service(&app, req).await.status(), 422);
+        // The built-in admin role is gone
+        let req = test::TestRequest::get()
+            .uri("/Projects")
+            .insert_header(("Authorization", "Bearer admin"))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 403);
+        // Unknown permissions are a configuration error
+        let mut roles = BTreeMap::new();
+        roles.insert("foreman".to_string(), vec!["projects:burn".to_string()]);
+        assert!(policy::validate_roles(&roles).is_err());
+    }
+
+    // Everyone changes their own time entries, only time:manage changes the entries of others
+    #[actix_rt::test]
+    async fn test_time_entry_owner() {
+        let store = MemoryStore::new();
+        store.add_client(Client::test_data().into()).unwrap();
+        store.add_project(Project::test_data().into()).unwrap();
+        let entry: NewTimeEntry = serde_json::from_value(json!({
+            "project_id": 1,
+            "started_at": "2026-10-18T08:00:00Z",
+            "duration": 30
+        }))
+        .unwrap();
+        store.add_time_entry(entry.clone(), "user-worker").unwrap();
+        store.add_time_entry(entry, "user-worker").unwrap();
+        let app = app!(&BTreeMap::new(), store);
+        let patch = |role: &str, id: i32| {
+            test::TestRequest::patch()
+                .uri(&format!("/TimeEntries/{}", id))
+                .insert_header(("Authorization", format!("Bearer {}", role)))
+                .set_json(json!({"note": "Sanding"}))
+                .to_request()
+        };
+        assert_eq!(
+            test::call_service(&app, patch("warehouse", 1))
+                .await
+                .status(),
+            403
+        );
+        assert_eq!(
+            test::call_service(&app, patch("worker", 1)).await.status(),
+            200
+        );
+        assert_eq!(
+            test::call_service(&app, patch("manager", 1)).await.status(),
+            200
+        );
+        let delete = |role: &str| {
+            test::TestRequest::delete()
+                .uri("/TimeEntries/2")
+                .insert_header(("Authorization", format!("Bearer {}", role)))
+                .to_request()
+        };
+        assert_eq!(
+            test::call_service(&app, delete("warehouse")).await.status(),
+            403
+        );
+        assert_eq!(
+            test::call_service(&app, delete("worker")).await.status(),
+            200
+        );
+    }
+}
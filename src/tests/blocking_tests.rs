@@ -0,0 +1,50 @@
+/*
+
+    Description: This file contains the tests for running blocking database calls off the actix workers.
+    How to run: cargo test
+
+*/
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::utils::repository::blocking;
+
+    // A slow query must not stall the other requests of the same worker
+    // (actix_rt::test runs everything on one thread, just like a single actix worker)
+    #[actix_rt::test]
+    async fn test_slow_query_does_not_block_worker() {
+        // Simulate a slow query that blocks its thread for half a second
+        let slow = actix_rt::spawn(blocking(|| {
+            std::thread::sleep(Duration::from_millis(500));
+            Ok("slow")
+        }));
+        // Give the slow query time to start
+        actix_rt::time::sleep(Duration::from_millis(50)).await;
+        // Meanwhile the worker keeps serving fast requests
+        let start = Instant::now();
+        for _ in 0..10 {
+            let result = blocking(|| Ok("fast")).await;
+            assert_eq!(result.unwrap(), "fast");
+        }
+        assert!(start.elapsed() < Duration::from_millis(400));
+        assert!(!slow.is_finished());
+        // The slow query still finishes with its result
+        assert_eq!(slow.await.unwrap().unwrap(), "slow");
+    }
+
+    #[actix_rt::test]
+    async fn test_blocking_returns_errors() {
+        let result: Result<(), _> = blocking(|| {
+            Err(crate::models::error::Error::new(
+                "Query failed".to_string(),
+                500,
+            ))
+        })
+        .await;
+        let err = result.unwrap_err();
+        assert_eq!(err.code, 500);
+        assert_eq!(err.message, "Query failed");
+    }
+}
@@ -0,0 +1,268 @@
+/*
+
+    Description: This file contains the tests for the costing of the projects and the budget variance report.
+    How to run: cargo test
+
+*/
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use crate::models::client::Client;
+    use crate::models::error;
+    use crate::models::material::Material;
+    use crate::models::material_type::MaterialType;
+    use crate::models::money::Money;
+    use crate::models::project::Project;
+    use crate::models::project_material::ProjectMaterial;
+    use crate::models::stock_movement::Booking;
+    use crate::models::time_entry::{NewTimeEntry, Timer};
+    use crate::models::timestamp;
+    use crate::utils::config::CostingConfig;
+    use crate::utils::costing::Costing;
+    use crate::utils::repository::Repository;
+    use crate::utils::store::memory::MemoryStore;
+    use crate::utils::store::query::{ListQuery, Page};
+    use crate::utils::store::WorkshopStore;
+
+    fn money(text: &str) -> Money {
+        text.parse().unwrap()
+    }
+
+    fn costing(labour_rate: &str) -> Costing {
+        Costing::new(&CostingConfig {
+            labour_rate: money(labour_rate),
+        })
+    }
+
+    // A project material of the project with amount pieces of a material that costs unit_costs
+    fn line(
+        id: i32,
+        project_id: i32,
+        amount: i32,
+        unit_costs: &str,
+    ) -> (ProjectMaterial, Material) {
+        (
+            ProjectMaterial {
+                id,
+                project_id,
+                material_id: id,
+                amount,
+                version: 1,
+            },
+            Material {
+                id,
+                name: format!("Material {}", id),
+                costs: money(unit_costs),
+                ..Material::test_data()
+            },
+        )
+    }
+
+    fn project(id: i32, estimated_costs: &str, estimated_hours: f32) -> Project {
+        Project {
+            id,
+            estimated_costs: money(estimated_costs),
+            estimated_hours,
+            ..Project::test_data()
+        }
+    }
+
+    #[test]
+    fn test_project_costing() {
+        let materials = [line(1, 1, 4, "12.50 EUR"), line(2, 1, 3, "0.99 EUR")];
+        let costing = costing("40.00 EUR")
+            .project(&project(1, "150.00 EUR", 2.5), &materials, 105)
+            .unwrap();
+        assert_eq!(
+            costing
+                .materials
+                .iter()
+                .map(|line| line.costs.to_string())
+                .collect::<Vec<_>>(),
+            ["50.00 EUR", "2.97 EUR"]
+        );
+        assert_eq!(costing.material_costs, money("52.97 EUR"));
+        assert_eq!(costing.labour.costs, money("70.00 EUR"));
+        assert_eq!(costing.total_costs, money("122.97 EUR"));
+        // Typed into the project, not computed
+        assert_eq!(costing.recorded_costs, Project::test_data().costs);
+        let variance = &costing.cost_variance;
+        assert_eq!(variance.difference, money("-27.03 EUR"));
+        assert_eq!(variance.percent, Some(-18.02));
+        let hours = &costing.hours_variance;
+        assert_eq!(
+            (hours.estimated, hours.logged, hours.difference),
+            (2.5, 1.75, -0.75)
+        );
+        assert_eq!(hours.percent, Some(-30.0));
+    }
+
+    // The labour costs are computed in cents, a started cent is rounded like money is
+    #[test]
+    fn test_labour_costs_are_rounded_to_cents() {
+        for (rate, minutes, costs) in [
+            ("40.00 EUR", 7, "4.67 EUR"),
+            ("0.50 EUR", 1, "0.01 EUR"),
+            ("0.01 EUR", 30, "0.01 EUR"),
+            ("0.01 EUR", 29, "0.00 EUR"),
+            ("45.00 EUR", 90, "67.50 EUR"),
+        ] {
+            let costing = costing(rate)
+                .project(&project(1, "100.00 EUR", 1.0), &[], minutes)
+                .unwrap();
+            assert_eq!(costing.labour.costs, money(costs), "{} {}", rate, minutes);
+        }
+    }
+
+    #[test]
+    fn test_nothing_estimated() {
+        let costing = costing("40.00 EUR")
+            .project(&project(1, "0 EUR", 0.0), &[line(1, 1, 1, "5 EUR")], 0)
+            .unwrap();
+        assert_eq!(costing.cost_variance.difference, money("5.00 EUR"));
+        assert_eq!(costing.cost_variance.percent, None);
+        assert_eq!(costing.hours_variance.percent, None);
+    }
+
+    #[test]
+    fn test_currency_mismatch() {
+        // The labour rate is only charged if hours were logged
+        let usd = project(1, "100.00 USD", 1.0);
+        let without_hours = costing("40.00 EUR")
+            .project(&usd, &[line(1, 1, 1, "5.00 USD")], 0)
+            .unwrap();
+        assert_eq!(without_hours.total_costs, money("5.00 USD"));
+        let err = costing("40.00 EUR")
+            .project(&usd, &[line(1, 1, 1, "5.00 USD")], 60)
+            .unwrap_err();
+        assert_eq!(
+            (err.code, err.kind.as_str()),
+            (409, error::CURRENCY_MISMATCH)
+        );
+        let err = costing("40.00 EUR")
+            .project(
+                &project(1, "100.00 EUR", 1.0),
+                &[line(1, 1, 1, "5.00 USD")],
+                0,
+            )
+            .unwrap_err();
+        assert_eq!(err.kind, error::CURRENCY_MISMATCH);
+    }
+
+    #[test]
+    fn test_budget_variance() {
+        let projects = Page {
+            items: vec![
+                project(1, "100.00 EUR", 10.0),
+                project(2, "50.00 EUR", 0.0),
+                project(3, "80.00 CHF", 2.0),
+                project(4, "10.00 EUR", 1.0),
+            ],
+            total: 7,
+        };
+        let materials = [
+            line(1, 1, 2, "30.00 EUR"),
+            line(2, 2, 1, "70.00 EUR"),
+            line(3, 3, 1, "20.00 CHF"),
+            line(4, 4, 1, "1.00 USD"),
+        ];
+        let minutes = BTreeMap::from([(1, 60)]);
+        let report = costing("25.00 EUR")
+            .budget_variance(&projects, &materials, &minutes)
+            .unwrap();
+        assert_eq!(report.total, 7);
+        assert_eq!(
+            report
+                .projects
+                .iter()
+                .map(|row| (row.project_id, row.cost_variance.difference.to_string()))
+                .collect::<Vec<_>>(),
+            [
+                (1, "-15.00 EUR".to_string()),
+                (2, "20.00 EUR".to_string()),
+                (3, "-60.00 CHF".to_string())
+            ]
+        );
+        assert_eq!(report.mixed_currency, [4]);
+        // One total per currency
+        assert_eq!(
+            report
+                .totals
+                .iter()
+                .map(|total| (total.estimated.to_string(), total.actual.to_string()))
+                .collect::<Vec<_>>(),
+            [
+                ("80.00 CHF".to_string(), "20.00 CHF".to_string()),
+                ("150.00 EUR".to_string(), "155.00 EUR".to_string())
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_repository_costing() {
+        let store = Arc::new(MemoryStore::new());
+        store.add_client(Client::test_data().into()).unwrap();
+        store
+            .add_material_type(MaterialType::test_data().into())
+            .unwrap();
+        store.add_material(Material::test_data().into()).unwrap();
+        store.add_project(Project::test_data().into()).unwrap();
+        store.add_project(Project::test_data().into()).unwrap();
+        store
+            .add_project_material(
+                ProjectMaterial::test_data().into(),
+                &Booking::new("user-1".to_string(), false),
+            )
+            .unwrap();
+        let repo = Repository::with_store(store.clone()).with_costing(costing("45.00 EUR"));
+        // 10 pieces of 10.00 EUR, no hours are logged yet
+        let costing = repo.get_project_costing(1).await.unwrap();
+        assert_eq!(costing.materials.len(), 1);
+        assert_eq!(costing.total_costs, money("100.00 EUR"));
+        assert_eq!(costing.labour.rate, money("45.00 EUR"));
+        assert_eq!(costing.cost_variance.difference.cents, 0);
+        assert_eq!(repo.get_project_costing(9).await.unwrap_err().code, 404);
+        let report = repo
+            .get_budget_variance(ListQuery::default())
+            .await
+            .unwrap();
+        assert_eq!((report.projects.len(), report.total), (2, 2));
+        assert_eq!(report.totals[0].actual, money("100.00 EUR"));
+        assert_eq!(report.totals[0].estimated, money("200.00 EUR"));
+        assert_eq!(report.totals[0].percent, Some(-50.0));
+        // Only stopped entries are logged hours
+        let entry: NewTimeEntry = serde_json::from_value(json!({
+            "project_id": 1,
+            "started_at": "2026-10-18T08:00:00Z",
+            "duration": 90
+        }))
+        .unwrap();
+        store.add_time_entry(entry, "user-1").unwrap();
+        store
+            .start_timer(
+                Timer {
+                    project_id: 1,
+                    note: String::new(),
+                    billable: true,
+                },
+                "user-2",
+                timestamp::now(),
+            )
+            .unwrap();
+        let costing = repo.get_project_costing(1).await.unwrap();
+        assert_eq!(costing.labour.hours, 1.5);
+        assert_eq!(costing.labour.costs, money("67.50 EUR"));
+        assert_eq!(costing.total_costs, money("167.50 EUR"));
+        let report = repo
+            .get_budget_variance(ListQuery::default())
+            .await
+            .unwrap();
+        assert_eq!(report.projects[0].hours_variance.logged, 1.5);
+        assert_eq!(report.projects[1].hours_variance.logged, 0.0);
+    }
+}
@@ -0,0 +1,103 @@
+/*
+
+    Description: This file contains the tests for the error responses of the server.
+    How to run: cargo test
+
+*/
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{get, middleware, test, App, HttpResponse};
+
+    use crate::models::error::{self, Error, ErrorBody};
+    use crate::utils::request_id;
+
+    #[get("/NotFound")]
+    async fn not_found() -> Result<HttpResponse, Error> {
+        Err(Error::new("Project not found".to_string(), 404))
+    }
+
+    #[get("/Duplicate")]
+    async fn duplicate() -> Result<HttpResponse, Error> {
+        Err(Error::with_kind(
+            "Duplicate entry '1' for key 'PRIMARY'".to_string(),
+            409,
+            error::DUPLICATE_KEY,
+        ))
+    }
+
+    #[get("/Invalid")]
+    async fn invalid() -> Result<HttpResponse, Error> {
+        Err(Error::new("Something odd".to_string(), 42))
+    }
+
+    macro_rules! app {
+        () => {
+            test::init_service(
+                App::new()
+                    .wrap(middleware::from_fn(request_id::middleware))
+                    .service(not_found)
+                    .service(duplicate)
+                    .service(invalid),
+            )
+            .await
+        };
+    }
+
+    // The status code of the error is used and the body tells the frontend what went wrong
+    #[actix_rt::test]
+    async fn test_error_response() {
+        let app = app!();
+        let res =
+            test::call_service(&app, test::TestRequest::get().uri("/NotFound").to_request()).await;
+        assert_eq!(res.status(), 404);
+        let id = res
+            .headers()
+            .get(request_id::HEADER)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body: ErrorBody = test::read_body_json(res).await;
+        assert_eq!(body.code, "not_found");
+        assert_eq!(body.status, 404);
+        assert_eq!(body.message, "Project not found");
+        // The id of the body is the one of the response header
+        assert_eq!(body.request_id, id);
+        assert!(!id.is_empty());
+
+        let res = test::call_service(
+            &app,
+            test::TestRequest::get().uri("/Duplicate").to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), 409);
+        let body: ErrorBody = test::read_body_json(res).await;
+        assert_eq!(body.code, error::DUPLICATE_KEY);
+
+        // Codes that are no HTTP error become an internal error
+        let res =
+            test::call_service(&app, test::TestRequest::get().uri("/Invalid").to_request()).await;
+        assert_eq!(res.status(), 500);
+    }
+
+    // The request id of the client is kept, unusable ones are replaced
+    #[actix_rt::test]
+    async fn test_request_id() {
+        let app = app!();
+        let req = test::TestRequest::get()
+            .uri("/NotFound")
+            .insert_header((request_id::HEADER, "abc-123"))
+            .to_request();
+        let body: ErrorBody = test::read_body_json(test::call_service(&app, req).await).await;
+        assert_eq!(body.request_id, "abc-123");
+
+        let req = test::TestRequest::get()
+            .uri("/NotFound")
+            .insert_header((request_id::HEADER, "no spaces allowed"))
+            .to_request();
+        let body: ErrorBody = test::read_body_json(test::call_service(&app, req).await).await;
+        assert_ne!(body.request_id, "no spaces allowed");
+        assert!(!body.request_id.is_empty());
+    }
+}
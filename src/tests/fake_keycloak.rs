@@ -0,0 +1,89 @@
+/*
+
+    Description: This file contains a fake Keycloak for the tests. It serves the signing keys and the introspection
+                 endpoint of the WMS realm on a random local port and counts the requests.
+
+*/
+
+#![cfg(test)]
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use actix_web::{web, App, HttpResponse, HttpServer};
+use serde_json::json;
+
+pub struct FakeKeycloak {
+    pub jwks: Mutex<serde_json::Value>,
+    // Answer every request for the keys with 503
+    pub down: Mutex<bool>,
+    // Tokens the introspection endpoint reports as inactive
+    pub revoked: Mutex<Vec<String>>,
+    // Seconds until the introspected tokens expire
+    pub lifetime: Mutex<u64>,
+    pub fetches: AtomicUsize,
+    pub introspections: AtomicUsize,
+}
+
+async fn certs(state: web::Data<FakeKeycloak>) -> HttpResponse {
+    state.fetches.fetch_add(1, Ordering::SeqCst);
+    if *state.down.lock().unwrap() {
+        return HttpResponse::ServiceUnavailable().finish();
+    }
+    HttpResponse::Ok().json(state.jwks.lock().unwrap().clone())
+}
+
+// Every token is active (Unless revoked) and belongs to a viewer
+async fn introspect(
+    state: web::Data<FakeKeycloak>,
+    form: web::Form<HashMap<String, String>>,
+) -> HttpResponse {
+    state.introspections.fetch_add(1, Ordering::SeqCst);
+    let token = form.get("token").cloned().unwrap_or_default();
+    if state.revoked.lock().unwrap().contains(&token) {
+        return HttpResponse::Ok().json(json!({ "active": false }));
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    HttpResponse::Ok().json(json!({
+        "active": true,
+        "sub": "introspected",
+        "exp": now + *state.lifetime.lock().unwrap(),
+        "realm_access": {"roles": ["viewer"]},
+    }))
+}
+
+// Start the fake Keycloak, returns its state and its base URL
+pub fn start(keys: Vec<serde_json::Value>) -> (web::Data<FakeKeycloak>, String) {
+    let state = web::Data::new(FakeKeycloak {
+        jwks: Mutex::new(json!({ "keys": keys })),
+        down: Mutex::new(false),
+        revoked: Mutex::new(Vec::new()),
+        lifetime: Mutex::new(300),
+        fetches: AtomicUsize::new(0),
+        introspections: AtomicUsize::new(0),
+    });
+    let data = state.clone();
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(data.clone())
+            .route(
+                "/realms/WMS/protocol/openid-connect/certs",
+                web::get().to(certs),
+            )
+            .route(
+                "/realms/WMS/protocol/openid-connect/token/introspect",
+                web::post().to(introspect),
+            )
+    })
+    .workers(1)
+    .bind(("127.0.0.1", 0))
+    .unwrap();
+    let url = format!("http://{}", server.addrs()[0]);
+    actix_rt::spawn(server.run());
+    (state, url)
+}
@@ -0,0 +1,143 @@
+/*
+
+    Description: This file contains the tests for the query parameters of the list endpoints (Pagination, sorting and
+                 filters). How the backends apply them is tested in the store tests.
+    How to run: cargo test
+
+*/
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+    use actix_web::{web::Data, App};
+
+    use crate::models::client::Client;
+    use crate::models::error::{Error, ErrorBody};
+    use crate::models::material::Material;
+    use crate::models::project::Project;
+    use crate::utils::controller;
+    use crate::utils::repository::Repository;
+    use crate::utils::store::memory::MemoryStore;
+    use crate::utils::store::query::{Fields, ListQuery, Op, SortOrder, DEFAULT_LIMIT};
+    use crate::utils::store::sql::SqlValue;
+    use crate::utils::store::WorkshopStore;
+
+    fn parse<T: Fields>(params: &[(&str, &str)]) -> Result<ListQuery, Error> {
+        let params: Vec<(String, String)> = params
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        ListQuery::parse::<T>(&params)
+    }
+
+    #[test]
+    fn test_parse() {
+        let query = parse::<Material>(&[
+            ("limit", "20"),
+            ("offset", "40"),
+            ("sort", "name,amount:desc"),
+            ("type_id", "3"),
+            ("amount_lt", "10"),
+            ("threshold_value_gte", "5"),
+            ("costs_gt", "1.5"),
+        ])
+        .unwrap();
+        assert_eq!((query.limit, query.offset), (20, 40));
+        assert_eq!(
+            query.sort,
+            vec![("name", SortOrder::Asc), ("amount", SortOrder::Desc)]
+        );
+        let filters: Vec<_> = query
+            .filters
+            .iter()
+            .map(|filter| (filter.field, filter.op, filter.value.clone()))
+            .collect();
+        assert_eq!(
+            filters,
+            vec![
+                ("type_id", Op::Eq, SqlValue::Int(3)),
+                ("amount", Op::Lt, SqlValue::Int(10)),
+                ("threshold_value", Op::Ge, SqlValue::Int(5)),
+                ("costs", Op::Gt, SqlValue::Real(1.5)),
+            ]
+        );
+        // The SQL only contains whitelisted field names, the values are parameters
+        assert_eq!(
+            query.where_clause().0,
+            " WHERE type_id = ? AND amount < ? AND threshold_value >= ? AND costs > ?"
+        );
+        assert_eq!(
+            query.order_clause(),
+            " ORDER BY name ASC, amount DESC, id ASC"
+        );
+        assert_eq!(parse::<Project>(&[]).unwrap().limit, DEFAULT_LIMIT);
+    }
+
+    #[test]
+    fn test_parse_dates() {
+        let query = parse::<Project>(&[
+            ("active_on", "2026-10-18"),
+            ("starts_after", "2026-10-01T10:00:00+02:00"),
+            ("endpoint_lte", "2026-12-31"),
+        ])
+        .unwrap();
+        let filters: Vec<_> = query
+            .filters
+            .iter()
+            .map(|filter| (filter.field, filter.op, filter.value.clone()))
+            .collect();
+        assert_eq!(
+            filters,
+            vec![
+                ("startpoint", Op::Lt, "2026-10-19 00:00:00".into()),
+                ("endpoint", Op::Ge, "2026-10-18 00:00:00".into()),
+                ("startpoint", Op::Gt, "2026-10-01 08:00:00".into()),
+                ("endpoint", Op::Le, "2026-12-31 00:00:00".into()),
+            ]
+        );
+        for params in [
+            ("active_on", "tomorrow"),
+            ("starts_after", "1790841600"),
+            ("startpoint_like", "2026"),
+        ] {
+            let err = parse::<Project>(&[params]).unwrap_err();
+            assert_eq!(err.code, 400, "{:?}", params);
+        }
+        // Only projects have dates
+        let err = parse::<Material>(&[("active_on", "2026-10-18")]).unwrap_err();
+        assert_eq!(err.code, 400);
+    }
+
+    #[actix_rt::test]
+    async fn test_list_endpoint() {
+        let store = Arc::new(MemoryStore::new());
+        for _ in 1..=3 {
+            store.add_client(Client::test_data().into()).unwrap();
+        }
+        let app = init_service(
+            App::new()
+                .app_data(Data::new(Repository::with_store(store)))
+                .configure(controller::configure),
+        )
+        .await;
+        let req = TestRequest::get()
+            .uri("/Clients?limit=2&sort=id:desc")
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.headers().get("X-Total-Count").unwrap(), "3");
+        let clients: Vec<Client> = read_body_json(res).await;
+        let ids: Vec<i32> = clients.iter().map(|client| client.id).collect();
+        assert_eq!(ids, vec![3, 2]);
+        // Unknown fields are a bad request with the usual error body
+        let req = TestRequest::get()
+            .uri("/Clients?password=secret")
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+        let body: ErrorBody = read_body_json(res).await;
+        assert_eq!(body.code, "bad_request");
+    }
+}
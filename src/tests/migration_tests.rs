@@ -0,0 +1,208 @@
+/*
+
+    Description: This file contains the tests for the schema migrations.
+    How to run: cargo test
+
+*/
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::models::client::Client;
+    use crate::models::material::Material;
+    use crate::models::project::Project;
+    use crate::utils::config::DatabaseConfig;
+    use crate::utils::store::migrations::{self, Direction, Migration, MYSQL, SQLITE};
+    use crate::utils::store::mysql::split_statements;
+    use crate::utils::store::query::ListQuery;
+    use crate::utils::store::sqlite::SqliteStore;
+    use crate::utils::store::WorkshopStore;
+
+    // Open an empty SQLite database without running the migrations
+    fn empty_store() -> SqliteStore {
+        SqliteStore::open(&DatabaseConfig {
+            url: "sqlite://:memory:".to_string(),
+            ..DatabaseConfig::default()
+        })
+        .unwrap()
+    }
+
+    // A SQLite file migrated up to a version and a second connection to write rows like an older binary did
+    fn file_store(name: &str, version: i64) -> (SqliteStore, rusqlite::Connection, PathBuf) {
+        let path = std::env::temp_dir().join(format!("wms_{}_{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = SqliteStore::open(&DatabaseConfig {
+            url: format!("sqlite://{}", path.display()),
+            ..DatabaseConfig::default()
+        })
+        .unwrap();
+        migrations::migrate_up(&store, Some(version)).unwrap();
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        (store, conn, path)
+    }
+
+    #[test]
+    fn test_dialects_have_the_same_versions() {
+        let mysql: Vec<i64> = MYSQL.iter().map(|m| m.version).collect();
+        let sqlite: Vec<i64> = SQLITE.iter().map(|m| m.version).collect();
+        assert_eq!(mysql, sqlite);
+        // Versions are strictly ascending
+        assert!(mysql.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_check_refuses_unmigrated_database() {
+        let store = empty_store();
+        assert!(migrations::check(&store).is_err());
+        migrations::migrate_up(&store, None).unwrap();
+        assert!(migrations::check(&store).is_ok());
+    }
+
+    #[test]
+    fn test_up_down_and_status() {
+        let store = empty_store();
+        assert!(migrations::status(&store)
+            .unwrap()
+            .iter()
+            .all(|migration| !migration.applied));
+        // Up applies every migration exactly once
+        let applied = migrations::migrate_up(&store, None).unwrap();
+        assert_eq!(applied.len(), SQLITE.len());
+        assert!(migrations::migrate_up(&store, None).unwrap().is_empty());
+        assert!(migrations::status(&store)
+            .unwrap()
+            .iter()
+            .all(|migration| migration.applied));
+        store.add_client(Client::test_data().into()).unwrap();
+        // Down to 0 removes the tables again
+        let reverted = migrations::migrate_down(&store, 0).unwrap();
+        assert_eq!(reverted.len(), SQLITE.len());
+        assert_eq!(migrations::current_version(&store).unwrap(), 0);
+        assert!(store.get_all_clients(&ListQuery::default()).is_err());
+    }
+
+    // A migration that fails halfway leaves nothing behind on SQLite
+    #[test]
+    fn test_failed_migration_is_rolled_back() {
+        let store = empty_store();
+        migrations::migrate_up(&store, None).unwrap();
+        let broken = Migration {
+            version: 99,
+            name: "9999_broken",
+            up: "CREATE TABLE half_done (id INTEGER); CREATE TABLE broken (",
+            down: "",
+        };
+        assert!(store.apply_migration(&broken, Direction::Up).is_err());
+        assert!(!store.applied_migrations().unwrap().contains(&99));
+        // The first statement was rolled back as well, so the table can be created again
+        let fixed = Migration {
+            up: "CREATE TABLE half_done (id INTEGER);",
+            ..broken
+        };
+        store.apply_migration(&fixed, Direction::Up).unwrap();
+        assert!(store.applied_migrations().unwrap().contains(&99));
+    }
+
+    // Tables that were created by hand are not adopted, the first migration fails before it changed anything
+    #[test]
+    fn test_existing_tables_are_not_adopted() {
+        let path = std::env::temp_dir().join(format!("wms_adopt_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE client (id INTEGER PRIMARY KEY, firstname TEXT NOT NULL);",
+        )
+        .unwrap();
+        let store = SqliteStore::open(&DatabaseConfig {
+            url: format!("sqlite://{}", path.display()),
+            ..DatabaseConfig::default()
+        })
+        .unwrap();
+        assert!(migrations::migrate_up(&store, None).is_err());
+        assert!(store.applied_migrations().unwrap().is_empty());
+        assert!(store.get_all_material_types(&ListQuery::default()).is_err());
+        // On MySQL the first statement is the one that fails (Nothing was committed before it)
+        let statements = split_statements(MYSQL[0].up);
+        assert!(statements[0].starts_with("CREATE TABLE client"));
+        assert!(statements
+            .iter()
+            .all(|statement| !statement.contains("IF NOT EXISTS")));
+        drop(conn);
+        drop(store);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_foreign_keys_are_enforced() {
+        let store = empty_store();
+        migrations::migrate_up(&store, None).unwrap();
+        // The material type 1 does not exist
+        assert!(store.add_material(Material::test_data().into()).is_err());
+    }
+
+    #[test]
+    fn test_project_seconds_become_timestamps() {
+        let (store, conn, path) = file_store("dates", 2);
+        // A project written before the migration (Seconds since 1970)
+        conn.execute_batch(
+            "INSERT INTO client (firstname, lastname, phone) VALUES ('John', 'Doe', '');
+             INSERT INTO project (name, client_id, description, startpoint, endpoint, estimated_costs, estimated_hours, costs)
+             VALUES ('Table', 1, '', 1790841600.0, 1792515600.4, 100.0, 10.0, 100.0);",
+        )
+        .unwrap();
+        migrations::migrate_up(&store, None).unwrap();
+        let project = store.get_project(1).unwrap();
+        assert_eq!(project.startpoint, Project::test_data().startpoint);
+        assert_eq!(project.endpoint, Project::test_data().endpoint);
+        // Down turns them back into seconds
+        migrations::migrate_down(&store, 2).unwrap();
+        let seconds: (f64, f64) = conn
+            .query_row("SELECT startpoint, endpoint FROM project", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(seconds, (1790841600.0, 1792515600.0));
+        drop(conn);
+        drop(store);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_float_costs_become_decimals() {
+        let (store, conn, path) = file_store("money", 3);
+        conn.execute_batch(
+            "INSERT INTO material_type (name, description) VALUES ('Wood', '');
+             INSERT INTO material (name, description, type_id, amount, costs, threshold_value)
+             VALUES ('Oak board', '', 1, 10, 19.999, 2);",
+        )
+        .unwrap();
+        migrations::migrate_up(&store, None).unwrap();
+        let costs = store.get_material(1).unwrap().costs;
+        assert_eq!(costs.to_string(), "20.00 EUR");
+        // New costs keep their cents and currency
+        let material = Material {
+            costs: "0.30 USD".parse().unwrap(),
+            ..store.get_material(1).unwrap()
+        };
+        store.update_material(material, None).unwrap();
+        assert_eq!(store.get_material(1).unwrap().costs.to_string(), "0.30 USD");
+        migrations::migrate_down(&store, 3).unwrap();
+        let costs: f64 = conn
+            .query_row("SELECT costs FROM material", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(costs, 0.3);
+        drop(conn);
+        drop(store);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_mysql_scripts_split_into_statements() {
+        let statements = split_statements(MYSQL[0].up);
+        assert_eq!(statements.len(), 8);
+        assert!(statements[0].starts_with("CREATE TABLE client"));
+        assert!(statements.iter().all(|statement| !statement.ends_with(';')));
+        assert_eq!(split_statements(MYSQL[0].down).len(), 5);
+    }
+}
@@ -1 +1,19 @@
+pub mod alert_tests;
+pub mod auth_tests;
+pub mod blocking_tests;
+pub mod config_tests;
+pub mod costing_tests;
+pub mod error_tests;
+pub mod fake_keycloak;
+pub mod jwks_tests;
+pub mod list_tests;
+pub mod merge_patch_tests;
+pub mod migration_tests;
+pub mod money_tests;
+pub mod rate_limit_tests;
 pub mod repo_tests;
+pub mod resource_tests;
+pub mod search_tests;
+pub mod store_tests;
+pub mod token_cache_tests;
+pub mod validation_tests;
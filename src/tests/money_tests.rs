@@ -0,0 +1,91 @@
+/*
+
+    Description: This file contains the tests for the money type (Parsing, formatting, arithmetic and the JSON format).
+    How to run: cargo test
+
+*/
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::models::money::{parse_amount, Currency, Money, DEFAULT_CURRENCY};
+
+    #[test]
+    fn test_parse_amounts() {
+        for (text, cents) in [
+            ("12", 1200),
+            ("12.5", 1250),
+            ("12.05", 1205),
+            ("0.10", 10),
+            ("-3.00", -300),
+            ("9999999999.99", 999_999_999_999),
+        ] {
+            assert_eq!(parse_amount(text), Ok(cents), "{}", text);
+        }
+        for text in ["", "-", ".5", "12.", "12.345", "1,50", "+1", "1e3", "12.5x"] {
+            assert!(parse_amount(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_format_and_parse() {
+        let usd: Currency = "USD".parse().unwrap();
+        assert_eq!(Money::new(1205, usd).to_string(), "12.05 USD");
+        assert_eq!(Money::new(-5, usd).to_string(), "-0.05 USD");
+        assert_eq!(Money::new(-5, usd).amount(), "-0.05");
+        assert_eq!("12.05 USD".parse(), Ok(Money::new(1205, usd)));
+        // Without a currency the default one is used
+        assert_eq!("7".parse(), Ok(Money::new(700, DEFAULT_CURRENCY)));
+        for text in ["12 usd", "12 EURO", "12 E1R", "EUR"] {
+            assert!(text.parse::<Money>().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let usd: Currency = "USD".parse().unwrap();
+        let money = Money::new(1250, DEFAULT_CURRENCY);
+        assert_eq!(
+            money.checked_add(Money::new(50, DEFAULT_CURRENCY)),
+            Some(Money::new(1300, DEFAULT_CURRENCY))
+        );
+        assert_eq!(
+            money.checked_sub(Money::new(1300, DEFAULT_CURRENCY)),
+            Some(Money::new(-50, DEFAULT_CURRENCY))
+        );
+        assert_eq!(
+            money.checked_mul(4),
+            Some(Money::new(5000, DEFAULT_CURRENCY))
+        );
+        // Other currencies are not converted
+        assert_eq!(money.checked_add(Money::new(50, usd)), None);
+        assert_eq!(money.checked_sub(Money::new(50, usd)), None);
+        assert_eq!(money.checked_mul(i64::MAX), None);
+    }
+
+    #[test]
+    fn test_json() {
+        let money = Money::new(1999, DEFAULT_CURRENCY);
+        assert_eq!(serde_json::to_value(money).unwrap(), json!("19.99 EUR"));
+        assert_eq!(
+            serde_json::from_value::<Money>(json!("19.99 EUR")).unwrap(),
+            money
+        );
+        // Numbers of the old clients are rounded to cents
+        assert_eq!(
+            serde_json::from_value::<Money>(json!(19.99)).unwrap(),
+            money
+        );
+        assert_eq!(
+            serde_json::from_value::<Money>(json!(0.1 + 0.2)).unwrap(),
+            Money::new(30, DEFAULT_CURRENCY)
+        );
+        assert_eq!(
+            serde_json::from_value::<Money>(json!(20)).unwrap().cents,
+            2000
+        );
+        assert!(serde_json::from_value::<Money>(json!("19.999 EUR")).is_err());
+        assert!(serde_json::from_value::<Money>(json!(null)).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+/*
+
+    Description: This file contains the tests for the rate limiting of the requests per user and client IP.
+    How to run: cargo test
+
+*/
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use actix_web::{
+        dev::ServiceResponse, get, middleware, post, test, web::Data, App, HttpResponse,
+    };
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    use crate::models::error::{Error, ErrorBody};
+    use crate::utils::auth::{self, Authorizer, Principal, TokenVerifier, Verification};
+    use crate::utils::config::Config;
+    use crate::utils::policy::Policy;
+    use crate::utils::rate_limit::{self, RateLimiter};
+    use crate::utils::request_id;
+
+    // Every token but "invalid" belongs to an admin, "invalid" is rejected like Keycloak would (Counts the verifications)
+    struct FakeVerifier(Arc<AtomicUsize>);
+
+    impl TokenVerifier for FakeVerifier {
+        fn verify<'a>(&'a self, token: &'a str) -> Verification<'a> {
+            Box::pin(async move {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                if token == "invalid" {
+                    return Err(Error::new("Invalid token".to_string(), 401));
+                }
+                Ok(Principal {
+                    subject: token.to_string(),
+                    roles: vec!["admin".to_string()],
+                })
+            })
+        }
+    }
+
+    // A JWT of the user (The limiter does not check the signature, only the verifier would)
+    fn token(subject: &str) -> String {
+        encode(
+            &Header::default(),
+            &json!({"sub": subject}),
+            &EncodingKey::from_secret(b"test"),
+        )
+        .unwrap()
+    }
+
+    #[get("/Projects")]
+    async fn projects() -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[post("/Projects")]
+    async fn add_project() -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[get("/Reports/BudgetVariance")]
+    async fn reports() -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    // The middlewares in the order of the server
+    macro_rules! app {
+        ($toml:expr) => {
+            app!($toml, Arc::new(AtomicUsize::new(0)))
+        };
+        ($toml:expr, $verifications:expr) => {
+            test::init_service(
+                App::new()
+                    .app_data(Data::new(RateLimiter::new(
+                        &Config::from_toml($toml).unwrap().rate_limit,
+                    )))
+                    .app_data(Data::new(Authorizer::with_verifier(
+                        Box::new(FakeVerifier($verifications)),
+                        Policy::new(&BTreeMap::new()),
+                    )))
+                    .wrap(middleware::from_fn(auth::middleware))
+                    .wrap(middleware::from_fn(rate_limit::middleware))
+                    .wrap(middleware::from_fn(request_id::middleware))
+                    .service(projects)
+                    .service(add_project)
+                    .service(reports),
+            )
+            .await
+        };
+    }
+
+    macro_rules! get {
+        ($uri:expr, $user:expr) => {
+            test::TestRequest::get()
+                .uri($uri)
+                .insert_header(("Authorization", format!("Bearer {}", token($user))))
+                .to_request()
+        };
+    }
+
+    fn header<B>(res: &ServiceResponse<B>, name: &str) -> String {
+        res.headers()
+            .get(name)
+            .map(|value| value.to_str().unwrap().to_string())
+            .unwrap_or_default()
+    }
+
+    #[actix_rt::test]
+    async fn test_limit_per_user() {
+        let app = app!("[rate_limit]\ncapacity = 3\nrefill_per_second = 0.5");
+        for remaining in ["2", "1", "0"] {
+            let res = test::call_service(&app, get!("/Projects", "alice")).await;
+            assert_eq!(res.status(), 200);
+            assert_eq!(header(&res, "x-ratelimit-limit"), "3");
+            assert_eq!(header(&res, "x-ratelimit-remaining"), remaining);
+            assert!(header(&res, "retry-after").is_empty());
+        }
+        let res = test::call_service(&app, get!("/Projects", "alice")).await;
+        assert_eq!(res.status(), 429);
+        // One token takes 2 seconds, the full bucket 6 seconds
+        assert_eq!(header(&res, "retry-after"), "2");
+        assert_eq!(header(&res, "x-ratelimit-reset"), "6");
+        let body: ErrorBody = test::read_body_json(res).await;
+        assert_eq!(body.code, "too_many_requests");
+        assert!(!body.request_id.is_empty());
+        // Another user still has a full bucket
+        let res = test::call_service(&app, get!("/Projects", "bob")).await;
+        assert_eq!(res.status(), 200);
+    }
+
+    #[actix_rt::test]
+    async fn test_limit_per_ip_without_user() {
+        let verifications = Arc::new(AtomicUsize::new(0));
+        let app = app!(
+            "[rate_limit]\ncapacity = 2\nrefill_per_second = 0.1",
+            verifications.clone()
+        );
+        let from = |ip: &str, token: &str| {
+            test::TestRequest::get()
+                .uri("/Projects")
+                .peer_addr(format!("{}:4000", ip).parse().unwrap())
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .to_request()
+        };
+        // Rejected tokens are counted against the address and get the headers as well
+        let res = test::call_service(&app, from("10.0.0.1", "invalid")).await;
+        assert_eq!(res.status(), 401);
+        assert_eq!(header(&res, "x-ratelimit-remaining"), "1");
+        // Only the auth middleware verified the token
+        assert_eq!(verifications.load(Ordering::SeqCst), 1);
+        let req = test::TestRequest::get()
+            .uri("/Projects")
+            .peer_addr("10.0.0.1:4000".parse().unwrap())
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 401);
+        assert_eq!(header(&res, "x-ratelimit-remaining"), "0");
+        let res = test::call_service(&app, from("10.0.0.1", "invalid")).await;
+        assert_eq!(res.status(), 429);
+        assert_eq!(header(&res, "retry-after"), "10");
+        // A limited request never reaches the verifier
+        assert_eq!(verifications.load(Ordering::SeqCst), 1);
+        // Another address and a valid user of the same address have their own buckets
+        let res = test::call_service(&app, from("10.0.0.2", "invalid")).await;
+        assert_eq!(res.status(), 401);
+        let res = test::call_service(&app, from("10.0.0.1", &token("alice"))).await;
+        assert_eq!(res.status(), 200);
+    }
+
+    #[actix_rt::test]
+    async fn test_route_groups() {
+        let app = app!(
+            r#"
+            [rate_limit]
+            capacity = 5
+            refill_per_second = 0.1
+
+            [[rate_limit.groups]]
+            name = "writes"
+            methods = ["POST"]
+            capacity = 1
+            refill_per_second = 0.1
+
+            [[rate_limit.groups]]
+            name = "reports"
+            prefix = "/Reports"
+            capacity = 2
+            refill_per_second = 0.1
+            "#
+        );
+        let post = || {
+            test::TestRequest::post()
+                .uri("/Projects")
+                .insert_header(("Authorization", format!("Bearer {}", token("alice"))))
+                .to_request()
+        };
+        assert_eq!(test::call_service(&app, post()).await.status(), 200);
+        assert_eq!(test::call_service(&app, post()).await.status(), 429);
+        // The groups have their own buckets
+        let res = test::call_service(&app, get!("/Reports", "alice")).await;
+        assert_eq!(header(&res, "x-ratelimit-limit"), "2");
+        let res = test::call_service(&app, get!("/Projects", "alice")).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(header(&res, "x-ratelimit-limit"), "5");
+    }
+
+    #[actix_rt::test]
+    async fn test_group_prefix_matches_whole_segments() {
+        let limiter = RateLimiter::new(
+            &Config::from_toml(
+                "[rate_limit]\ncapacity = 5\n[[rate_limit.groups]]\nname = \"materials\"\nprefix = \"/Materials\"\ncapacity = 1\nrefill_per_second = 0.1",
+            )
+            .unwrap()
+            .rate_limit,
+        );
+        for (path, limit) in [
+            ("/Materials", 1),
+            ("/Materials/LowStock", 1),
+            ("/Materials/5/Movements", 1),
+            ("/MaterialTypes", 5),
+            ("/MaterialsExport", 5),
+        ] {
+            assert_eq!(limiter.check("GET", path, "alice").limit, limit, "{}", path);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_bucket_refills() {
+        let app = app!("[rate_limit]\ncapacity = 1\nrefill_per_second = 20.0");
+        assert_eq!(
+            test::call_service(&app, get!("/Projects", "alice"))
+                .await
+                .status(),
+            200
+        );
+        assert_eq!(
+            test::call_service(&app, get!("/Projects", "alice"))
+                .await
+                .status(),
+            429
+        );
+        actix_rt::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(
+            test::call_service(&app, get!("/Projects", "alice"))
+                .await
+                .status(),
+            200
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_disabled() {
+        let app = app!("[rate_limit]\nenabled = false\ncapacity = 1");
+        for _ in 0..3 {
+            let res = test::call_service(&app, get!("/Projects", "alice")).await;
+            assert_eq!(res.status(), 200);
+            assert!(header(&res, "x-ratelimit-limit").is_empty());
+        }
+    }
+}
@@ -9,20 +9,33 @@
 
 #[cfg(test)]
 mod tests {
-    use crate::models::client::Client;
-    use crate::models::material::Material;
-    use crate::models::material_type::MaterialType;
-    use crate::models::project::Project;
-    use crate::models::project_material::ProjectMaterial;
+    use crate::models::client::{Client, NewClient};
+    use crate::models::material::{Material, NewMaterial};
+    use crate::models::material_type::{MaterialType, NewMaterialType};
+    use crate::models::project::{NewProject, Project, ProjectStatus};
+    use crate::models::project_material::{NewProjectMaterial, ProjectMaterial};
+    use crate::models::stock_movement::Booking;
+    use crate::utils::config::{Args, Config};
     use crate::utils::keycloak::Keycloak;
     use crate::utils::repository::Repository;
+    use crate::utils::store::query::ListQuery;
+
+    // Load the configuration of the test instance (config.toml, WMS_CONFIG or WMS_* variables)
+    fn test_config() -> Config {
+        Config::load_from(&Args::default(), |key| std::env::var(key).ok()).unwrap()
+    }
+
+    // The stock movements of the project materials are booked for this user
+    fn test_booking() -> Booking {
+        Booking::new("test".to_string(), false)
+    }
 
     /*
 
         Add a function to keycloak to get a token (Usually only needed in frontend)
 
     */
-    impl<'a> Keycloak<'a> {
+    impl Keycloak {
         pub async fn login_user(
             &mut self,
             username: &str,
@@ -30,16 +43,16 @@ mod tests {
         ) -> Result<String, reqwest::Error> {
             // Send a request to keycloak to get a token
             let response = reqwest::Client::new()
-                .post(&format!(
+                .post(format!(
                     "{}/realms/{}/protocol/openid-connect/token",
                     &self.api_url, &self.realm
                 ))
                 .form(&[
-                    ("client_id", &self.client_id),
-                    ("client_secret", &self.client_secret),
-                    ("username", &username),
-                    ("password", &password),
-                    ("grant_type", &"password"),
+                    ("client_id", self.client_id.as_str()),
+                    ("client_secret", self.client_secret.as_str()),
+                    ("username", username),
+                    ("password", password),
+                    ("grant_type", "password"),
                 ])
                 .timeout(std::time::Duration::from_secs(5))
                 .send()
@@ -68,6 +81,7 @@ mod tests {
                 firstname: "John".to_string(),
                 lastname: "Doe".to_string(),
                 phone: "123456789".to_string(),
+                version: 1,
             }
         }
 
@@ -77,6 +91,7 @@ mod tests {
                 firstname: "Jane".to_string(),
                 lastname: "Doe".to_string(),
                 phone: "987654321".to_string(),
+                version: 1,
             }
         }
     }
@@ -87,6 +102,7 @@ mod tests {
                 id: 1,
                 name: "Wood".to_string(),
                 description: "A material that comes from trees.".to_string(),
+                version: 1,
             }
         }
 
@@ -95,6 +111,7 @@ mod tests {
                 id: 1,
                 name: "Metal".to_string(),
                 description: "A material that comes from the ground.".to_string(),
+                version: 1,
             }
         }
     }
@@ -107,8 +124,10 @@ mod tests {
                 description: "A material that comes from trees.".to_string(),
                 type_id: 1,
                 amount: 100,
-                costs: 10.0,
+                costs: "10.00 EUR".parse().unwrap(),
                 threshold_value: 50,
+                preferred_supplier_id: None,
+                version: 1,
             }
         }
 
@@ -119,8 +138,10 @@ mod tests {
                 description: "A material that comes from the ground.".to_string(),
                 type_id: 1,
                 amount: 100,
-                costs: 10.0,
+                costs: "10.00 EUR".parse().unwrap(),
                 threshold_value: 50,
+                preferred_supplier_id: None,
+                version: 1,
             }
         }
     }
@@ -132,6 +153,7 @@ mod tests {
                 project_id: 1,
                 material_id: 1,
                 amount: 10,
+                version: 1,
             }
         }
 
@@ -141,6 +163,66 @@ mod tests {
                 project_id: 1,
                 material_id: 1,
                 amount: 20,
+                version: 1,
+            }
+        }
+    }
+
+    // The test data without its id (The id is assigned by the database)
+    impl From<Client> for NewClient {
+        fn from(value: Client) -> NewClient {
+            NewClient {
+                firstname: value.firstname,
+                lastname: value.lastname,
+                phone: value.phone,
+            }
+        }
+    }
+
+    impl From<MaterialType> for NewMaterialType {
+        fn from(value: MaterialType) -> NewMaterialType {
+            NewMaterialType {
+                name: value.name,
+                description: value.description,
+            }
+        }
+    }
+
+    impl From<Material> for NewMaterial {
+        fn from(value: Material) -> NewMaterial {
+            NewMaterial {
+                name: value.name,
+                description: value.description,
+                type_id: value.type_id,
+                amount: value.amount,
+                costs: value.costs,
+                threshold_value: value.threshold_value,
+                preferred_supplier_id: value.preferred_supplier_id,
+            }
+        }
+    }
+
+    impl From<Project> for NewProject {
+        fn from(value: Project) -> NewProject {
+            NewProject {
+                name: value.name,
+                client_id: value.client_id,
+                description: value.description,
+                startpoint: value.startpoint,
+                endpoint: value.endpoint,
+                estimated_costs: value.estimated_costs,
+                estimated_hours: value.estimated_hours,
+                costs: value.costs,
+            }
+        }
+    }
+
+    impl From<ProjectMaterial> for NewProjectMaterial {
+        fn from(value: ProjectMaterial) -> NewProjectMaterial {
+            NewProjectMaterial {
+                project_id: value.project_id,
+                material_id: value.material_id,
+                amount: value.amount,
             }
         }
     }
@@ -152,11 +234,13 @@ mod tests {
                 name: "Table".to_string(),
                 client_id: 1,
                 description: "A table made out of wood.".to_string(),
-                startpoint: 1000000000.0,
-                endpoint: 1000000000.0,
-                estimated_costs: 100.0,
+                startpoint: "2026-10-01T08:00:00Z".parse().unwrap(),
+                endpoint: "2026-10-20T17:00:00Z".parse().unwrap(),
+                estimated_costs: "100.00 EUR".parse().unwrap(),
                 estimated_hours: 10.0,
-                costs: 100.0,
+                costs: "100.00 EUR".parse().unwrap(),
+                status: ProjectStatus::Inquiry,
+                version: 1,
             }
         }
 
@@ -166,11 +250,13 @@ mod tests {
                 name: "Chair".to_string(),
                 client_id: 1,
                 description: "A chair made out of wood.".to_string(),
-                startpoint: 1000000000.0,
-                endpoint: 1000000000.0,
-                estimated_costs: 100.0,
+                startpoint: "2026-10-01T08:00:00Z".parse().unwrap(),
+                endpoint: "2026-10-20T17:00:00Z".parse().unwrap(),
+                estimated_costs: "100.00 EUR".parse().unwrap(),
                 estimated_hours: 10.0,
-                costs: 100.0,
+                costs: "100.00 EUR".parse().unwrap(),
+                status: ProjectStatus::Inquiry,
+                version: 1,
             }
         }
     }
@@ -181,15 +267,26 @@ mod tests {
 
     */
 
+    // The token of the test user is accepted by keycloak
+    #[actix_rt::test]
+    async fn test_keycloak_introspect() {
+        let config = test_config();
+        let mut keycloak = Keycloak::new(&config.keycloak);
+        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
+        let principal = keycloak.introspect(&token).await.unwrap();
+        assert!(!principal.subject.is_empty());
+        // A made up token is rejected
+        let result = keycloak.introspect("invalid").await;
+        assert_eq!(result.unwrap_err().code, 401);
+    }
+
     // Testing add methods (Need to do it in one function because of the specific order and async would randomize it)
     // Only commented the first one because the other test are mostly the same but with different function calls
     #[actix_rt::test]
     async fn test_add_values() {
-        // Create a new repository and keycloak instance
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        // Get a token from keycloak
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
+        // Create a new repository (The permissions are checked by the auth middleware, see auth_tests)
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
         // Create test data
         let client = Client::test_data();
         let material_type = MaterialType::test_data();
@@ -197,17 +294,17 @@ mod tests {
         let project = Project::test_data();
         let project_material = ProjectMaterial::test_data();
         // Add the test data to the repository
-        let result = repository.add_client(client, &token).await;
+        let result = repository.add_client(client.into()).await;
         // Check if the result is ok
         assert!(result.is_ok());
-        let result = repository.add_material_type(material_type, &token).await;
+        let result = repository.add_material_type(material_type.into()).await;
         assert!(result.is_ok());
-        let result = repository.add_material(material, &token).await;
+        let result = repository.add_material(material.into()).await;
         assert!(result.is_ok());
-        let result = repository.add_project(project, &token).await;
+        let result = repository.add_project(project.into()).await;
         assert!(result.is_ok());
         let result = repository
-            .add_project_material(project_material, &token)
+            .add_project_material(project_material.into(), test_booking())
             .await;
         assert!(result.is_ok());
     }
@@ -215,144 +312,139 @@ mod tests {
     // Testing get all methods
     #[actix_rt::test]
     async fn test_get_all_projects() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let result = repository.get_all_projects(&token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        let result = repository.get_all_projects(ListQuery::default()).await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_get_all_clients() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let result = repository.get_all_clients(&token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        let result = repository.get_all_clients(ListQuery::default()).await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_get_all_materials() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let result = repository.get_all_materials(&token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        let result = repository.get_all_materials(ListQuery::default()).await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_get_all_material_types() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let result = repository.get_all_material_types(&token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        let result = repository
+            .get_all_material_types(ListQuery::default())
+            .await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_get_all_project_materials() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let result = repository.get_all_project_materials(&token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        let result = repository
+            .get_all_project_materials(ListQuery::default())
+            .await;
         assert!(result.is_ok());
     }
 
     // Testing single get methods
     #[actix_rt::test]
     async fn test_get_project() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let result = repository.get_project(1, &token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        let result = repository.get_project(1).await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_get_client() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let result = repository.get_client(1, &token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        let result = repository.get_client(1).await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_get_material() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let result = repository.get_material(1, &token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        let result = repository.get_material(1).await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_get_material_type() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let result = repository.get_material_type(1, &token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        let result = repository.get_material_type(1).await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_get_project_material() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let result = repository.get_project_material(1, &token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        let result = repository.get_project_material(1).await;
         assert!(result.is_ok());
     }
 
     // Testing Update Methods
     #[actix_rt::test]
     async fn test_update_project() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
         let project = Project::test_update_data();
-        let result = repository.update_project(project, &token).await;
+        let result = repository.update_project(project, None).await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_update_client() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
         let client = Client::test_update_data();
-        let result = repository.update_client(client, &token).await;
+        let result = repository.update_client(client, None).await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_update_material() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let material = Material::test_update_data();
-        let result = repository.update_material(material, &token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        // The amount is only changed by stock movements, so the stored one is sent
+        let current = repository.get_material(1).await.unwrap();
+        let material = Material {
+            amount: current.amount,
+            version: current.version,
+            ..Material::test_update_data()
+        };
+        let result = repository.update_material(material, None).await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_update_material_type() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
         let material_type = MaterialType::test_update_data();
-        let result = repository.update_material_type(material_type, &token).await;
+        let result = repository.update_material_type(material_type, None).await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_update_project_material() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
         let project_material = ProjectMaterial::test_update_data();
         let result = repository
-            .update_project_material(project_material, &token)
+            .update_project_material(project_material, None, test_booking())
             .await;
         assert!(result.is_ok());
     }
@@ -360,46 +452,48 @@ mod tests {
     // Testing remove methods
     #[actix_rt::test]
     async fn test_remove_project() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let result = repository.remove_project(1, &token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        let result = repository.remove_project(1, None).await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_remove_client() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let result = repository.remove_client(1, &token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        let result = repository.remove_client(1, None).await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_remove_material() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let result = repository.remove_material(1, &token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        // Material 1 has stock movements, only a material without a ledger can be removed
+        let material = repository
+            .add_material(Material::test_data().into())
+            .await
+            .unwrap();
+        let result = repository.remove_material(material.id, None).await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_remove_material_type() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let result = repository.remove_material_type(1, &token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        let result = repository.remove_material_type(1, None).await;
         assert!(result.is_ok());
     }
 
     #[actix_rt::test]
     async fn test_remove_project_material() {
-        let mut repository = Repository::new();
-        let mut keycloak = Keycloak::new();
-        let token = keycloak.login_user("test_user", "test_user").await.unwrap();
-        let result = repository.remove_project_material(1, &token).await;
+        let config = test_config();
+        let repository = Repository::new(&config).unwrap();
+        let result = repository
+            .remove_project_material(1, None, test_booking())
+            .await;
         assert!(result.is_ok());
     }
 }
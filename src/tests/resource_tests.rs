@@ -0,0 +1,524 @@
+/*
+
+    Description: This file contains the tests for creating and changing single resources over HTTP (Status codes,
+                 headers and the returned bodies).
+    How to run: cargo test
+
+*/
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::dev::ServiceResponse;
+    use actix_web::test::{call_service, init_service, read_body, read_body_json, TestRequest};
+    use actix_web::{web::Data, App, HttpMessage};
+    use serde_json::json;
+
+    use crate::models::client::Client;
+    use crate::models::error::ErrorBody;
+    use crate::models::material::Material;
+    use crate::models::stock_movement::StockMovement;
+    use crate::utils::auth::Principal;
+    use crate::utils::controller;
+    use crate::utils::repository::Repository;
+    use crate::utils::store::memory::MemoryStore;
+
+    macro_rules! app {
+        () => {
+            init_service(
+                App::new()
+                    .app_data(Data::new(Repository::with_store(Arc::new(
+                        MemoryStore::new(),
+                    ))))
+                    .configure(controller::configure),
+            )
+            .await
+        };
+    }
+
+    macro_rules! post {
+        ($uri:expr, $body:expr) => {
+            TestRequest::post().uri($uri).set_json($body).to_request()
+        };
+    }
+
+    macro_rules! put {
+        ($uri:expr, $body:expr) => {
+            TestRequest::put().uri($uri).set_json($body).to_request()
+        };
+    }
+
+    // A merge patch with its own content type
+    macro_rules! patch {
+        ($uri:expr, $body:expr) => {
+            TestRequest::patch()
+                .uri($uri)
+                .insert_header(("Content-Type", "application/merge-patch+json"))
+                .set_payload($body.to_string())
+                .to_request()
+        };
+    }
+
+    // Material 1 of material type 1
+    macro_rules! add_material {
+        ($app:expr) => {
+            let body = json!({"name": "Wood", "description": ""});
+            call_service(&$app, post!("/MaterialTypes", &body)).await;
+            let body = json!({
+                "name": "Oak board",
+                "description": "2m",
+                "type_id": 1,
+                "amount": 10,
+                "costs": 5.0,
+                "threshold_value": 2
+            });
+            call_service(&$app, post!("/Materials", &body)).await;
+        };
+    }
+
+    macro_rules! get_material {
+        ($app:expr) => {{
+            let req = TestRequest::get().uri("/Materials/1").to_request();
+            let material: Material = read_body_json(call_service(&$app, req).await).await;
+            material
+        }};
+    }
+
+    #[actix_rt::test]
+    async fn test_create_returns_the_resource_and_its_location() {
+        let app = app!();
+        let body = json!({"firstname": "John", "lastname": "Doe", "phone": "089 12345"});
+        let res = call_service(&app, post!("/Clients", &body)).await;
+        assert_eq!(res.status(), 201);
+        assert_eq!(res.headers().get("Location").unwrap(), "/Clients/1");
+        let client: Client = read_body_json(res).await;
+        assert_eq!((client.id, client.lastname.as_str()), (1, "Doe"));
+        // An id in the body is ignored, the next id is assigned
+        let body = json!({"id": 7, "firstname": "Jane", "lastname": "Doe", "phone": ""});
+        let res = call_service(&app, post!("/Clients", &body)).await;
+        assert_eq!(res.headers().get("Location").unwrap(), "/Clients/2");
+        // The location can be read
+        let req = TestRequest::get().uri("/Clients/2").to_request();
+        let client: Client = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(client.firstname, "Jane");
+    }
+
+    #[actix_rt::test]
+    async fn test_create_with_unknown_reference_is_rejected() {
+        let app = app!();
+        let body = json!({
+            "name": "Oak board",
+            "description": "",
+            "type_id": 1,
+            "amount": 1,
+            "costs": 1.0,
+            "threshold_value": 0
+        });
+        let res = call_service(&app, post!("/Materials", &body)).await;
+        assert_eq!(res.status(), 422);
+        assert!(res.headers().get("Location").is_none());
+        let body: ErrorBody = read_body_json(res).await;
+        assert_eq!(body.code, "foreign_key_violation");
+        // Nothing was stored
+        let req = TestRequest::get().uri("/Materials").to_request();
+        let materials: Vec<Material> = read_body_json(call_service(&app, req).await).await;
+        assert!(materials.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_put_uses_the_id_of_the_path() {
+        let app = app!();
+        add_material!(app);
+        let mut material = get_material!(app);
+        material.name = "Beech board".to_string();
+        // The body points to another material
+        material.id = 2;
+        let res = call_service(&app, put!("/Materials/1", &material)).await;
+        assert_eq!(res.status(), 422);
+        let body: ErrorBody = read_body_json(res).await;
+        assert_eq!(body.code, "id_mismatch");
+        assert_eq!(get_material!(app).name, "Oak board");
+        // Same id in the path and the body
+        material.id = 1;
+        let res = call_service(&app, put!("/Materials/1", &material)).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(get_material!(app).name, "Beech board");
+    }
+
+    #[actix_rt::test]
+    async fn test_patch_only_changes_the_given_fields() {
+        let app = app!();
+        add_material!(app);
+        let res = call_service(&app, patch!("/Materials/1", json!({"threshold_value": 7}))).await;
+        assert_eq!(res.status(), 200);
+        let material: Material = read_body_json(res).await;
+        assert_eq!(
+            (material.threshold_value, material.name.as_str()),
+            (7, "Oak board")
+        );
+        let material = get_material!(app);
+        assert_eq!(
+            (material.id, material.threshold_value, material.amount),
+            (1, 7, 10)
+        );
+        assert_eq!(material.costs.to_string(), "5.00 EUR");
+        // The same id may be sent along, plain JSON works too
+        let req = TestRequest::patch()
+            .uri("/Materials/1")
+            .set_json(json!({"id": 1, "description": "3m"}))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 200);
+        assert_eq!(get_material!(app).description, "3m");
+    }
+
+    #[actix_rt::test]
+    async fn test_invalid_patches_are_rejected() {
+        let app = app!();
+        add_material!(app);
+        for (patch, code) in [
+            // The id cannot be changed
+            (json!({"id": 2}), "id_mismatch"),
+            (json!({"id": null}), "id_mismatch"),
+            // Removing a required field, the wrong type or an unknown field
+            (json!({"name": null}), "unprocessable_entity"),
+            (json!({"amount": "many"}), "unprocessable_entity"),
+            (json!({"amuont": 1}), "unprocessable_entity"),
+        ] {
+            let res = call_service(&app, patch!("/Materials/1", patch)).await;
+            assert_eq!(res.status(), 422, "{}", patch);
+            let body: ErrorBody = read_body_json(res).await;
+            assert_eq!(body.code, code, "{}", patch);
+        }
+        // Nothing was changed
+        let material = get_material!(app);
+        assert_eq!((material.name.as_str(), material.amount), ("Oak board", 10));
+        let res = call_service(&app, patch!("/Materials/9", json!({"amount": 1}))).await;
+        assert_eq!(res.status(), 404);
+    }
+
+    fn etag<B>(res: &ServiceResponse<B>) -> String {
+        res.headers()
+            .get("ETag")
+            .map(|value| value.to_str().unwrap().to_string())
+            .unwrap_or_default()
+    }
+
+    #[actix_rt::test]
+    async fn test_get_returns_the_etag_and_304_if_unchanged() {
+        let app = app!();
+        add_material!(app);
+        let req = TestRequest::get().uri("/Materials/1").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(etag(&res), "\"1\"");
+        // The client already has the current version
+        for tag in ["\"1\"", "W/\"1\"", "\"7\", \"1\"", "*"] {
+            let req = TestRequest::get()
+                .uri("/Materials/1")
+                .insert_header(("If-None-Match", tag))
+                .to_request();
+            let res = call_service(&app, req).await;
+            assert_eq!(res.status(), 304, "{}", tag);
+            assert_eq!(etag(&res), "\"1\"");
+            assert!(read_body(res).await.is_empty());
+        }
+        // After an update the old tag gets the new version
+        call_service(&app, patch!("/Materials/1", json!({"threshold_value": 3}))).await;
+        let req = TestRequest::get()
+            .uri("/Materials/1")
+            .insert_header(("If-None-Match", "\"1\""))
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(etag(&res), "\"2\"");
+    }
+
+    #[actix_rt::test]
+    async fn test_if_match_prevents_lost_updates() {
+        let app = app!();
+        add_material!(app);
+        let material = get_material!(app);
+        // The first client updates with the version it read
+        let req = TestRequest::put()
+            .uri("/Materials/1")
+            .insert_header(("If-Match", "\"1\""))
+            .set_json(Material {
+                threshold_value: 8,
+                ..material.clone()
+            })
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(etag(&res), "\"2\"");
+        // The second client read the same version
+        let req = TestRequest::put()
+            .uri("/Materials/1")
+            .insert_header(("If-Match", "\"1\""))
+            .set_json(Material {
+                threshold_value: 9,
+                ..material
+            })
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), 412);
+        let body: ErrorBody = read_body_json(res).await;
+        assert_eq!(body.code, "precondition_failed");
+        let req = TestRequest::patch()
+            .uri("/Materials/1")
+            .insert_header(("If-Match", "\"1\""))
+            .set_json(json!({"threshold_value": 9}))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 412);
+        let req = TestRequest::delete()
+            .uri("/Materials/1")
+            .insert_header(("If-Match", "\"1\""))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 412);
+        assert_eq!(get_material!(app).threshold_value, 8);
+        // Weak tags never match, several versions are not supported
+        for (tag, status) in [("W/\"2\"", 412), ("\"1\", \"2\"", 400)] {
+            let req = TestRequest::delete()
+                .uri("/Materials/1")
+                .insert_header(("If-Match", tag))
+                .to_request();
+            assert_eq!(call_service(&app, req).await.status(), status, "{}", tag);
+        }
+        // The current version
+        let req = TestRequest::delete()
+            .uri("/Materials/1")
+            .insert_header(("If-Match", "\"2\""))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 200);
+    }
+
+    #[actix_rt::test]
+    async fn test_invalid_bodies_are_rejected_with_their_fields() {
+        let app = app!();
+        let body = json!({"firstname": " ", "lastname": "Doe", "phone": "call me"});
+        let res = call_service(&app, post!("/Clients", &body)).await;
+        assert_eq!(res.status(), 422);
+        let body: ErrorBody = read_body_json(res).await;
+        assert_eq!(body.code, "validation_failed");
+        let fields: Vec<&str> = body.fields.iter().map(|f| f.field.as_str()).collect();
+        assert_eq!(fields, vec!["firstname", "phone"]);
+        // Nothing was stored
+        let req = TestRequest::get().uri("/Clients/1").to_request();
+        assert_eq!(call_service(&app, req).await.status(), 404);
+        // Updates and patches are checked as well
+        add_material!(app);
+        let mut material = get_material!(app);
+        material.amount = -1;
+        let res = call_service(&app, put!("/Materials/1", &material)).await;
+        assert_eq!(res.status(), 422);
+        let res = call_service(&app, patch!("/Materials/1", json!({"costs": -2.5}))).await;
+        assert_eq!(res.status(), 422);
+        let body: ErrorBody = read_body_json(res).await;
+        assert_eq!(body.fields[0].field, "costs");
+        assert_eq!(get_material!(app).version, 1);
+    }
+
+    // Bodies and ids the extractors cannot read get the error body of every other error
+    #[actix_rt::test]
+    async fn test_unreadable_requests_get_the_error_body() {
+        let app = app!();
+        let req = TestRequest::post()
+            .uri("/Clients")
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload("{\"firstname\": ")
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+        let body: ErrorBody = read_body_json(res).await;
+        assert_eq!((body.code.as_str(), body.status), ("bad_request", 400));
+        let req = TestRequest::post()
+            .uri("/Clients")
+            .insert_header(("Content-Type", "text/plain"))
+            .set_payload("{}")
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+        let body: ErrorBody = read_body_json(res).await;
+        assert!(body.message.contains("Content type"), "{}", body.message);
+        let req = TestRequest::get().uri("/Clients/abc").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), 404);
+        let body: ErrorBody = read_body_json(res).await;
+        assert_eq!(body.code, "not_found");
+    }
+
+    // Values that do not fit the model are answered like the validation, with the path of the field
+    #[actix_rt::test]
+    async fn test_undecodable_fields_are_named() {
+        let app = app!();
+        let project = json!({
+            "name": "Table",
+            "client_id": 1,
+            "description": "",
+            "startpoint": "2026-10-18",
+            "endpoint": "2026-10-20",
+            "estimated_costs": 100.0,
+            "estimated_hours": 10.0,
+            "costs": 0.0
+        });
+        for (key, value, field) in [
+            ("estimated_costs", json!("a lot"), "estimated_costs"),
+            ("startpoint", json!("tomorrow"), "startpoint"),
+            ("name", serde_json::Value::Null, "name"),
+        ] {
+            let mut body = project.clone();
+            if value.is_null() {
+                body.as_object_mut().unwrap().remove(key);
+            } else {
+                body[key] = value;
+            }
+            let res = call_service(&app, post!("/Projects", &body)).await;
+            assert_eq!(res.status(), 422, "{}", key);
+            let body: ErrorBody = read_body_json(res).await;
+            assert_eq!(body.code, "validation_failed");
+            assert_eq!(body.fields[0].field, field);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_project_dates_are_iso_8601_in_utc() {
+        let app = app!();
+        let body = json!({"firstname": "John", "lastname": "Doe", "phone": ""});
+        call_service(&app, post!("/Clients", &body)).await;
+        let body = json!({
+            "name": "Table",
+            "client_id": 1,
+            "description": "",
+            "startpoint": "2026-10-18T08:30:00.250+02:00",
+            "endpoint": "2026-10-20",
+            "estimated_costs": 100.0,
+            "estimated_hours": 10.0,
+            "costs": 0.0
+        });
+        let res = call_service(&app, post!("/Projects", &body)).await;
+        assert_eq!(res.status(), 201);
+        let project: serde_json::Value = read_body_json(res).await;
+        assert_eq!(project["startpoint"], "2026-10-18T06:30:00Z");
+        assert_eq!(project["endpoint"], "2026-10-20T00:00:00Z");
+        // Old clients still send seconds since 1970
+        let res = call_service(&app, patch!("/Projects/1", json!({"endpoint": 1792515600}))).await;
+        let project: serde_json::Value = read_body_json(res).await;
+        assert_eq!(project["endpoint"], "2026-10-20T17:00:00Z");
+        let res = call_service(
+            &app,
+            patch!("/Projects/1", json!({"endpoint": "next week"})),
+        )
+        .await;
+        assert_eq!(res.status(), 422);
+        // Listed by the day they are active on
+        let req = TestRequest::get()
+            .uri("/Projects?active_on=2026-10-19")
+            .to_request();
+        let projects: Vec<serde_json::Value> = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(projects.len(), 1);
+        let req = TestRequest::get()
+            .uri("/Projects?active_on=soon")
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400);
+    }
+
+    // A request that books stock movements for a user (The auth middleware is not part of these tests)
+    macro_rules! book {
+        ($app:expr, $uri:expr, $body:expr) => {{
+            let req = post!($uri, $body);
+            req.extensions_mut().insert(Principal {
+                subject: "user-1".to_string(),
+                roles: vec!["worker".to_string()],
+            });
+            call_service(&$app, req).await
+        }};
+    }
+
+    #[actix_rt::test]
+    async fn test_stock_is_changed_by_movements() {
+        let app = app!();
+        add_material!(app);
+        let body = json!({"kind": "consume", "quantity": 4, "reason": "Table"});
+        let res = book!(app, "/Materials/1/Movements", &body);
+        assert_eq!(res.status(), 201);
+        // The ledger of the material (A movement is not a resource of its own)
+        assert_eq!(
+            res.headers().get("Location").unwrap(),
+            "/Materials/1/Movements"
+        );
+        let movement: StockMovement = read_body_json(res).await;
+        assert_eq!(
+            (movement.amount_after, movement.user_id.as_str()),
+            (6, "user-1")
+        );
+        let material = get_material!(app);
+        assert_eq!((material.amount, material.version), (6, 2));
+        // More than the stock is a conflict
+        let body = json!({"kind": "consume", "quantity": 7});
+        let res = book!(app, "/Materials/1/Movements", &body);
+        assert_eq!(res.status(), 409);
+        let body: ErrorBody = read_body_json(res).await;
+        assert_eq!(body.code, "insufficient_stock");
+        // An adjust needs a reason
+        let body = json!({"kind": "adjust", "quantity": -1, "reason": " "});
+        let res = book!(app, "/Materials/1/Movements", &body);
+        assert_eq!(res.status(), 422);
+        let body: ErrorBody = read_body_json(res).await;
+        assert_eq!(body.fields[0].field, "reason");
+        let body = json!({"kind": "receive", "quantity": 1});
+        assert_eq!(book!(app, "/Materials/9/Movements", &body).status(), 404);
+        // The ledger of the material
+        let req = TestRequest::get()
+            .uri("/Materials/1/Movements?kind=consume")
+            .to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.headers().get("X-Total-Count").unwrap(), "1");
+        let movements: Vec<StockMovement> = read_body_json(res).await;
+        assert_eq!(movements[0].reason, "Table");
+        let req = TestRequest::get()
+            .uri("/Materials/9/Movements")
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 404);
+        // The amount cannot be changed by an update
+        let res = call_service(&app, patch!("/Materials/1", json!({"amount": 50}))).await;
+        assert_eq!(res.status(), 422);
+        let body: ErrorBody = read_body_json(res).await;
+        assert_eq!(body.fields[0].field, "amount");
+        assert_eq!(get_material!(app).amount, 6);
+    }
+
+    #[actix_rt::test]
+    async fn test_project_materials_take_the_stock() {
+        let app = app!();
+        add_material!(app);
+        let body = json!({"firstname": "John", "lastname": "Doe", "phone": ""});
+        call_service(&app, post!("/Clients", &body)).await;
+        let body = json!({
+            "name": "Table",
+            "client_id": 1,
+            "description": "",
+            "startpoint": "2026-10-01",
+            "endpoint": "2026-10-20",
+            "estimated_costs": "100.00 EUR",
+            "estimated_hours": 10.0,
+            "costs": "0.00 EUR"
+        });
+        call_service(&app, post!("/Projects", &body)).await;
+        let body = json!({"project_id": 1, "material_id": 1, "amount": 12});
+        let res = book!(app, "/ProjectMaterials", &body);
+        assert_eq!(res.status(), 409);
+        assert_eq!(get_material!(app).amount, 10);
+        // A backorder takes it anyway
+        let res = book!(app, "/ProjectMaterials?backorder=true", &body);
+        assert_eq!(res.status(), 201);
+        let material = get_material!(app);
+        assert_eq!(material.amount, -2);
+        // The material can still be changed while it is backordered
+        let res = call_service(&app, put!("/Materials/1", &material)).await;
+        assert_eq!(res.status(), 200);
+        let req = TestRequest::get()
+            .uri("/Materials/1/Movements")
+            .to_request();
+        let movements: Vec<StockMovement> = read_body_json(call_service(&app, req).await).await;
+        assert_eq!(movements[0].reason, "Project 1");
+    }
+}
@@ -0,0 +1,243 @@
+/*
+
+    Description: This file contains the tests for the search across clients, projects and materials. Every backend
+                 has to find and rank the same hits.
+    How to run: cargo test
+
+*/
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+    use actix_web::{middleware, web::Data, App};
+
+    use crate::models::client::NewClient;
+    use crate::models::error::Error;
+    use crate::models::material::Material;
+    use crate::models::material_type::MaterialType;
+    use crate::models::project::Project;
+    use crate::models::search::{SearchHit, SearchResults};
+    use crate::utils::auth::{self, Authorizer, Principal, TokenVerifier, Verification};
+    use crate::utils::config::DatabaseConfig;
+    use crate::utils::controller;
+    use crate::utils::policy::Policy;
+    use crate::utils::repository::Repository;
+    use crate::utils::store::memory::MemoryStore;
+    use crate::utils::store::migrations;
+    use crate::utils::store::search::{SearchQuery, CANDIDATES};
+    use crate::utils::store::sqlite::SqliteStore;
+    use crate::utils::store::WorkshopStore;
+
+    fn sqlite_store() -> SqliteStore {
+        let store = SqliteStore::open(&DatabaseConfig {
+            url: "sqlite://:memory:".to_string(),
+            ..DatabaseConfig::default()
+        })
+        .unwrap();
+        migrations::migrate_up(&store, None).unwrap();
+        store
+    }
+
+    fn add_values(store: &dyn WorkshopStore) {
+        // The clients get the ids 1 to 3 and the projects 1 and 2
+        for (firstname, lastname, phone) in [
+            ("Jane", "Doering", "089 12345"),
+            ("John", "Doe", "0171-555 1234"),
+            ("Bob", "Smith", "0171 999"),
+        ] {
+            store
+                .add_client(NewClient {
+                    firstname: firstname.to_string(),
+                    lastname: lastname.to_string(),
+                    phone: phone.to_string(),
+                })
+                .unwrap();
+        }
+        for (name, description) in [
+            ("Chair", "Oak chair to match the table"),
+            ("Kitchen table", "For the Doe family"),
+        ] {
+            store
+                .add_project(
+                    Project {
+                        name: name.to_string(),
+                        description: description.to_string(),
+                        client_id: 2,
+                        ..Project::test_data()
+                    }
+                    .into(),
+                )
+                .unwrap();
+        }
+        store
+            .add_material_type(MaterialType::test_data().into())
+            .unwrap();
+        store
+            .add_material(
+                Material {
+                    name: "Oak board".to_string(),
+                    description: "50% off".to_string(),
+                    ..Material::test_data()
+                }
+                .into(),
+            )
+            .unwrap();
+    }
+
+    fn search(store: &dyn WorkshopStore, text: &str) -> SearchResults {
+        store
+            .search(&SearchQuery::parse(text, None).unwrap())
+            .unwrap()
+    }
+
+    fn ids(hits: &[SearchHit]) -> Vec<i32> {
+        hits.iter().map(|hit| hit.id).collect()
+    }
+
+    fn check_search(store: &dyn WorkshopStore) {
+        add_values(store);
+        // The exact last name ranks above the one that only starts with it
+        let results = search(store, "DOE");
+        assert_eq!(ids(&results.clients), vec![2, 1]);
+        assert!(results.clients[0].score > results.clients[1].score);
+        assert_eq!(results.clients[0].kind, "client");
+        assert_eq!(results.clients[0].title, "John Doe");
+        assert_eq!(results.clients[0].matched, vec!["lastname".to_string()]);
+        // Projects are searched by name and description
+        assert_eq!(ids(&results.projects), vec![2]);
+        // Every word has to match
+        assert_eq!(ids(&search(store, "john doe").clients), vec![2]);
+        assert!(search(store, "jane doe smith").clients.is_empty());
+        // Phone numbers are found without their separators
+        let results = search(store, "0171555");
+        assert_eq!(ids(&results.clients), vec![2]);
+        assert_eq!(results.clients[0].matched, vec!["phone".to_string()]);
+        assert_eq!(ids(&search(store, "555-12").clients), vec![2]);
+        assert_eq!(ids(&search(store, "0171").clients), vec![2, 3]);
+        // A match in the name ranks above one in the description
+        let results = search(store, "table");
+        assert_eq!(ids(&results.projects), vec![2, 1]);
+        assert_eq!(results.projects[0].kind, "project");
+        assert_eq!(ids(&search(store, "oak").materials), vec![1]);
+        // Wildcards are searched for literally
+        assert_eq!(ids(&search(store, "50%").materials), vec![1]);
+        assert!(search(store, "_").materials.is_empty());
+        // Groups that are not searched stay empty
+        let mut query = SearchQuery::parse("oak", Some(1)).unwrap();
+        query.materials = false;
+        let results = store.search(&query).unwrap();
+        assert!(results.materials.is_empty());
+        assert_eq!(ids(&results.projects), vec![1]);
+    }
+
+    #[test]
+    fn test_memory_store_search() {
+        check_search(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store_search() {
+        check_search(&sqlite_store());
+    }
+
+    // More matching rows than the SQL backends rank, the best one was added last
+    fn check_many_candidates(store: &dyn WorkshopStore) {
+        for lastname in std::iter::repeat_n("Doering", CANDIDATES as usize).chain(["Doe"]) {
+            store
+                .add_client(NewClient {
+                    firstname: "Jane".to_string(),
+                    lastname: lastname.to_string(),
+                    phone: String::new(),
+                })
+                .unwrap();
+        }
+        let results = store
+            .search(&SearchQuery::parse("doe", Some(1)).unwrap())
+            .unwrap();
+        assert_eq!(ids(&results.clients), vec![CANDIDATES as i32 + 1]);
+    }
+
+    #[test]
+    fn test_memory_store_many_candidates() {
+        check_many_candidates(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store_many_candidates() {
+        check_many_candidates(&sqlite_store());
+    }
+
+    #[test]
+    fn test_invalid_search_is_rejected() {
+        for (text, limit) in [
+            ("", None),
+            ("   ", None),
+            ("doe", Some(0)),
+            ("doe", Some(51)),
+        ] {
+            assert_eq!(SearchQuery::parse(text, limit).unwrap_err().code, 400);
+        }
+        assert!(SearchQuery::parse(&"x".repeat(101), None).is_err());
+        // Repeated words only count once
+        assert_eq!(
+            SearchQuery::parse("Doe doe", None).unwrap().terms,
+            vec!["doe"]
+        );
+    }
+
+    // The token is the role of the user
+    struct RoleVerifier;
+
+    impl TokenVerifier for RoleVerifier {
+        fn verify<'a>(&'a self, token: &'a str) -> Verification<'a> {
+            Box::pin(async move {
+                Ok::<Principal, Error>(Principal {
+                    subject: token.to_string(),
+                    roles: vec![token.to_string()],
+                })
+            })
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_search_endpoint_only_returns_readable_groups() {
+        let store = Arc::new(MemoryStore::new());
+        add_values(store.as_ref());
+        let app = init_service(
+            App::new()
+                .app_data(Data::new(Repository::with_store(store)))
+                .app_data(Data::new(Authorizer::with_verifier(
+                    Box::new(RoleVerifier),
+                    Policy::new(&BTreeMap::new()),
+                )))
+                .wrap(middleware::from_fn(auth::middleware))
+                .configure(controller::configure),
+        )
+        .await;
+        let request = |role: &str, uri: &str| {
+            TestRequest::get()
+                .uri(uri)
+                .insert_header(("Authorization", format!("Bearer {}", role)))
+                .to_request()
+        };
+        let res = call_service(&app, request("viewer", "/Search?q=doe")).await;
+        assert_eq!(res.status(), 200);
+        let results: SearchResults = read_body_json(res).await;
+        assert_eq!(results.query, "doe");
+        assert_eq!(ids(&results.clients), vec![2, 1]);
+        assert_eq!(ids(&results.projects), vec![2]);
+        // A worker cannot read clients
+        let res = call_service(&app, request("worker", "/Search?q=doe")).await;
+        let results: SearchResults = read_body_json(res).await;
+        assert!(results.clients.is_empty());
+        assert_eq!(ids(&results.projects), vec![2]);
+        // Without a search text or a role
+        let res = call_service(&app, request("viewer", "/Search")).await;
+        assert_eq!(res.status(), 400);
+        let res = call_service(&app, request("guest", "/Search?q=doe")).await;
+        assert_eq!(res.status(), 403);
+    }
+}
@@ -0,0 +1,1365 @@
+/*
+
+    Description: This file contains the tests for the storage backends. Every backend has to pass the same tests.
+    How to run: cargo test
+
+*/
+
+#[cfg(test)]
+mod tests {
+    use crate::models::client::Client;
+    use crate::models::error;
+    use crate::models::material::Material;
+    use crate::models::material_type::MaterialType;
+    use crate::models::project::{Project, ProjectStatus};
+    use crate::models::project_material::ProjectMaterial;
+    use crate::models::project_transition::{ProjectTransition, Transition};
+    use crate::models::purchase_order::{NewPurchaseOrder, PurchaseOrder, PurchaseOrderStatus};
+    use crate::models::purchase_order_line::NewPurchaseOrderLine;
+    use crate::models::stock_movement::{Booking, MovementKind, NewStockMovement, StockMovement};
+    use crate::models::supplier::NewSupplier;
+    use crate::models::time_entry::{NewTimeEntry, TimeEntry, TimeGroup, TimeSummary, Timer};
+    use crate::models::timestamp;
+    use crate::utils::config::DatabaseConfig;
+    use crate::utils::store::memory::MemoryStore;
+    use crate::utils::store::migrations;
+    use crate::utils::store::query::ListQuery;
+    use crate::utils::store::sqlite::SqliteStore;
+    use crate::utils::store::WorkshopStore;
+
+    // Open a fresh SQLite database that only lives in memory and create the tables
+    fn sqlite_store() -> SqliteStore {
+        let store = SqliteStore::open(&DatabaseConfig {
+            url: "sqlite://:memory:".to_string(),
+            ..DatabaseConfig::default()
+        })
+        .unwrap();
+        migrations::migrate_up(&store, None).unwrap();
+        store
+    }
+
+    // The stock movements are booked for this user
+    fn booking() -> Booking {
+        Booking::new("user-1".to_string(), false)
+    }
+
+    // Add the test data of every table (Same order as the repository tests)
+    fn add_values(store: &dyn WorkshopStore) {
+        store.add_client(Client::test_data().into()).unwrap();
+        store
+            .add_material_type(MaterialType::test_data().into())
+            .unwrap();
+        store.add_material(Material::test_data().into()).unwrap();
+        store.add_project(Project::test_data().into()).unwrap();
+        store
+            .add_project_material(ProjectMaterial::test_data().into(), &booking())
+            .unwrap();
+    }
+
+    // The complete lifecycle of every table
+    fn check_crud(store: &dyn WorkshopStore) {
+        add_values(store);
+        // Everything can be read back
+        assert_eq!(
+            store
+                .get_all_clients(&ListQuery::default())
+                .unwrap()
+                .items
+                .len(),
+            1
+        );
+        assert_eq!(
+            store
+                .get_all_material_types(&ListQuery::default())
+                .unwrap()
+                .items
+                .len(),
+            1
+        );
+        assert_eq!(
+            store
+                .get_all_materials(&ListQuery::default())
+                .unwrap()
+                .items
+                .len(),
+            1
+        );
+        assert_eq!(
+            store
+                .get_all_projects(&ListQuery::default())
+                .unwrap()
+                .items
+                .len(),
+            1
+        );
+        assert_eq!(
+            store
+                .get_all_project_materials(&ListQuery::default())
+                .unwrap()
+                .items
+                .len(),
+            1
+        );
+        assert_eq!(store.get_client(1).unwrap().firstname, "John");
+        assert_eq!(
+            store.get_material(1).unwrap().costs.to_string(),
+            "10.00 EUR"
+        );
+        assert_eq!(store.get_project(1).unwrap().name, "Table");
+        // The database assigns the next id and returns the stored row
+        let client = store.add_client(Client::test_data().into()).unwrap();
+        assert_eq!((client.id, client.firstname.as_str()), (2, "John"));
+        store.remove_client(2, None).unwrap();
+        // Updates replace the stored values
+        store
+            .update_client(Client::test_update_data(), None)
+            .unwrap();
+        store
+            .update_material_type(MaterialType::test_update_data(), None)
+            .unwrap();
+        store
+            .update_material(Material::test_update_data(), None)
+            .unwrap();
+        store
+            .update_project(Project::test_update_data(), None)
+            .unwrap();
+        store
+            .update_project_material(ProjectMaterial::test_update_data(), None, &booking())
+            .unwrap();
+        assert_eq!(store.get_client(1).unwrap().firstname, "Jane");
+        assert_eq!(store.get_material_type(1).unwrap().name, "Metal");
+        assert_eq!(store.get_material(1).unwrap().name, "Metal");
+        assert_eq!(store.get_project(1).unwrap().name, "Chair");
+        assert_eq!(store.get_project_material(1).unwrap().amount, 20);
+        // Removed values are gone (Material 1 keeps the stock movements of the project, new rows are removed instead)
+        store.remove_project_material(1, None, &booking()).unwrap();
+        store.remove_project(1, None).unwrap();
+        let material_type = store
+            .add_material_type(MaterialType::test_data().into())
+            .unwrap();
+        let material = store
+            .add_material(
+                Material {
+                    type_id: material_type.id,
+                    ..Material::test_data()
+                }
+                .into(),
+            )
+            .unwrap();
+        store.remove_material(material.id, None).unwrap();
+        store.remove_material_type(material_type.id, None).unwrap();
+        store.remove_client(1, None).unwrap();
+        assert_eq!(store.get_project_material(1).unwrap_err().code, 404);
+        assert_eq!(store.get_project(1).unwrap_err().code, 404);
+        assert_eq!(store.get_material(material.id).unwrap_err().code, 404);
+        assert_eq!(
+            store.get_material_type(material_type.id).unwrap_err().code,
+            404
+        );
+        assert_eq!(store.get_client(1).unwrap_err().code, 404);
+    }
+
+    // Every backend reports the same status code and error code for the same mistake
+    fn check_errors(store: &dyn WorkshopStore) {
+        // Unknown rows cannot be updated or removed
+        let err = store
+            .update_client(Client::test_update_data(), None)
+            .unwrap_err();
+        assert_eq!((err.code, err.kind.as_str()), (404, "not_found"));
+        let err = store.remove_material_type(1, None).unwrap_err();
+        assert_eq!((err.code, err.kind.as_str()), (404, "not_found"));
+        // References have to exist
+        let err = store.add_project(Project::test_data().into()).unwrap_err();
+        assert_eq!(
+            (err.code, err.kind.as_str()),
+            (422, error::FOREIGN_KEY_VIOLATION)
+        );
+        add_values(store);
+        // Ids of removed rows are not used again
+        let client = store.add_client(Client::test_data().into()).unwrap();
+        store.remove_client(client.id, None).unwrap();
+        let next = store.add_client(Client::test_data().into()).unwrap();
+        assert_eq!(next.id, client.id + 1);
+        // Rows that are still referenced cannot be removed
+        let err = store.remove_client(1, None).unwrap_err();
+        assert_eq!(
+            (err.code, err.kind.as_str()),
+            (409, error::REFERENCED_BY_OTHERS)
+        );
+        let err = store.remove_material(1, None).unwrap_err();
+        assert_eq!(
+            (err.code, err.kind.as_str()),
+            (409, error::REFERENCED_BY_OTHERS)
+        );
+        // Updating a row without changing it is not a "not found"
+        store.update_client(Client::test_data(), None).unwrap();
+    }
+
+    // Materials with the given names and amounts (All of material type 1)
+    fn add_materials(store: &dyn WorkshopStore, materials: &[(&str, i32)]) {
+        store
+            .add_material_type(MaterialType::test_data().into())
+            .unwrap();
+        for (name, amount) in materials {
+            store
+                .add_material(
+                    Material {
+                        name: name.to_string(),
+                        amount: *amount,
+                        ..Material::test_data()
+                    }
+                    .into(),
+                )
+                .unwrap();
+        }
+    }
+
+    fn list(store: &dyn WorkshopStore, query: &str) -> (Vec<String>, u64) {
+        let params: Vec<(String, String)> = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap();
+                (key.to_string(), value.to_string())
+            })
+            .collect();
+        let page = store
+            .get_all_materials(&ListQuery::parse::<Material>(&params).unwrap())
+            .unwrap();
+        let names = page
+            .items
+            .into_iter()
+            .map(|material| material.name)
+            .collect();
+        (names, page.total)
+    }
+
+    // Every backend pages, sorts and filters the same way
+    fn check_list(store: &dyn WorkshopStore) {
+        add_materials(
+            store,
+            &[
+                ("Screw", 40),
+                ("Nail", 5),
+                ("Glue", 12),
+                ("Oak board", 5),
+                ("100% Cotton", 1),
+            ],
+        );
+        // Without parameters everything ordered by id
+        assert_eq!(list(store, "").1, 5);
+        assert_eq!(list(store, "").0[0], "Screw");
+        // Pages of the sorted list, equal amounts are ordered by id
+        let sorted = "sort=amount:desc&limit=2";
+        assert_eq!(
+            list(store, sorted),
+            (vec!["Screw".into(), "Glue".into()], 5)
+        );
+        assert_eq!(
+            list(store, &format!("{}&offset=2", sorted)),
+            (vec!["Nail".into(), "Oak board".into()], 5)
+        );
+        assert_eq!(list(store, &format!("{}&offset=10", sorted)).0.len(), 0);
+        // Filters are combined, the total only counts the matching rows
+        assert_eq!(
+            list(store, "type_id=1&amount_lt=10&sort=name"),
+            (
+                vec!["100% Cotton".into(), "Nail".into(), "Oak board".into()],
+                3
+            )
+        );
+        assert_eq!(list(store, "amount_gte=12&amount_lte=40").1, 2);
+        assert_eq!(list(store, "amount_ne=5").1, 3);
+        assert_eq!(list(store, "amount_gt=12").0, vec!["Screw".to_string()]);
+        // like is case insensitive and the wildcards of the value are taken literally
+        assert_eq!(list(store, "name_like=oa").0, vec!["Oak board".to_string()]);
+        assert_eq!(
+            list(store, "name_like=0%").0,
+            vec!["100% Cotton".to_string()]
+        );
+        assert_eq!(list(store, "name_like=_").1, 0);
+        assert_eq!(list(store, "type_id=2").1, 0);
+    }
+
+    // Projects with the given names, starts and ends (All of client 1)
+    fn add_projects(store: &dyn WorkshopStore, projects: &[(&str, &str, &str)]) {
+        store.add_client(Client::test_data().into()).unwrap();
+        for (name, startpoint, endpoint) in projects {
+            store
+                .add_project(
+                    Project {
+                        name: name.to_string(),
+                        startpoint: startpoint.parse().unwrap(),
+                        endpoint: endpoint.parse().unwrap(),
+                        ..Project::test_data()
+                    }
+                    .into(),
+                )
+                .unwrap();
+        }
+    }
+
+    fn list_projects(store: &dyn WorkshopStore, query: &str) -> Vec<String> {
+        let params: Vec<(String, String)> = query
+            .split('&')
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap();
+                (key.to_string(), value.to_string())
+            })
+            .collect();
+        store
+            .get_all_projects(&ListQuery::parse::<Project>(&params).unwrap())
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|project| project.name)
+            .collect()
+    }
+
+    // Every backend compares and sorts the timestamps the same way
+    fn check_dates(store: &dyn WorkshopStore) {
+        add_projects(
+            store,
+            &[
+                ("Shelf", "2026-10-19T00:00:00Z", "2026-10-25T18:00:00Z"),
+                ("Table", "2026-10-01T08:00:00Z", "2026-10-18T00:00:00Z"),
+                ("Chair", "2026-10-17T22:00:00-04:00", "2026-10-18T23:59:59Z"),
+                ("Door", "2026-09-01T08:00:00Z", "2026-10-17T23:59:59Z"),
+            ],
+        );
+        // The timestamps come back in UTC
+        let chair = store.get_project(3).unwrap();
+        assert_eq!(chair.startpoint.to_rfc3339(), "2026-10-18T02:00:00+00:00");
+        // active_on covers the whole day, the bounds included
+        assert_eq!(
+            list_projects(store, "active_on=2026-10-18&sort=name"),
+            vec!["Chair".to_string(), "Table".to_string()]
+        );
+        assert_eq!(
+            list_projects(
+                store,
+                "sort=startpoint:desc&starts_after=2026-09-01T08:00:00Z"
+            ),
+            vec![
+                "Shelf".to_string(),
+                "Chair".to_string(),
+                "Table".to_string()
+            ]
+        );
+        assert_eq!(
+            list_projects(store, "ends_before=2026-10-18&starts_before=2026-10-01"),
+            vec!["Door".to_string()]
+        );
+        assert_eq!(
+            list_projects(store, "ends_after=2026-10-18T01:59:59+02:00&sort=endpoint"),
+            vec![
+                "Table".to_string(),
+                "Chair".to_string(),
+                "Shelf".to_string()
+            ]
+        );
+        assert_eq!(
+            list_projects(store, "startpoint_gte=2026-10-18T02:00:00Z"),
+            vec!["Shelf".to_string(), "Chair".to_string()]
+        );
+    }
+
+    // Amounts come back exactly and are filtered by their value
+    fn check_money(store: &dyn WorkshopStore) {
+        store
+            .add_material_type(MaterialType::test_data().into())
+            .unwrap();
+        for costs in ["0.10 EUR", "0.20 USD", "9999999999.99 EUR", "0.30 EUR"] {
+            store
+                .add_material(
+                    Material {
+                        name: costs.to_string(),
+                        costs: costs.parse().unwrap(),
+                        ..Material::test_data()
+                    }
+                    .into(),
+                )
+                .unwrap();
+        }
+        for (id, costs) in [(1, "0.10 EUR"), (2, "0.20 USD"), (3, "9999999999.99 EUR")] {
+            assert_eq!(store.get_material(id).unwrap().costs.to_string(), costs);
+        }
+        // 0.1 + 0.2 is exactly 0.3 in cents
+        assert_eq!(list(store, "costs=0.3").0, vec!["0.30 EUR".to_string()]);
+        assert_eq!(
+            list(store, "costs_lte=0.3&costs_currency=EUR").0,
+            vec!["0.10 EUR".to_string(), "0.30 EUR".to_string()]
+        );
+        assert_eq!(
+            list(store, "sort=costs:desc&limit=1").0,
+            vec!["9999999999.99 EUR".to_string()]
+        );
+    }
+
+    // Every update counts the version up, an outdated version is rejected
+    fn check_versions(store: &dyn WorkshopStore) {
+        add_values(store);
+        // The project material took its amount from the stock
+        let material = store.get_material(1).unwrap();
+        assert_eq!(material.version, 2);
+        // The version of the value is ignored, the stored one counts up
+        let updated = store
+            .update_material(
+                Material {
+                    threshold_value: 5,
+                    version: 42,
+                    ..material.clone()
+                },
+                Some(2),
+            )
+            .unwrap();
+        assert_eq!((updated.threshold_value, updated.version), (5, 3));
+        assert_eq!(store.get_material(1).unwrap().version, 3);
+        // A second update that read version 2 does not overwrite the first one
+        let err = store.update_material(material, Some(2)).unwrap_err();
+        assert_eq!((err.code, err.kind.as_str()), (412, "precondition_failed"));
+        assert_eq!(store.get_material(1).unwrap().threshold_value, 5);
+        // Without an expected version any version is updated
+        let client = store.get_client(1).unwrap();
+        let client = store.update_client(client, None).unwrap();
+        assert_eq!(client.version, 2);
+        // Removing checks the version before the references
+        let err = store.remove_client(1, Some(1)).unwrap_err();
+        assert_eq!(err.code, 412);
+        let err = store
+            .remove_project_material(1, Some(3), &booking())
+            .unwrap_err();
+        assert_eq!(err.code, 412);
+        store
+            .remove_project_material(1, Some(1), &booking())
+            .unwrap();
+        // Unknown rows are still not found
+        let err = store
+            .remove_project_material(1, Some(1), &booking())
+            .unwrap_err();
+        assert_eq!(err.code, 404);
+        let err = store
+            .update_material_type(
+                MaterialType {
+                    id: 9,
+                    ..MaterialType::test_data()
+                },
+                Some(1),
+            )
+            .unwrap_err();
+        assert_eq!(err.code, 404);
+    }
+
+    fn book(
+        store: &dyn WorkshopStore,
+        kind: MovementKind,
+        quantity: i32,
+        created_at: &str,
+    ) -> Result<StockMovement, error::Error> {
+        let movement = NewStockMovement {
+            kind,
+            quantity,
+            reason: "Counted".to_string(),
+        };
+        let booking = Booking {
+            created_at: timestamp::parse(created_at).unwrap(),
+            ..booking()
+        };
+        store.add_stock_movement(1, movement, &booking)
+    }
+
+    fn list_movements(store: &dyn WorkshopStore, query: &str) -> Vec<i32> {
+        let params: Vec<(String, String)> = query
+            .split('&')
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap();
+                (key.to_string(), value.to_string())
+            })
+            .collect();
+        let page = store
+            .get_stock_movements(&ListQuery::parse::<StockMovement>(&params).unwrap())
+            .unwrap();
+        page.items.into_iter().map(|movement| movement.id).collect()
+    }
+
+    // Movements change the stock of the material and are kept as its ledger
+    fn check_stock_movements(store: &dyn WorkshopStore) {
+        add_materials(store, &[("Oak board", 10)]);
+        let movement = book(store, MovementKind::Receive, 5, "2026-10-01").unwrap();
+        assert_eq!((movement.id, movement.amount_after), (1, 15));
+        assert_eq!(movement.user_id, "user-1");
+        assert_eq!(
+            book(store, MovementKind::Consume, 12, "2026-10-05")
+                .unwrap()
+                .amount_after,
+            3
+        );
+        assert_eq!(
+            book(store, MovementKind::Adjust, -1, "2026-10-10")
+                .unwrap()
+                .amount_after,
+            2
+        );
+        assert_eq!(
+            book(store, MovementKind::Return, 2, "2026-10-15")
+                .unwrap()
+                .amount_after,
+            4
+        );
+        let material = store.get_material(1).unwrap();
+        assert_eq!((material.amount, material.version), (4, 5));
+        // The stock never becomes negative, nothing is booked then
+        let err = book(store, MovementKind::Consume, 5, "2026-10-16").unwrap_err();
+        assert_eq!((err.code, err.kind.as_str()), (409, "insufficient_stock"));
+        let err = book(store, MovementKind::Adjust, -5, "2026-10-16").unwrap_err();
+        assert_eq!(err.code, 409);
+        assert_eq!(store.get_material(1).unwrap().amount, 4);
+        assert_eq!(list_movements(store, "material_id=1"), vec![1, 2, 3, 4]);
+        // Unknown materials are not found
+        let movement = NewStockMovement {
+            kind: MovementKind::Receive,
+            quantity: 1,
+            reason: String::new(),
+        };
+        let err = store
+            .add_stock_movement(9, movement, &booking())
+            .unwrap_err();
+        assert_eq!(err.code, 404);
+        // Updating the material keeps the booked amount
+        let material = store
+            .update_material(
+                Material {
+                    amount: 100,
+                    ..store.get_material(1).unwrap()
+                },
+                None,
+            )
+            .unwrap();
+        assert_eq!(material.amount, 4);
+        // Filtered by date and kind
+        assert_eq!(
+            list_movements(store, "after=2026-10-05&before=2026-10-15"),
+            vec![3]
+        );
+        assert_eq!(list_movements(store, "kind=consume"), vec![2]);
+        assert_eq!(
+            list_movements(store, "sort=created_at:desc&limit=1"),
+            vec![4]
+        );
+        // A material with a ledger is not removed
+        let err = store.remove_material(1, None).unwrap_err();
+        assert_eq!(err.code, 409);
+    }
+
+    fn assign(
+        store: &dyn WorkshopStore,
+        material_id: i32,
+        amount: i32,
+        backorder: bool,
+    ) -> Result<ProjectMaterial, error::Error> {
+        let project_material = ProjectMaterial {
+            material_id,
+            amount,
+            ..ProjectMaterial::test_data()
+        };
+        let booking = Booking {
+            backorder,
+            ..booking()
+        };
+        store.add_project_material(project_material.into(), &booking)
+    }
+
+    fn amounts(store: &dyn WorkshopStore) -> (i32, i32) {
+        (
+            store.get_material(1).unwrap().amount,
+            store.get_material(2).unwrap().amount,
+        )
+    }
+
+    // Project materials take their amount from the stock and give it back
+    fn check_project_stock(store: &dyn WorkshopStore) {
+        add_materials(store, &[("Oak board", 10), ("Pine board", 5)]);
+        add_projects(
+            store,
+            &[("Table", "2026-10-01T08:00:00Z", "2026-10-20T17:00:00Z")],
+        );
+        let project_material = assign(store, 1, 4, false).unwrap();
+        assert_eq!(amounts(store), (6, 5));
+        let movements = store
+            .get_stock_movements(&ListQuery::default())
+            .unwrap()
+            .items;
+        assert_eq!(movements[0].kind, MovementKind::Consume);
+        assert_eq!(
+            (movements[0].quantity, movements[0].reason.as_str()),
+            (4, "Project 1")
+        );
+        // Not more than the stock, unless it is a backorder
+        let err = assign(store, 1, 7, false).unwrap_err();
+        assert_eq!((err.code, err.kind.as_str()), (409, "insufficient_stock"));
+        assert_eq!(
+            store
+                .get_all_project_materials(&ListQuery::default())
+                .unwrap()
+                .total,
+            1
+        );
+        assert_eq!(amounts(store), (6, 5));
+        let backorder = assign(store, 1, 7, true).unwrap();
+        assert_eq!(amounts(store), (-1, 5));
+        // Lowering the amount gives the difference back
+        let lowered = store
+            .update_project_material(
+                ProjectMaterial {
+                    amount: 2,
+                    ..project_material.clone()
+                },
+                Some(1),
+                &booking(),
+            )
+            .unwrap();
+        assert_eq!((lowered.amount, lowered.version), (2, 2));
+        assert_eq!(amounts(store), (1, 5));
+        // Another material takes from its stock and gives the old one back (Everything or nothing)
+        let err = store
+            .update_project_material(
+                ProjectMaterial {
+                    material_id: 2,
+                    amount: 6,
+                    ..lowered.clone()
+                },
+                None,
+                &booking(),
+            )
+            .unwrap_err();
+        assert_eq!(err.code, 409);
+        assert_eq!(amounts(store), (1, 5));
+        assert_eq!(store.get_project_material(lowered.id).unwrap().version, 2);
+        store
+            .update_project_material(
+                ProjectMaterial {
+                    material_id: 2,
+                    amount: 5,
+                    ..lowered.clone()
+                },
+                Some(2),
+                &booking(),
+            )
+            .unwrap();
+        assert_eq!(amounts(store), (3, 0));
+        // An outdated version changes no stock
+        let err = store
+            .update_project_material(lowered, Some(2), &booking())
+            .unwrap_err();
+        assert_eq!(err.code, 412);
+        assert_eq!(amounts(store), (3, 0));
+        // Removing gives everything back
+        store
+            .remove_project_material(backorder.id, None, &booking())
+            .unwrap();
+        store
+            .remove_project_material(project_material.id, None, &booking())
+            .unwrap();
+        assert_eq!(amounts(store), (10, 5));
+        let kinds: Vec<&str> = store
+            .get_stock_movements(&ListQuery::default())
+            .unwrap()
+            .items
+            .iter()
+            .map(|movement| movement.kind.as_str())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec!["consume", "consume", "return", "consume", "return", "return", "return"]
+        );
+    }
+
+    // The materials below their threshold_value (Filtered and paged like every list)
+    fn check_low_stock(store: &dyn WorkshopStore) {
+        add_materials(store, &[("Oak", 1), ("Pine", 2), ("Birch", 3), ("Ash", 0)]);
+        for id in 1..=4 {
+            let material = store.get_material(id).unwrap();
+            store
+                .update_material(
+                    Material {
+                        threshold_value: if id == 4 { 0 } else { 2 },
+                        ..material
+                    },
+                    None,
+                )
+                .unwrap();
+        }
+        let names = |query: &str| {
+            let params: Vec<(String, String)> = query
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| {
+                    let (key, value) = pair.split_once('=').unwrap();
+                    (key.to_string(), value.to_string())
+                })
+                .collect();
+            let page = store
+                .get_low_stock_materials(&ListQuery::parse::<Material>(&params).unwrap())
+                .unwrap();
+            let names: Vec<String> = page.items.into_iter().map(|m| m.name).collect();
+            (names, page.total)
+        };
+        // Only Oak is below, Pine is at the threshold and Ash has none
+        assert_eq!(names(""), (vec!["Oak".to_string()], 1));
+        book(store, MovementKind::Consume, 1, "2026-10-01").unwrap();
+        store
+            .add_stock_movement(
+                2,
+                NewStockMovement {
+                    kind: MovementKind::Consume,
+                    quantity: 1,
+                    reason: String::new(),
+                },
+                &booking(),
+            )
+            .unwrap();
+        assert_eq!(
+            names("sort=name"),
+            (vec!["Oak".to_string(), "Pine".to_string()], 2)
+        );
+        assert_eq!(names("name_like=pi"), (vec!["Pine".to_string()], 1));
+        assert_eq!(names("limit=1&offset=1").1, 2);
+    }
+
+    fn add_supplier(store: &dyn WorkshopStore, name: &str) -> i32 {
+        store
+            .add_supplier(NewSupplier {
+                name: name.to_string(),
+                email: "orders@example.com".to_string(),
+                phone: String::new(),
+            })
+            .unwrap()
+            .id
+    }
+
+    fn order(store: &dyn WorkshopStore, supplier_id: i32) -> PurchaseOrder {
+        store
+            .add_purchase_order(NewPurchaseOrder {
+                supplier_id,
+                note: String::new(),
+            })
+            .unwrap()
+    }
+
+    fn order_line(purchase_order_id: i32, material_id: i32, quantity: i32) -> NewPurchaseOrderLine {
+        NewPurchaseOrderLine {
+            purchase_order_id,
+            material_id,
+            quantity,
+            unit_price: "9.50 EUR".parse().unwrap(),
+        }
+    }
+
+    fn set_status(
+        store: &dyn WorkshopStore,
+        id: i32,
+        status: PurchaseOrderStatus,
+    ) -> Result<PurchaseOrder, error::Error> {
+        let purchase_order = store.get_purchase_order(id).unwrap();
+        store.update_purchase_order(
+            PurchaseOrder {
+                status,
+                ..purchase_order
+            },
+            None,
+        )
+    }
+
+    // A purchase order goes from draft to sent and is received line by line into the stock
+    fn check_purchasing(store: &dyn WorkshopStore) {
+        add_materials(store, &[("Oak", 1)]);
+        let supplier_id = add_supplier(store, "Timber Ltd");
+        let purchase_order = order(store, supplier_id);
+        assert_eq!(purchase_order.status, PurchaseOrderStatus::Draft);
+        // Not without lines
+        let err = set_status(store, 1, PurchaseOrderStatus::Sent).unwrap_err();
+        assert_eq!((err.code, err.kind.as_str()), (409, error::INVALID_STATUS));
+        let line = store.add_purchase_order_line(order_line(1, 1, 10)).unwrap();
+        assert_eq!((line.quantity, line.received_quantity), (10, 0));
+        let err = store
+            .add_purchase_order_line(order_line(9, 1, 1))
+            .unwrap_err();
+        assert_eq!(
+            (err.code, err.kind.as_str()),
+            (422, error::FOREIGN_KEY_VIOLATION)
+        );
+        // A draft is not received
+        let err = store
+            .receive_purchase_order_line(1, 1, &booking())
+            .unwrap_err();
+        assert_eq!(err.code, 409);
+        let sent = set_status(store, 1, PurchaseOrderStatus::Sent).unwrap();
+        assert_eq!((sent.status, sent.version), (PurchaseOrderStatus::Sent, 2));
+        assert_eq!(sent.created_at, purchase_order.created_at);
+        // Once it is sent the lines are fixed and it cannot go back
+        let errors = [
+            store
+                .add_purchase_order_line(order_line(1, 1, 1))
+                .unwrap_err(),
+            store
+                .update_purchase_order_line(line.clone(), None)
+                .unwrap_err(),
+            store.remove_purchase_order_line(1, None).unwrap_err(),
+            store.remove_purchase_order(1, None).unwrap_err(),
+            set_status(store, 1, PurchaseOrderStatus::Draft).unwrap_err(),
+            set_status(store, 1, PurchaseOrderStatus::Received).unwrap_err(),
+        ];
+        assert!(errors
+            .iter()
+            .all(|err| err.code == 409 && err.kind == error::INVALID_STATUS));
+        // Received in two deliveries, the stock goes up with every one
+        let line = store.receive_purchase_order_line(1, 4, &booking()).unwrap();
+        assert_eq!((line.received_quantity, line.version), (4, 2));
+        assert_eq!(
+            store.get_purchase_order(1).unwrap().status,
+            PurchaseOrderStatus::PartiallyReceived
+        );
+        assert_eq!(store.get_material(1).unwrap().amount, 5);
+        let err = store
+            .receive_purchase_order_line(1, 7, &booking())
+            .unwrap_err();
+        assert_eq!((err.code, err.fields[0].field.as_str()), (422, "quantity"));
+        store.receive_purchase_order_line(1, 6, &booking()).unwrap();
+        assert_eq!(
+            store.get_purchase_order(1).unwrap().status,
+            PurchaseOrderStatus::Received
+        );
+        assert_eq!(store.get_material(1).unwrap().amount, 11);
+        let movements = store.get_stock_movements(&ListQuery::default()).unwrap();
+        assert!(movements
+            .items
+            .iter()
+            .all(|movement| movement.kind == MovementKind::Receive
+                && movement.reason == "Purchase order 1"));
+        assert_eq!(movements.total, 2);
+        assert_eq!(
+            set_status(store, 1, PurchaseOrderStatus::Cancelled)
+                .unwrap_err()
+                .code,
+            409
+        );
+        // The supplier and the material are still used by the order
+        let err = store.remove_supplier(supplier_id, None).unwrap_err();
+        assert_eq!(
+            (err.code, err.kind.as_str()),
+            (409, error::REFERENCED_BY_OTHERS)
+        );
+        assert_eq!(store.remove_material(1, None).unwrap_err().code, 409);
+        // A draft can be removed with its lines
+        order(store, supplier_id);
+        store.add_purchase_order_line(order_line(2, 1, 3)).unwrap();
+        store.remove_purchase_order(2, None).unwrap();
+        assert_eq!(
+            store
+                .get_all_purchase_order_lines(&ListQuery::default())
+                .unwrap()
+                .total,
+            1
+        );
+    }
+
+    // The reorder suggestion drafts what is missing per preferred supplier
+    fn check_reorder(store: &dyn WorkshopStore) {
+        // The threshold_value of the test data is 50
+        add_materials(store, &[("Oak", 1), ("Pine", 2), ("Birch", 60), ("Ash", 0)]);
+        let timber = add_supplier(store, "Timber Ltd");
+        let forest = add_supplier(store, "Forest Inc");
+        for (id, supplier_id) in [(1, timber), (2, forest), (3, timber)] {
+            let material = store.get_material(id).unwrap();
+            store
+                .update_material(
+                    Material {
+                        preferred_supplier_id: Some(supplier_id),
+                        ..material
+                    },
+                    None,
+                )
+                .unwrap();
+        }
+        // 9 Oak are already on a draft
+        order(store, timber);
+        store.add_purchase_order_line(order_line(1, 1, 9)).unwrap();
+        let reorder = store.suggest_reorder().unwrap();
+        let drafted: Vec<(i32, Vec<(i32, i32)>)> = reorder
+            .purchase_orders
+            .iter()
+            .map(|order| {
+                assert_eq!(order.purchase_order.status, PurchaseOrderStatus::Draft);
+                let lines = order
+                    .lines
+                    .iter()
+                    .map(|line| {
+                        assert_eq!(line.purchase_order_id, order.purchase_order.id);
+                        (line.material_id, line.quantity)
+                    })
+                    .collect();
+                (order.purchase_order.supplier_id, lines)
+            })
+            .collect();
+        assert_eq!(
+            drafted,
+            vec![(timber, vec![(1, 40)]), (forest, vec![(2, 48)])]
+        );
+        assert_eq!(
+            reorder.purchase_orders[0].lines[0].unit_price,
+            Material::test_data().costs
+        );
+        assert_eq!(reorder.without_supplier, vec![4]);
+        // Everything is on order now
+        let reorder = store.suggest_reorder().unwrap();
+        assert!(reorder.purchase_orders.is_empty());
+        assert_eq!(reorder.without_supplier, vec![4]);
+        // A cancelled order does not count
+        set_status(store, 2, PurchaseOrderStatus::Cancelled).unwrap();
+        let reorder = store.suggest_reorder().unwrap();
+        assert_eq!(reorder.purchase_orders.len(), 1);
+        assert_eq!(reorder.purchase_orders[0].lines[0].quantity, 40);
+        // The preferred supplier cannot be removed
+        assert_eq!(store.remove_supplier(forest, None).unwrap_err().code, 409);
+    }
+
+    // The costing gets the project materials of the asked projects with their materials
+    fn check_costed_materials(store: &dyn WorkshopStore) {
+        add_values(store);
+        store.add_project(Project::test_data().into()).unwrap();
+        store
+            .add_material(
+                Material {
+                    name: "Screws".to_string(),
+                    costs: "0.05 EUR".parse().unwrap(),
+                    ..Material::test_data()
+                }
+                .into(),
+            )
+            .unwrap();
+        for (project_id, material_id) in [(2, 2), (2, 1)] {
+            store
+                .add_project_material(
+                    ProjectMaterial {
+                        project_id,
+                        material_id,
+                        ..ProjectMaterial::test_data()
+                    }
+                    .into(),
+                    &booking(),
+                )
+                .unwrap();
+        }
+        let costed = |ids: &[i32]| -> Vec<(i32, i32, String)> {
+            store
+                .get_costed_materials(ids)
+                .unwrap()
+                .into_iter()
+                .map(|(project_material, material)| {
+                    assert_eq!(project_material.material_id, material.id);
+                    (
+                        project_material.id,
+                        project_material.project_id,
+                        material.costs.to_string(),
+                    )
+                })
+                .collect()
+        };
+        assert_eq!(
+            costed(&[2]),
+            [
+                (2, 2, "0.05 EUR".to_string()),
+                (3, 2, "10.00 EUR".to_string())
+            ]
+        );
+        assert_eq!(costed(&[1, 2]).len(), 3);
+        assert!(costed(&[]).is_empty());
+        assert!(costed(&[9]).is_empty());
+    }
+
+    fn transition(
+        store: &dyn WorkshopStore,
+        status: ProjectStatus,
+        expected: Option<i32>,
+    ) -> Result<Project, error::Error> {
+        let transition = Transition {
+            status,
+            note: format!("To {}", status.as_str()),
+        };
+        store.transition_project(1, transition, "user-1", timestamp::now(), expected)
+    }
+
+    // The status of a project follows the transition graph and every transition is kept
+    fn check_project_lifecycle(store: &dyn WorkshopStore) {
+        add_values(store);
+        assert_eq!(store.get_project(1).unwrap().status, ProjectStatus::Inquiry);
+        for status in [
+            ProjectStatus::Quoted,
+            ProjectStatus::Accepted,
+            ProjectStatus::InProgress,
+        ] {
+            assert_eq!(transition(store, status, None).unwrap().status, status);
+        }
+        // Skipping the completion or staying in the status is not a transition
+        for status in [ProjectStatus::Invoiced, ProjectStatus::InProgress] {
+            let err = transition(store, status, None).unwrap_err();
+            assert_eq!((err.code, err.kind.as_str()), (409, error::INVALID_STATUS));
+        }
+        assert_eq!(
+            transition(store, ProjectStatus::OnHold, Some(1))
+                .unwrap_err()
+                .code,
+            412
+        );
+        // An update keeps the status
+        let project = store
+            .update_project(
+                Project {
+                    status: ProjectStatus::Invoiced,
+                    ..store.get_project(1).unwrap()
+                },
+                None,
+            )
+            .unwrap();
+        assert_eq!(project.status, ProjectStatus::InProgress);
+        // Not completed while its material is backordered
+        store
+            .add_project_material(
+                ProjectMaterial {
+                    amount: 200,
+                    ..ProjectMaterial::test_data()
+                }
+                .into(),
+                &Booking {
+                    backorder: true,
+                    ..booking()
+                },
+            )
+            .unwrap();
+        let err = transition(store, ProjectStatus::Completed, None).unwrap_err();
+        assert_eq!(
+            (err.code, err.kind.as_str()),
+            (409, error::TRANSITION_BLOCKED)
+        );
+        assert_eq!(
+            store.get_project(1).unwrap().status,
+            ProjectStatus::InProgress
+        );
+        store
+            .add_stock_movement(
+                1,
+                NewStockMovement {
+                    kind: MovementKind::Receive,
+                    quantity: 200,
+                    reason: String::new(),
+                },
+                &booking(),
+            )
+            .unwrap();
+        transition(store, ProjectStatus::Completed, None).unwrap();
+        transition(store, ProjectStatus::Invoiced, None).unwrap();
+        let err = transition(store, ProjectStatus::Cancelled, None).unwrap_err();
+        assert_eq!(err.kind, error::INVALID_STATUS);
+        // The history of the project in order
+        let history: Vec<ProjectTransition> = store
+            .get_project_transitions(
+                &ListQuery::parse::<ProjectTransition>(&[(
+                    "project_id".to_string(),
+                    "1".to_string(),
+                )])
+                .unwrap(),
+            )
+            .unwrap()
+            .items;
+        assert_eq!(
+            history
+                .iter()
+                .map(|row| (row.from_status.as_str(), row.to_status.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("inquiry", "quoted"),
+                ("quoted", "accepted"),
+                ("accepted", "in_progress"),
+                ("in_progress", "completed"),
+                ("completed", "invoiced")
+            ]
+        );
+        assert_eq!(
+            (history[4].user_id.as_str(), history[4].note.as_str()),
+            ("user-1", "To invoiced")
+        );
+        // The list filters by status
+        store.add_project(Project::test_data().into()).unwrap();
+        let ids = |query: &str| -> Vec<i32> {
+            let (key, value) = query.split_once('=').unwrap();
+            store
+                .get_all_projects(
+                    &ListQuery::parse::<Project>(&[(key.to_string(), value.to_string())]).unwrap(),
+                )
+                .unwrap()
+                .items
+                .into_iter()
+                .map(|project| project.id)
+                .collect()
+        };
+        assert_eq!(ids("status=invoiced"), [1]);
+        assert_eq!(ids("status=inquiry"), [2]);
+        assert_eq!(ids("status_ne=inquiry"), [1]);
+        // The history keeps the project
+        assert_eq!(store.remove_project(1, None).unwrap_err().code, 409);
+    }
+
+    fn timer(project_id: i32) -> Timer {
+        Timer {
+            project_id,
+            note: String::new(),
+            billable: true,
+        }
+    }
+
+    // Logged work of a user with a duration in minutes
+    fn log_time(
+        store: &dyn WorkshopStore,
+        project_id: i32,
+        user_id: &str,
+        duration: i32,
+        billable: bool,
+    ) -> TimeEntry {
+        store
+            .add_time_entry(
+                NewTimeEntry {
+                    project_id,
+                    started_at: timestamp::parse("2026-10-18T08:00:00Z").unwrap(),
+                    ended_at: None,
+                    duration: Some(duration),
+                    note: String::new(),
+                    billable,
+                },
+                user_id,
+            )
+            .unwrap()
+    }
+
+    // Timers, logged work and the summaries of the time entries
+    fn check_time_entries(store: &dyn WorkshopStore) {
+        add_values(store);
+        store.add_project(Project::test_data().into()).unwrap();
+        let start = timestamp::parse("2026-10-18T13:00:00Z").unwrap();
+        let entry = store.start_timer(timer(1), "user-1", start).unwrap();
+        assert!(entry.is_running());
+        // One timer per user at a time, other users have their own
+        let err = store.start_timer(timer(2), "user-1", start).unwrap_err();
+        assert_eq!((err.code, err.kind.as_str()), (409, error::TIMER_RUNNING));
+        store.start_timer(timer(2), "user-2", start).unwrap();
+        let stopped = store
+            .stop_timer("user-1", timestamp::parse("2026-10-18T14:30:20Z").unwrap())
+            .unwrap();
+        assert_eq!((stopped.id, stopped.minutes), (entry.id, 90));
+        assert_eq!(stopped.version, 2);
+        assert_eq!(store.stop_timer("user-1", start).unwrap_err().code, 404);
+        assert_eq!(
+            store
+                .start_timer(timer(9), "user-1", start)
+                .unwrap_err()
+                .code,
+            422
+        );
+        log_time(store, 1, "user-2", 45, true);
+        log_time(store, 2, "user-2", 60, false);
+        // The running timer of user-2 is not counted
+        assert_eq!(
+            store
+                .get_time_summary(&ListQuery::default(), TimeGroup::Project)
+                .unwrap(),
+            [
+                TimeSummary::new(Some(1), None, 2, 135, 135),
+                TimeSummary::new(Some(2), None, 1, 60, 0)
+            ]
+        );
+        let query =
+            ListQuery::parse::<TimeEntry>(&[("project_id".to_string(), "1".to_string())]).unwrap();
+        let summary = store.get_time_summary(&query, TimeGroup::User).unwrap();
+        assert_eq!(
+            summary
+                .iter()
+                .map(|row| (row.user_id.clone().unwrap(), row.minutes, row.hours))
+                .collect::<Vec<_>>(),
+            [
+                ("user-1".to_string(), 90, 1.5),
+                ("user-2".to_string(), 45, 0.75)
+            ]
+        );
+        let minutes = store.get_logged_minutes(&[1, 2, 9]).unwrap();
+        assert_eq!(minutes.into_iter().collect::<Vec<_>>(), [(1, 135), (2, 60)]);
+        assert!(store.get_logged_minutes(&[]).unwrap().is_empty());
+        // A stopped entry cannot run again, a changed end counts the minutes again
+        let err = store
+            .update_time_entry(
+                TimeEntry {
+                    ended_at: None,
+                    ..stopped.clone()
+                },
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(err.code, 422);
+        let changed = store
+            .update_time_entry(
+                TimeEntry {
+                    ended_at: Some(timestamp::parse("2026-10-18T13:10:00Z").unwrap()),
+                    user_id: "user-2".to_string(),
+                    ..stopped
+                },
+                Some(2),
+            )
+            .unwrap();
+        assert_eq!(
+            (changed.minutes, changed.user_id.as_str(), changed.version),
+            (10, "user-1", 3)
+        );
+        // The project cannot be removed while time is logged on it
+        assert_eq!(store.remove_project(2, None).unwrap_err().code, 409);
+        store.remove_time_entry(changed.id, Some(3)).unwrap();
+        assert_eq!(store.get_time_entry(changed.id).unwrap_err().code, 404);
+    }
+
+    #[test]
+    fn test_memory_store() {
+        check_crud(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store() {
+        check_crud(&sqlite_store());
+    }
+
+    #[test]
+    fn test_memory_store_errors() {
+        check_errors(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store_errors() {
+        check_errors(&sqlite_store());
+    }
+
+    #[test]
+    fn test_memory_store_list() {
+        check_list(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store_list() {
+        check_list(&sqlite_store());
+    }
+
+    #[test]
+    fn test_memory_store_dates() {
+        check_dates(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store_dates() {
+        check_dates(&sqlite_store());
+    }
+
+    #[test]
+    fn test_memory_store_money() {
+        check_money(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store_money() {
+        check_money(&sqlite_store());
+    }
+
+    #[test]
+    fn test_memory_store_versions() {
+        check_versions(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store_versions() {
+        check_versions(&sqlite_store());
+    }
+
+    #[test]
+    fn test_memory_store_stock_movements() {
+        check_stock_movements(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store_stock_movements() {
+        check_stock_movements(&sqlite_store());
+    }
+
+    #[test]
+    fn test_memory_store_project_stock() {
+        check_project_stock(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store_project_stock() {
+        check_project_stock(&sqlite_store());
+    }
+
+    #[test]
+    fn test_memory_store_low_stock() {
+        check_low_stock(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store_low_stock() {
+        check_low_stock(&sqlite_store());
+    }
+
+    #[test]
+    fn test_memory_store_purchasing() {
+        check_purchasing(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store_purchasing() {
+        check_purchasing(&sqlite_store());
+    }
+
+    #[test]
+    fn test_memory_store_reorder() {
+        check_reorder(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store_reorder() {
+        check_reorder(&sqlite_store());
+    }
+
+    #[test]
+    fn test_memory_store_costed_materials() {
+        check_costed_materials(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store_costed_materials() {
+        check_costed_materials(&sqlite_store());
+    }
+
+    #[test]
+    fn test_memory_store_time_entries() {
+        check_time_entries(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store_time_entries() {
+        check_time_entries(&sqlite_store());
+    }
+
+    #[test]
+    fn test_memory_store_project_lifecycle() {
+        check_project_lifecycle(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_sqlite_store_project_lifecycle() {
+        check_project_lifecycle(&sqlite_store());
+    }
+}
@@ -0,0 +1,130 @@
+/*
+
+    Description: This file contains the tests for the cache of the introspection results.
+    How to run: cargo test
+
+*/
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use crate::tests::fake_keycloak::start;
+    use crate::utils::auth::Principal;
+    use crate::utils::config::KeycloakConfig;
+    use crate::utils::keycloak::Keycloak;
+    use crate::utils::token_cache::TokenCache;
+
+    fn keycloak(url: &str, ttl: u64) -> Keycloak {
+        Keycloak::new(&KeycloakConfig {
+            api_url: url.to_string(),
+            client_secret: "secret".to_string(),
+            introspection_cache_ttl: ttl,
+            ..KeycloakConfig::default()
+        })
+    }
+
+    fn principal(subject: &str) -> Principal {
+        Principal {
+            subject: subject.to_string(),
+            roles: vec!["viewer".to_string()],
+        }
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    #[actix_rt::test]
+    async fn test_same_token_is_introspected_once() {
+        let (state, url) = start(Vec::new());
+        let keycloak = keycloak(&url, 60);
+        for _ in 0..20 {
+            keycloak.introspect("token-1").await.unwrap();
+        }
+        assert_eq!(state.introspections.load(Ordering::SeqCst), 1);
+        // Another token is introspected on its own
+        keycloak.introspect("token-2").await.unwrap();
+        assert_eq!(state.introspections.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_rt::test]
+    async fn test_invalid_tokens_are_not_cached() {
+        let (state, url) = start(Vec::new());
+        state.revoked.lock().unwrap().push("revoked".to_string());
+        let keycloak = keycloak(&url, 60);
+        for _ in 0..2 {
+            let err = keycloak.introspect("revoked").await.unwrap_err();
+            assert_eq!(err.code, 401);
+        }
+        assert_eq!(state.introspections.load(Ordering::SeqCst), 2);
+    }
+
+    // A token Keycloak rejected is introspected again instead of being answered from the cache
+    #[actix_rt::test]
+    async fn test_rejected_tokens_are_evicted() {
+        let (state, url) = start(Vec::new());
+        let keycloak = keycloak(&url, 60);
+        keycloak.introspect("token").await.unwrap();
+        state.revoked.lock().unwrap().push("token".to_string());
+        // Still cached, Keycloak is not asked
+        keycloak.introspect("token").await.unwrap();
+        assert_eq!(state.introspections.load(Ordering::SeqCst), 1);
+        // A call with the token was answered with 401
+        keycloak.invalidate("token");
+        let err = keycloak.introspect("token").await.unwrap_err();
+        assert_eq!(err.code, 401);
+        let err = keycloak.introspect("token").await.unwrap_err();
+        assert_eq!(err.code, 401);
+        assert_eq!(state.introspections.load(Ordering::SeqCst), 3);
+    }
+
+    #[actix_rt::test]
+    async fn test_disabled_cache() {
+        let (state, url) = start(Vec::new());
+        let keycloak = keycloak(&url, 0);
+        keycloak.introspect("token").await.unwrap();
+        keycloak.introspect("token").await.unwrap();
+        assert_eq!(state.introspections.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_rt::test]
+    async fn test_entries_expire_with_the_ttl() {
+        let cache = TokenCache::new(Duration::from_millis(100), 10).unwrap();
+        cache.insert("token", principal("a"), Some(now() + 300));
+        assert_eq!(cache.get("token").unwrap().subject, "a");
+        actix_rt::time::sleep(Duration::from_millis(150)).await;
+        assert!(cache.get("token").is_none());
+    }
+
+    #[test]
+    fn test_entries_never_outlive_the_token() {
+        let cache = TokenCache::new(Duration::from_secs(60), 10).unwrap();
+        // Already expired tokens are not cached at all
+        cache.insert("expired", principal("a"), Some(now() - 1));
+        assert!(cache.get("expired").is_none());
+        cache.insert("valid", principal("b"), Some(now() + 30));
+        assert!(cache.get("valid").is_some());
+        cache.remove("valid");
+        assert!(cache.get("valid").is_none());
+    }
+
+    #[test]
+    fn test_least_recently_used_entries_are_dropped() {
+        let cache = TokenCache::new(Duration::from_secs(60), 2).unwrap();
+        cache.insert("a", principal("a"), None);
+        cache.insert("b", principal("b"), None);
+        // Using a makes b the oldest entry
+        cache.get("a");
+        cache.insert("c", principal("c"), None);
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+        // A size of 0 disables the cache
+        assert!(TokenCache::new(Duration::from_secs(60), 0).is_none());
+    }
+}
@@ -0,0 +1,221 @@
+/*
+
+    Description: This file contains the tests for the validation rules of the models.
+    How to run: cargo test
+
+*/
+
+#[cfg(test)]
+mod tests {
+    use crate::models::client::{Client, NewClient};
+    use crate::models::error::FieldError;
+    use crate::models::material::{Material, NewMaterial};
+    use crate::models::material_type::MaterialType;
+    use crate::models::project::Project;
+    use crate::models::project_material::ProjectMaterial;
+    use crate::models::time_entry::NewTimeEntry;
+    use crate::models::timestamp;
+    use crate::utils::validation::{Validate, Validator};
+
+    // The invalid fields of a value (Empty if it is valid)
+    fn invalid(value: &impl Validate) -> Vec<String> {
+        match value.validate() {
+            Ok(()) => Vec::new(),
+            Err(err) => {
+                assert_eq!((err.code, err.kind.as_str()), (422, "validation_failed"));
+                err.fields.into_iter().map(|field| field.field).collect()
+            }
+        }
+    }
+
+    #[test]
+    fn test_test_data_is_valid() {
+        assert!(invalid(&Client::test_data()).is_empty());
+        assert!(invalid(&MaterialType::test_data()).is_empty());
+        assert!(invalid(&Material::test_data()).is_empty());
+        assert!(invalid(&Project::test_data()).is_empty());
+        assert!(invalid(&ProjectMaterial::test_data()).is_empty());
+    }
+
+    #[test]
+    fn test_names() {
+        for name in ["", "   ", &"x".repeat(256)] {
+            let material_type = MaterialType {
+                name: name.to_string(),
+                ..MaterialType::test_data()
+            };
+            assert_eq!(invalid(&material_type), vec!["name"], "{:?}", name);
+        }
+        let material_type = MaterialType {
+            name: "ö".repeat(255),
+            ..MaterialType::test_data()
+        };
+        assert!(invalid(&material_type).is_empty());
+    }
+
+    #[test]
+    fn test_phone_numbers() {
+        for phone in ["", "089 12345", "+49 (89) 123-456", "0171/555.1234"] {
+            let client = Client {
+                phone: phone.to_string(),
+                ..Client::test_data()
+            };
+            assert!(invalid(&client).is_empty(), "{:?}", phone);
+        }
+        for phone in [
+            "1234",
+            "call me",
+            "0171 555 x12",
+            "49+ 171 5555",
+            &"1".repeat(21),
+        ] {
+            let client = Client {
+                phone: phone.to_string(),
+                ..Client::test_data()
+            };
+            assert_eq!(invalid(&client), vec!["phone"], "{:?}", phone);
+        }
+    }
+
+    #[test]
+    fn test_clients() {
+        let client = Client {
+            firstname: "".to_string(),
+            lastname: " ".to_string(),
+            ..Client::test_data()
+        };
+        // Every invalid field is reported, not only the first one
+        assert_eq!(invalid(&client), vec!["firstname", "lastname"]);
+        // New clients have the same rules
+        let client = NewClient {
+            firstname: "".to_string(),
+            lastname: "Doe".to_string(),
+            phone: "".to_string(),
+        };
+        assert_eq!(invalid(&client), vec!["firstname"]);
+    }
+
+    #[test]
+    fn test_materials() {
+        let material = Material {
+            type_id: 0,
+            amount: -1,
+            costs: "-0.50 EUR".parse().unwrap(),
+            threshold_value: -3,
+            ..Material::test_data()
+        };
+        // A stored amount is negative if material was backordered, a new material cannot start with it
+        assert_eq!(
+            invalid(&material),
+            vec!["type_id", "costs", "threshold_value"]
+        );
+        assert_eq!(
+            invalid(&NewMaterial::from(material)),
+            vec!["type_id", "costs", "threshold_value", "amount"]
+        );
+        let material = Material {
+            amount: 0,
+            costs: "0 EUR".parse().unwrap(),
+            threshold_value: 0,
+            ..Material::test_data()
+        };
+        assert!(invalid(&material).is_empty());
+        // More than the DECIMAL(12, 2) columns can hold
+        let material = Material {
+            costs: "10000000000.00 EUR".parse().unwrap(),
+            ..Material::test_data()
+        };
+        assert_eq!(invalid(&material), vec!["costs"]);
+    }
+
+    #[test]
+    fn test_projects() {
+        let project = Project {
+            client_id: -1,
+            estimated_costs: "-1 EUR".parse().unwrap(),
+            estimated_hours: f32::INFINITY,
+            costs: "-100.00 USD".parse().unwrap(),
+            ..Project::test_data()
+        };
+        assert_eq!(
+            invalid(&project),
+            vec!["client_id", "estimated_costs", "estimated_hours", "costs"]
+        );
+        // A project can start and end at the same time, but not end before it starts
+        let project = Project {
+            endpoint: Project::test_data().startpoint,
+            ..Project::test_data()
+        };
+        assert!(invalid(&project).is_empty());
+        let project = Project {
+            startpoint: "2026-10-18T12:00:00+02:00".parse().unwrap(),
+            endpoint: "2026-10-18T09:59:59Z".parse().unwrap(),
+            ..Project::test_data()
+        };
+        let err = project.validate().unwrap_err();
+        assert_eq!(
+            err.fields,
+            vec![FieldError {
+                field: "endpoint".to_string(),
+                message: "must not be before startpoint".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_project_materials() {
+        let project_material = ProjectMaterial {
+            project_id: 0,
+            material_id: 0,
+            amount: 0,
+            ..ProjectMaterial::test_data()
+        };
+        assert_eq!(
+            invalid(&project_material),
+            vec!["project_id", "material_id", "amount"]
+        );
+    }
+
+    #[test]
+    fn test_time_entries() {
+        let entry = |ended_at: Option<&str>, duration: Option<i32>| NewTimeEntry {
+            project_id: 1,
+            started_at: timestamp::parse("2026-10-18T08:00:00Z").unwrap(),
+            ended_at: ended_at.map(|at| timestamp::parse(at).unwrap()),
+            duration,
+            note: String::new(),
+            billable: true,
+        };
+        assert!(invalid(&entry(Some("2026-10-18T12:00:00Z"), None)).is_empty());
+        assert!(invalid(&entry(None, Some(1440))).is_empty());
+        // Either an end or a duration
+        assert_eq!(invalid(&entry(None, None)), vec!["ended_at"]);
+        assert_eq!(
+            invalid(&entry(Some("2026-10-18T12:00:00Z"), Some(30))),
+            vec!["duration"]
+        );
+        assert_eq!(invalid(&entry(None, Some(0))), vec!["duration"]);
+        assert_eq!(invalid(&entry(None, Some(1441))), vec!["duration"]);
+        assert_eq!(
+            invalid(&entry(Some("2026-10-18T08:00:00Z"), None)),
+            vec!["ended_at"]
+        );
+        assert_eq!(
+            invalid(&entry(Some("2026-10-19T08:01:00Z"), None)),
+            vec!["ended_at"]
+        );
+    }
+
+    #[test]
+    fn test_one_error_per_field() {
+        struct Rules;
+        impl Validate for Rules {
+            fn rules(&self, v: &mut Validator) {
+                v.min("count", 2, 3).check("count", false, "ignored");
+            }
+        }
+        let err = Rules.validate().unwrap_err();
+        assert_eq!(err.message, "Invalid fields: count");
+        assert_eq!(err.fields[0].message, "must be at least 3");
+    }
+}
@@ -0,0 +1,320 @@
+/*
+
+    Description: This file contains the low stock alerts. Every change of a stock or a threshold_value is checked with
+                 the material before and after it, a material that crossed its threshold is reported once to every
+                 notifier (Log, webhook, mail) and only again after it was restocked.
+
+*/
+
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::Utc;
+
+use super::config::{AlertsConfig, SmtpConfig};
+use super::repository::blocking;
+use crate::models::error::Error;
+use crate::models::material::Material;
+use crate::models::stock_alert::StockAlert;
+use crate::models::timestamp;
+
+pub type Delivery<'a> = Pin<Box<dyn Future<Output = Result<(), Error>> + 'a>>;
+
+// Something that delivers an alert to the storekeeper
+pub trait Notifier: Send + Sync {
+    // Used in the log if a delivery fails
+    fn name(&self) -> &'static str;
+    fn notify<'a>(&'a self, alert: &'a StockAlert) -> Delivery<'a>;
+}
+
+// Writes the alerts to stderr
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    fn notify<'a>(&'a self, alert: &'a StockAlert) -> Delivery<'a> {
+        Box::pin(async move {
+            eprintln!("[alert] {}", alert.summary());
+            Ok(())
+        })
+    }
+}
+
+// POSTs every alert as JSON (A 2xx answer counts as delivered)
+pub struct WebhookNotifier {
+    url: String,
+    timeout: Duration,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String, timeout: Duration) -> WebhookNotifier {
+        WebhookNotifier {
+            url,
+            timeout,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn notify<'a>(&'a self, alert: &'a StockAlert) -> Delivery<'a> {
+        Box::pin(async move {
+            self.client
+                .post(&self.url)
+                .timeout(self.timeout)
+                .json(alert)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|err| Error::new(format!("Webhook failed: {}", err), 502))?;
+            Ok(())
+        })
+    }
+}
+
+// Sends a plain text mail through the configured relay
+pub struct SmtpNotifier {
+    config: SmtpConfig,
+    timeout: Duration,
+}
+
+impl SmtpNotifier {
+    pub fn new(config: SmtpConfig, timeout: Duration) -> SmtpNotifier {
+        SmtpNotifier { config, timeout }
+    }
+}
+
+impl Notifier for SmtpNotifier {
+    fn name(&self) -> &'static str {
+        "smtp"
+    }
+
+    fn notify<'a>(&'a self, alert: &'a StockAlert) -> Delivery<'a> {
+        let config = self.config.clone();
+        let timeout = self.timeout;
+        let alert = alert.clone();
+        // The SMTP conversation is blocking, so it runs on the blocking thread pool
+        Box::pin(blocking(move || send_mail(&config, timeout, &alert)))
+    }
+}
+
+// A material before and after a change of its stock or its threshold_value
+#[derive(Debug, Clone)]
+pub struct StockChange {
+    pub before: Material,
+    pub after: Material,
+}
+
+// The notifiers of the configuration and the materials that were already reported
+#[derive(Default)]
+pub struct Alerter {
+    notifiers: Vec<Box<dyn Notifier>>,
+    // Reported materials that are still low (Only guards against repeats, a restart does not report them again)
+    alerted: Mutex<BTreeSet<i32>>,
+}
+
+impl Alerter {
+    pub fn new(config: &AlertsConfig) -> Alerter {
+        let timeout = Duration::from_secs(config.timeout);
+        let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+        if config.log {
+            notifiers.push(Box::new(LogNotifier));
+        }
+        if !config.webhook_url.is_empty() {
+            notifiers.push(Box::new(WebhookNotifier::new(
+                config.webhook_url.clone(),
+                timeout,
+            )));
+        }
+        if !config.smtp.to.is_empty() {
+            notifiers.push(Box::new(SmtpNotifier::new(config.smtp.clone(), timeout)));
+        }
+        Alerter::with_notifiers(notifiers)
+    }
+
+    pub fn with_notifiers(notifiers: Vec<Box<dyn Notifier>>) -> Alerter {
+        Alerter {
+            notifiers,
+            alerted: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    // The alerts of the materials that went below their threshold with the change
+    pub fn check(&self, changes: &[StockChange]) -> Vec<StockAlert> {
+        let mut alerted = self.alerted.lock().unwrap();
+        let raised_at = timestamp::now();
+        changes
+            .iter()
+            .filter_map(|change| {
+                let material = &change.after;
+                if !material.is_low_on_stock() {
+                    alerted.remove(&material.id);
+                    return None;
+                }
+                // A material that was already low did not cross its threshold (Reported before or before a restart)
+                if change.before.is_low_on_stock() {
+                    return None;
+                }
+                alerted
+                    .insert(material.id)
+                    .then(|| StockAlert::new(material, raised_at))
+            })
+            .collect()
+    }
+
+    // Deliver the alerts to every notifier (A failed delivery is only logged, the stock change already happened)
+    pub async fn send(&self, alerts: &[StockAlert]) {
+        for alert in alerts {
+            for notifier in &self.notifiers {
+                if let Err(err) = notifier.notify(alert).await {
+                    eprintln!(
+                        "Cannot send the low stock alert of material {} ({}): {}",
+                        alert.material_id,
+                        notifier.name(),
+                        err
+                    );
+                }
+            }
+        }
+    }
+}
+
+// One mail over SMTP (Without TLS or login, the relay is expected on the local network)
+fn send_mail(config: &SmtpConfig, timeout: Duration, alert: &StockAlert) -> Result<(), Error> {
+    let address = (config.host.as_str(), config.port)
+        .to_socket_addrs()
+        .map_err(smtp_error)?
+        .next()
+        .ok_or_else(|| Error::new(format!("Cannot resolve {}", config.host), 502))?;
+    let stream = TcpStream::connect_timeout(&address, timeout).map_err(smtp_error)?;
+    stream
+        .set_read_timeout(Some(timeout))
+        .and_then(|_| stream.set_write_timeout(Some(timeout)))
+        .map_err(smtp_error)?;
+    let mut reader = BufReader::new(stream.try_clone().map_err(smtp_error)?);
+    let mut writer = stream;
+    expect(&mut reader, 2)?;
+    command(&mut writer, &mut reader, "EHLO localhost", 2)?;
+    command(
+        &mut writer,
+        &mut reader,
+        &format!("MAIL FROM:<{}>", config.from),
+        2,
+    )?;
+    for to in &config.to {
+        command(&mut writer, &mut reader, &format!("RCPT TO:<{}>", to), 2)?;
+    }
+    command(&mut writer, &mut reader, "DATA", 3)?;
+    let message = mail(config, alert);
+    writer.write_all(message.as_bytes()).map_err(smtp_error)?;
+    command(&mut writer, &mut reader, ".", 2)?;
+    // The mail is accepted, a failed QUIT does not matter
+    let _ = command(&mut writer, &mut reader, "QUIT", 2);
+    Ok(())
+}
+
+// The headers and the text of the mail (Lines starting with a dot get a second one, a single dot would end it)
+fn mail(config: &SmtpConfig, alert: &StockAlert) -> String {
+    let text = format!(
+        "{}\n\nMaterial: {} (Id {})\nAmount: {}\nThreshold: {}\nRaised at: {}\n",
+        alert.summary(),
+        alert.name,
+        alert.material_id,
+        alert.amount,
+        alert.threshold_value,
+        alert.raised_at.to_rfc3339()
+    );
+    let mut message = format!(
+        "From: <{}>\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: 8bit\r\n\r\n",
+        config.from,
+        config
+            .to
+            .iter()
+            .map(|to| format!("<{}>", to))
+            .collect::<Vec<String>>()
+            .join(", "),
+        header_text(&alert.summary()),
+        Utc::now().to_rfc2822()
+    );
+    for line in text.lines() {
+        if line.starts_with('.') {
+            message.push('.');
+        }
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+    message
+}
+
+// Header values are ASCII, anything else is sent as a RFC 2047 encoded word (So a name cannot add headers either)
+fn header_text(text: &str) -> String {
+    if text.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+        return text.to_string();
+    }
+    let mut encoded = "=?UTF-8?Q?".to_string();
+    for byte in text.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' => encoded.push(byte as char),
+            b' ' => encoded.push('_'),
+            _ => encoded.push_str(&format!("={:02X}", byte)),
+        }
+    }
+    encoded.push_str("?=");
+    encoded
+}
+
+fn command(
+    writer: &mut TcpStream,
+    reader: &mut BufReader<TcpStream>,
+    line: &str,
+    class: u16,
+) -> Result<(), Error> {
+    writer
+        .write_all(format!("{}\r\n", line).as_bytes())
+        .map_err(smtp_error)?;
+    expect(reader, class)
+}
+
+// Read a reply and check its class (2 for 2xx), replies of several lines have a dash after the code (250-SIZE)
+fn expect(reader: &mut BufReader<TcpStream>, class: u16) -> Result<(), Error> {
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).map_err(smtp_error)? == 0 {
+            return Err(Error::new(
+                "The mail server closed the connection".to_string(),
+                502,
+            ));
+        }
+        let code: u16 = line
+            .get(0..3)
+            .and_then(|code| code.parse().ok())
+            .ok_or_else(|| Error::new(format!("Invalid SMTP reply: {}", line.trim_end()), 502))?;
+        if line.as_bytes().get(3) == Some(&b'-') {
+            continue;
+        }
+        if code / 100 != class {
+            return Err(Error::new(
+                format!("The mail server answered {}", line.trim_end()),
+                502,
+            ));
+        }
+        return Ok(());
+    }
+}
+
+fn smtp_error(err: std::io::Error) -> Error {
+    Error::new(format!("SMTP failed: {}", err), 502)
+}
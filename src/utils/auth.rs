@@ -0,0 +1,125 @@
+/*
+
+    Description: This file contains the middleware that authenticates every request and checks the permission the
+                 route needs. The authenticated user is put into the request, handlers can take it as Principal.
+
+*/
+
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    http::header::Header,
+    middleware::Next,
+    web::Data,
+    FromRequest, HttpMessage, HttpRequest, ResponseError,
+};
+use actix_web_httpauth::headers::authorization::{Authorization, Bearer};
+
+use super::config::Config;
+use super::keycloak::Keycloak;
+use super::policy::{self, Policy};
+use crate::models::error::Error;
+
+// The authenticated user of a request
+#[derive(Debug, Clone)]
+pub struct Principal {
+    // Keycloak user id
+    pub subject: String,
+    // Keycloak realm roles
+    pub roles: Vec<String>,
+}
+
+// Take the user that the middleware authenticated
+impl FromRequest for Principal {
+    type Error = Error;
+    type Future = Ready<Result<Principal, Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<Principal>()
+                .cloned()
+                .ok_or_else(|| Error::new("Not authenticated".to_string(), 401)),
+        )
+    }
+}
+
+pub type Verification<'a> = Pin<Box<dyn Future<Output = Result<Principal, Error>> + 'a>>;
+
+// Something that turns a bearer token into the user it belongs to
+pub trait TokenVerifier: Send + Sync {
+    fn verify<'a>(&'a self, token: &'a str) -> Verification<'a>;
+}
+
+// Shared by all workers (Registered as app data)
+pub struct Authorizer {
+    verifier: Box<dyn TokenVerifier>,
+    policy: Policy,
+}
+
+impl Authorizer {
+    // Check the tokens with Keycloak and use the roles of the configuration
+    pub fn new(config: &Config) -> Authorizer {
+        Authorizer::with_verifier(
+            Box::new(Keycloak::new(&config.keycloak)),
+            Policy::new(&config.roles),
+        )
+    }
+
+    pub fn with_verifier(verifier: Box<dyn TokenVerifier>, policy: Policy) -> Authorizer {
+        Authorizer { verifier, policy }
+    }
+
+    // For handlers that return more or less depending on the permissions of the user
+    pub fn allows(&self, principal: &Principal, permission: &str) -> bool {
+        self.policy.allows(&principal.roles, permission)
+    }
+}
+
+// Authenticate the request and check its permission (None for paths without a route, the router answers 404)
+async fn authorize(req: &ServiceRequest) -> Result<Option<Principal>, Error> {
+    let pattern = match req.match_pattern() {
+        Some(pattern) => pattern,
+        None => return Ok(None),
+    };
+    let authorizer = req
+        .app_data::<Data<Authorizer>>()
+        .ok_or_else(|| Error::new("No authorizer configured".to_string(), 500))?;
+    // Deny by default, a new route has to be added to the table first
+    let permission = policy::required_permission(req.method().as_str(), &pattern)
+        .ok_or_else(|| Error::new("No permission is defined for this route".to_string(), 403))?;
+    let auth = Authorization::<Bearer>::parse(req)
+        .map_err(|_| Error::new("Missing bearer token".to_string(), 401))?;
+    let principal = authorizer.verifier.verify(auth.as_ref().token()).await?;
+    if !authorizer.policy.allows(&principal.roles, permission) {
+        return Err(Error::new(
+            format!(
+                "User {} does not have the permission {}",
+                principal.subject, permission
+            ),
+            403,
+        ));
+    }
+    Ok(Some(principal))
+}
+
+pub async fn middleware<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, actix_web::Error> {
+    match authorize(&req).await {
+        Ok(Some(principal)) => {
+            req.extensions_mut().insert(principal);
+        }
+        Ok(None) => {}
+        // Answer right here so the error body still gets the request id
+        Err(err) => {
+            let res = err.error_response();
+            return Ok(req.into_response(res).map_into_right_body());
+        }
+    }
+    Ok(next.call(req).await?.map_into_left_body())
+}
@@ -0,0 +1,80 @@
+/*
+
+    Description: This file contains the JSON body extractor of the controllers. The body is read by the JSON extractor
+                 of actix-web (So the JsonConfig still answers unreadable bodies) and then turned into the model, an
+                 invalid field is answered with 422 and the path of the field.
+
+*/
+
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::models::error::{Error, FieldError};
+
+// A request body of type T (Used like the Json extractor of actix-web)
+#[derive(Debug)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Json<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Json<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: DeserializeOwned> FromRequest for Json<T> {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let body = web::Json::<Value>::from_request(req, payload);
+        Box::pin(async move {
+            let value = body.await?.into_inner();
+            Ok(Json(from_value(value)?))
+        })
+    }
+}
+
+// Turn a JSON value into T, the field that does not fit is named in the error
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, Error> {
+    serde_path_to_error::deserialize(value).map_err(|err| {
+        let path = err.path().to_string();
+        let message = err.into_inner().to_string();
+        let field = match named_field(&message) {
+            // A missing or unknown field is reported at the object that holds it
+            Some(name) if path == "." => name.to_string(),
+            Some(name) => format!("{}.{}", path, name),
+            None => path,
+        };
+        Error::with_fields(
+            format!("Invalid body: {}", message),
+            vec![FieldError { field, message }],
+        )
+    })
+}
+
+// The field of "missing field `name`" and "unknown field `name`, expected ..."
+fn named_field(message: &str) -> Option<&str> {
+    let rest = message
+        .strip_prefix("missing field `")
+        .or_else(|| message.strip_prefix("unknown field `"))?;
+    rest.split('`').next()
+}
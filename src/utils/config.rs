@@ -0,0 +1,590 @@
+/*
+
+    Description: This file contains the runtime configuration of the server. The configuration is layered:
+                 built-in defaults < TOML file < environment variables (WMS_*) < command line flags.
+
+*/
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use serde::Deserialize;
+
+use super::policy;
+use super::store::migrations::MigrateAction;
+use super::store::Backend;
+use super::validation::mail_address;
+use crate::models::error::Error;
+use crate::models::money::{Money, DEFAULT_CURRENCY};
+
+// Path of the configuration file if none was given on the command line or in WMS_CONFIG
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+// Longest time a revoked token may still be accepted from the introspection cache (Seconds)
+const MAX_INTROSPECTION_CACHE_TTL: u64 = 300;
+
+// Methods a rate limit group can be restricted to
+const METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+// Command line flags (Every flag overrides the matching value of the file and the environment)
+#[derive(Parser, Debug, Default)]
+#[command(version, about = "Workshop management server")]
+pub struct Args {
+    /// Run a maintenance command instead of the server
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Path to the TOML configuration file
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    /// Address the HTTP server binds to
+    #[arg(long)]
+    pub host: Option<String>,
+    /// Port the HTTP server listens on
+    #[arg(long)]
+    pub port: Option<u16>,
+    /// Database URL (mysql://, sqlite:// or memory://)
+    #[arg(long)]
+    pub database_url: Option<String>,
+    /// Number of database connections that are kept open
+    #[arg(long)]
+    pub database_min_connections: Option<usize>,
+    /// Maximum number of open database connections
+    #[arg(long)]
+    pub database_max_connections: Option<usize>,
+    /// Seconds a request waits for a free database connection
+    #[arg(long)]
+    pub database_acquire_timeout: Option<u64>,
+    /// Base URL of the Keycloak server
+    #[arg(long)]
+    pub keycloak_url: Option<String>,
+    /// Keycloak realm
+    #[arg(long)]
+    pub keycloak_realm: Option<String>,
+    /// Keycloak client id
+    #[arg(long)]
+    pub keycloak_client_id: Option<String>,
+    /// Keycloak client secret
+    #[arg(long)]
+    pub keycloak_client_secret: Option<String>,
+    /// How access tokens are checked (introspection or jwks)
+    #[arg(long)]
+    pub keycloak_validation: Option<TokenValidation>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Manage the database schema
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub keycloak: KeycloakConfig,
+    // Permissions of the Keycloak realm roles (Replaces the built-in roles if it is set)
+    pub roles: BTreeMap<String, Vec<String>>,
+    pub rate_limit: RateLimitConfig,
+    pub alerts: AlertsConfig,
+    pub costing: CostingConfig,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub min_connections: usize,
+    pub max_connections: usize,
+    // Seconds to wait for a free connection before the request fails
+    pub acquire_timeout: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeycloakConfig {
+    pub api_url: String,
+    pub realm: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub validation: TokenValidation,
+    // Ask the introspection endpoint if a token cannot be checked locally (e.g. the keys cannot be fetched)
+    pub fallback_to_introspection: bool,
+    // Expected iss and aud claims (Empty means <api_url>/realms/<realm> and the client id)
+    pub issuer: String,
+    pub audience: String,
+    // Seconds until the signing keys are fetched again
+    pub jwks_refresh: u64,
+    // Seconds an introspection result is kept at most (0 disables the cache) and the number of kept tokens
+    pub introspection_cache_ttl: u64,
+    pub introspection_cache_size: usize,
+}
+
+// Every user (Or client IP) gets a bucket of `capacity` requests that refills with `refill_per_second`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub capacity: u32,
+    pub refill_per_second: f64,
+    // Routes with their own buckets, the first group that matches is used (Otherwise the limits above)
+    pub groups: Vec<RateLimitGroup>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitGroup {
+    pub name: String,
+    // Path prefix (Whole segments) and HTTP methods of the routes (Empty matches all)
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub methods: Vec<String>,
+    pub capacity: u32,
+    pub refill_per_second: f64,
+}
+
+// Where the low stock alerts are sent (Every enabled notifier gets every alert)
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertsConfig {
+    // Write the alerts to stderr
+    pub log: bool,
+    // POST the alerts as JSON to this URL (Empty disables the webhook)
+    pub webhook_url: String,
+    pub smtp: SmtpConfig,
+    // Seconds a webhook or a mail may take
+    pub timeout: u64,
+}
+
+// A local mail relay that accepts mails without TLS or login (e.g. the Postfix of the host)
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub from: String,
+    // Without recipients no mails are sent
+    pub to: Vec<String>,
+}
+
+// What the costing reports charge for the logged hours
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct CostingConfig {
+    // Per hour, e.g. "45.00 EUR" (Compared with the projects estimated in the same currency)
+    pub labour_rate: Money,
+}
+
+// Introspection asks Keycloak about every token, jwks checks the signature with the cached keys of the realm
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenValidation {
+    #[default]
+    Introspection,
+    Jwks,
+}
+
+impl Default for ServerConfig {
+    fn default() -> ServerConfig {
+        ServerConfig {
+            host: "0.0.0.0".to_string(),
+            port: 8580,
+        }
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> DatabaseConfig {
+        DatabaseConfig {
+            url: String::new(),
+            min_connections: 1,
+            max_connections: 10,
+            acquire_timeout: 5,
+        }
+    }
+}
+
+impl Default for KeycloakConfig {
+    fn default() -> KeycloakConfig {
+        KeycloakConfig {
+            api_url: String::new(),
+            realm: "WMS".to_string(),
+            client_id: "workshop_client".to_string(),
+            client_secret: String::new(),
+            validation: TokenValidation::Introspection,
+            fallback_to_introspection: false,
+            issuer: String::new(),
+            audience: String::new(),
+            jwks_refresh: 300,
+            introspection_cache_ttl: 60,
+            introspection_cache_size: 10000,
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> RateLimitConfig {
+        RateLimitConfig {
+            enabled: true,
+            capacity: 100,
+            refill_per_second: 10.0,
+            groups: Vec::new(),
+        }
+    }
+}
+
+impl Default for AlertsConfig {
+    fn default() -> AlertsConfig {
+        AlertsConfig {
+            log: true,
+            webhook_url: String::new(),
+            smtp: SmtpConfig::default(),
+            timeout: 10,
+        }
+    }
+}
+
+impl Default for CostingConfig {
+    fn default() -> CostingConfig {
+        CostingConfig {
+            labour_rate: Money::new(0, DEFAULT_CURRENCY),
+        }
+    }
+}
+
+impl Default for SmtpConfig {
+    fn default() -> SmtpConfig {
+        SmtpConfig {
+            host: "localhost".to_string(),
+            port: 25,
+            from: "wms@localhost".to_string(),
+            to: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    // Load the configuration of the running process (Command line, environment and file)
+    pub fn load(args: &Args) -> Result<Config, Error> {
+        Config::load_from(args, |key| std::env::var(key).ok())
+    }
+
+    // Load the configuration from already parsed flags and an environment lookup (Used by the tests)
+    pub fn load_from(args: &Args, env: impl Fn(&str) -> Option<String>) -> Result<Config, Error> {
+        // The file is optional unless it was explicitly requested
+        let explicit_path = args
+            .config
+            .clone()
+            .or_else(|| env("WMS_CONFIG").map(PathBuf::from));
+        let mut config = match explicit_path {
+            Some(path) => Config::from_file(&path)?,
+            None => {
+                let path = PathBuf::from(DEFAULT_CONFIG_PATH);
+                if path.exists() {
+                    Config::from_file(&path)?
+                } else {
+                    Config::default()
+                }
+            }
+        };
+        config.apply_env(env)?;
+        config.apply_args(args);
+        // The maintenance commands only talk to the database (No Keycloak needed to migrate)
+        match args.command {
+            Some(Command::Migrate { .. }) => config.validate_database()?,
+            None => config.validate()?,
+        }
+        Ok(config)
+    }
+
+    // Parse a TOML file, missing keys keep their defaults
+    pub fn from_file(path: &Path) -> Result<Config, Error> {
+        let content = std::fs::read_to_string(path).map_err(|err| {
+            Error::new(
+                format!("Cannot read config file {}: {}", path.display(), err),
+                500,
+            )
+        })?;
+        Config::from_toml(&content)
+    }
+
+    pub fn from_toml(content: &str) -> Result<Config, Error> {
+        toml::from_str(content)
+            .map_err(|err| Error::new(format!("Invalid config file: {}", err), 500))
+    }
+
+    // Override the values with the WMS_* environment variables
+    fn apply_env(&mut self, env: impl Fn(&str) -> Option<String>) -> Result<(), Error> {
+        if let Some(host) = env("WMS_HOST") {
+            self.server.host = host;
+        }
+        if let Some(port) = env("WMS_PORT") {
+            self.server.port = parse_env("WMS_PORT", &port)?;
+        }
+        if let Some(url) = env("WMS_DATABASE_URL") {
+            self.database.url = url;
+        }
+        if let Some(min) = env("WMS_DATABASE_MIN_CONNECTIONS") {
+            self.database.min_connections = parse_env("WMS_DATABASE_MIN_CONNECTIONS", &min)?;
+        }
+        if let Some(max) = env("WMS_DATABASE_MAX_CONNECTIONS") {
+            self.database.max_connections = parse_env("WMS_DATABASE_MAX_CONNECTIONS", &max)?;
+        }
+        if let Some(timeout) = env("WMS_DATABASE_ACQUIRE_TIMEOUT") {
+            self.database.acquire_timeout = parse_env("WMS_DATABASE_ACQUIRE_TIMEOUT", &timeout)?;
+        }
+        if let Some(url) = env("WMS_KEYCLOAK_URL") {
+            self.keycloak.api_url = url;
+        }
+        if let Some(realm) = env("WMS_KEYCLOAK_REALM") {
+            self.keycloak.realm = realm;
+        }
+        if let Some(client_id) = env("WMS_KEYCLOAK_CLIENT_ID") {
+            self.keycloak.client_id = client_id;
+        }
+        if let Some(client_secret) = env("WMS_KEYCLOAK_CLIENT_SECRET") {
+            self.keycloak.client_secret = client_secret;
+        }
+        if let Some(validation) = env("WMS_KEYCLOAK_VALIDATION") {
+            self.keycloak.validation =
+                <TokenValidation as clap::ValueEnum>::from_str(&validation, true).map_err(
+                    |_| {
+                        Error::new(
+                            format!(
+                                "WMS_KEYCLOAK_VALIDATION must be introspection or jwks: {}",
+                                validation
+                            ),
+                            500,
+                        )
+                    },
+                )?;
+        }
+        Ok(())
+    }
+
+    // Override the values with the command line flags
+    fn apply_args(&mut self, args: &Args) {
+        if let Some(host) = &args.host {
+            self.server.host = host.clone();
+        }
+        if let Some(port) = args.port {
+            self.server.port = port;
+        }
+        if let Some(url) = &args.database_url {
+            self.database.url = url.clone();
+        }
+        if let Some(min) = args.database_min_connections {
+            self.database.min_connections = min;
+        }
+        if let Some(max) = args.database_max_connections {
+            self.database.max_connections = max;
+        }
+        if let Some(timeout) = args.database_acquire_timeout {
+            self.database.acquire_timeout = timeout;
+        }
+        if let Some(url) = &args.keycloak_url {
+            self.keycloak.api_url = url.clone();
+        }
+        if let Some(realm) = &args.keycloak_realm {
+            self.keycloak.realm = realm.clone();
+        }
+        if let Some(client_id) = &args.keycloak_client_id {
+            self.keycloak.client_id = client_id.clone();
+        }
+        if let Some(client_secret) = &args.keycloak_client_secret {
+            self.keycloak.client_secret = client_secret.clone();
+        }
+        if let Some(validation) = args.keycloak_validation {
+            self.keycloak.validation = validation;
+        }
+    }
+
+    // Check that the configuration is complete before the server starts
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.server.port == 0 {
+            return Err(Error::new("server.port must not be 0".to_string(), 500));
+        }
+        self.validate_database()?;
+        if reqwest::Url::parse(&self.keycloak.api_url).is_err() {
+            return Err(Error::new(
+                "keycloak.api_url must be a valid URL".to_string(),
+                500,
+            ));
+        }
+        if self.keycloak.realm.is_empty() || self.keycloak.client_id.is_empty() {
+            return Err(Error::new(
+                "keycloak.realm and keycloak.client_id are required".to_string(),
+                500,
+            ));
+        }
+        if self.keycloak.client_secret.is_empty() {
+            return Err(Error::new(
+                "keycloak.client_secret is required".to_string(),
+                500,
+            ));
+        }
+        if self.keycloak.validation == TokenValidation::Jwks && self.keycloak.jwks_refresh == 0 {
+            return Err(Error::new(
+                "keycloak.jwks_refresh must not be 0".to_string(),
+                500,
+            ));
+        }
+        if self.keycloak.introspection_cache_ttl > MAX_INTROSPECTION_CACHE_TTL {
+            return Err(Error::new(
+                format!(
+                    "keycloak.introspection_cache_ttl must not be above {}",
+                    MAX_INTROSPECTION_CACHE_TTL
+                ),
+                500,
+            ));
+        }
+        policy::validate_roles(&self.roles)?;
+        self.validate_rate_limit()?;
+        self.validate_alerts()?;
+        if self.costing.labour_rate.cents < 0 {
+            return Err(Error::new(
+                "costing.labour_rate must not be negative".to_string(),
+                500,
+            ));
+        }
+        Ok(())
+    }
+
+    // Check the database settings (All the migrate command needs)
+    pub fn validate_database(&self) -> Result<(), Error> {
+        if self.database.url.is_empty() {
+            return Err(Error::new("database.url is required".to_string(), 500));
+        }
+        match Backend::from_url(&self.database.url)? {
+            Backend::MySql => {
+                if let Err(err) = mysql::Opts::from_url(&self.database.url) {
+                    return Err(Error::new(format!("database.url is invalid: {}", err), 500));
+                }
+            }
+            Backend::Sqlite => {
+                if self.database.url == "sqlite://" {
+                    return Err(Error::new(
+                        "database.url needs a SQLite file".to_string(),
+                        500,
+                    ));
+                }
+            }
+            Backend::Memory => {}
+        }
+        if self.database.max_connections == 0
+            || self.database.min_connections > self.database.max_connections
+        {
+            return Err(Error::new(
+                "database.max_connections must be at least 1 and not below database.min_connections"
+                    .to_string(),
+                500,
+            ));
+        }
+        if self.database.acquire_timeout == 0 {
+            return Err(Error::new(
+                "database.acquire_timeout must not be 0".to_string(),
+                500,
+            ));
+        }
+        Ok(())
+    }
+
+    fn validate_rate_limit(&self) -> Result<(), Error> {
+        let limits = &self.rate_limit;
+        if limits.capacity == 0 || !positive(limits.refill_per_second) {
+            return Err(Error::new(
+                "rate_limit.capacity and rate_limit.refill_per_second must be above 0".to_string(),
+                500,
+            ));
+        }
+        for group in &limits.groups {
+            if group.capacity == 0 || !positive(group.refill_per_second) {
+                return Err(Error::new(
+                    format!(
+                        "rate_limit group {}: capacity and refill_per_second must be above 0",
+                        group.name
+                    ),
+                    500,
+                ));
+            }
+            if let Some(method) = group
+                .methods
+                .iter()
+                .find(|method| !METHODS.contains(&method.as_str()))
+            {
+                return Err(Error::new(
+                    format!("rate_limit group {}: unknown method {}", group.name, method),
+                    500,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_alerts(&self) -> Result<(), Error> {
+        let alerts = &self.alerts;
+        if alerts.timeout == 0 {
+            return Err(Error::new("alerts.timeout must not be 0".to_string(), 500));
+        }
+        if !alerts.webhook_url.is_empty()
+            && !matches!(
+                reqwest::Url::parse(&alerts.webhook_url).map(|url| url.scheme().to_string()),
+                Ok(scheme) if scheme == "http" || scheme == "https"
+            )
+        {
+            return Err(Error::new(
+                "alerts.webhook_url must be an http or https URL".to_string(),
+                500,
+            ));
+        }
+        let smtp = &alerts.smtp;
+        if smtp.to.is_empty() {
+            return Ok(());
+        }
+        if smtp.host.is_empty() || smtp.port == 0 {
+            return Err(Error::new(
+                "alerts.smtp.host and alerts.smtp.port are required for mails".to_string(),
+                500,
+            ));
+        }
+        if let Some(address) = std::iter::once(&smtp.from)
+            .chain(&smtp.to)
+            .find(|address| !mail_address(address))
+        {
+            return Err(Error::new(
+                format!("alerts.smtp: {} is not a mail address", address),
+                500,
+            ));
+        }
+        Ok(())
+    }
+
+    // Address the HTTP server binds to
+    pub fn bind_address(&self) -> String {
+        format!("{}:{}", self.server.host, self.server.port)
+    }
+}
+
+// Parse a numeric environment variable
+fn parse_env<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::new(format!("{} is not a valid number: {}", key, value), 500))
+}
+
+// A usable refill rate (Not 0, negative, NaN or infinite)
+fn positive(value: f64) -> bool {
+    value.is_finite() && value > 0.0
+}
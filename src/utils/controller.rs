@@ -5,457 +5,1135 @@
 
 */
 
-// Mutex is used to allow multiple threads to access the repository (Need to use external crate because it needs to have thread safety)
-use async_mutex::Mutex;
-
 // Import the actix-web crate
 use actix_web::{
-    delete, get, post, put,
-    web::{Json, Path},
-    HttpResponse,
+    delete,
+    error::{JsonPayloadError, PathError, QueryPayloadError},
+    get,
+    http::header::{ETAG, LOCATION},
+    patch, post, put,
+    web::{Data, JsonConfig, Path, PathConfig, Query, QueryConfig, ServiceConfig},
+    HttpRequest, HttpResponse, ResponseError,
 };
-use actix_web_httpauth::extractors::bearer::BearerAuth;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
 
 // Import the models
-use crate::models::client::Client;
-use crate::models::material::Material;
-use crate::models::material_type::MaterialType;
-use crate::models::project::Project;
-use crate::models::project_material::ProjectMaterial;
+use crate::models::client::{Client, NewClient};
+use crate::models::error::{self, Error};
+use crate::models::material::{Material, NewMaterial};
+use crate::models::material_type::{MaterialType, NewMaterialType};
+use crate::models::project::{NewProject, Project};
+use crate::models::project_material::{NewProjectMaterial, ProjectMaterial};
+use crate::models::project_transition::{ProjectTransition, Transition};
+use crate::models::purchase_order::{NewPurchaseOrder, PurchaseOrder};
+use crate::models::purchase_order_line::{NewPurchaseOrderLine, PurchaseOrderLine, Receipt};
+use crate::models::stock_movement::{Booking, NewStockMovement, StockMovement};
+use crate::models::supplier::{NewSupplier, Supplier};
+use crate::models::time_entry::{NewTimeEntry, TimeEntry, TimeGroup, Timer};
+use crate::utils::auth::{Authorizer, Principal};
+use crate::utils::body::Json;
+use crate::utils::etag;
+use crate::utils::policy;
 use crate::utils::repository::Repository;
+use crate::utils::store::query::{Fields, ListQuery, Page};
+use crate::utils::store::search::SearchQuery;
+use crate::utils::store::version::Versioned;
+use crate::utils::validation::Validate;
+
+// Register all routes (Used by the server and the tests)
+pub fn configure(cfg: &mut ServiceConfig) {
+    // Bodies, ids and query strings that cannot be read are answered with the usual error body
+    cfg.app_data(JsonConfig::default().error_handler(json_error))
+        .app_data(PathConfig::default().error_handler(path_error))
+        .app_data(QueryConfig::default().error_handler(query_error));
+    cfg.service(get_project)
+        .service(get_project_by_id)
+        .service(create_project)
+        .service(update_project)
+        .service(patch_project)
+        .service(delete_project)
+        .service(get_project_transitions)
+        .service(transition_project)
+        .service(get_project_costing)
+        .service(get_budget_variance)
+        .service(get_material_types)
+        .service(get_material_type_by_id)
+        .service(create_material_type)
+        .service(update_material_type)
+        .service(patch_material_type)
+        .service(delete_material_type)
+        .service(get_materials)
+        // Before /Materials/{id}, otherwise LowStock would be taken for an id
+        .service(get_low_stock_materials)
+        .service(get_material_by_id)
+        .service(create_material)
+        .service(update_material)
+        .service(patch_material)
+        .service(delete_material)
+        .service(get_stock_movements)
+        .service(create_stock_movement)
+        .service(get_project_materials)
+        .service(get_project_material_by_id)
+        .service(create_project_material)
+        .service(update_project_material)
+        .service(patch_project_material)
+        .service(delete_project_material)
+        .service(get_clients)
+        .service(get_client_by_id)
+        .service(create_client)
+        .service(update_client)
+        .service(patch_client)
+        .service(delete_client)
+        .service(get_suppliers)
+        .service(get_supplier_by_id)
+        .service(create_supplier)
+        .service(update_supplier)
+        .service(patch_supplier)
+        .service(delete_supplier)
+        .service(get_purchase_orders)
+        // Before /PurchaseOrders/{id}, otherwise the permission of SuggestReorder would be looked up for an id
+        .service(suggest_reorder)
+        .service(get_purchase_order_by_id)
+        .service(create_purchase_order)
+        .service(update_purchase_order)
+        .service(patch_purchase_order)
+        .service(delete_purchase_order)
+        .service(get_purchase_order_lines)
+        .service(get_purchase_order_line_by_id)
+        .service(create_purchase_order_line)
+        .service(update_purchase_order_line)
+        .service(patch_purchase_order_line)
+        .service(delete_purchase_order_line)
+        .service(receive_purchase_order_line)
+        .service(get_time_entries)
+        // Before /TimeEntries/{id}, otherwise Summary, Start and Stop would be taken for an id
+        .service(get_project_time_summary)
+        .service(get_employee_time_summary)
+        .service(start_timer)
+        .service(stop_timer)
+        .service(get_time_entry_by_id)
+        .service(create_time_entry)
+        .service(update_time_entry)
+        .service(patch_time_entry)
+        .service(delete_time_entry)
+        .service(search);
+}
+
+// A body that is no JSON, too large or of the wrong content type
+fn json_error(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let code = i32::from(err.status_code().as_u16());
+    Error::new(format!("Invalid body: {}", err), code).into()
+}
+
+// A path segment that does not fit (e.g. /Projects/abc), there is no such resource
+fn path_error(err: PathError, _req: &HttpRequest) -> actix_web::Error {
+    Error::new(format!("Invalid path: {}", err), 404).into()
+}
+
+fn query_error(err: QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    Error::new(format!("Invalid query string: {}", err), 400).into()
+}
 
-// Create a new repository
-static REPO: Mutex<Repository> = Mutex::new(Repository::new());
+// The pagination, sorting and filters of a list request (Checked against the fields of the model)
+fn list_query<T: Fields>(req: &HttpRequest) -> Result<ListQuery, Error> {
+    let params: Vec<(String, String)> = query_params(req)?;
+    ListQuery::parse::<T>(&params)
+}
+
+// The query parameters of the request (400 with the usual error body if they do not fit)
+fn query_params<T: DeserializeOwned>(req: &HttpRequest) -> Result<T, Error> {
+    Query::<T>::from_query(req.query_string())
+        .map(Query::into_inner)
+        .map_err(|err| Error::new(format!("Invalid query string: {}", err), 400))
+}
+
+// A page of a list, the number of all matching rows is sent in the X-Total-Count header
+fn page_response<T: Serialize>(page: Page<T>) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("X-Total-Count", page.total))
+        .json(page.items)
+}
+
+// 201 Created with the new resource and where it can be found
+fn created<T: Serialize + Versioned>(path: &str, id: i32, value: T) -> HttpResponse {
+    HttpResponse::Created()
+        .insert_header((LOCATION, format!("{}/{}", path, id)))
+        .insert_header((ETAG, etag::etag(value.version())))
+        .json(value)
+}
+
+// A resource with its ETag (304 Not Modified if the client already has this version)
+fn resource<T: Serialize + Versioned>(req: &HttpRequest, value: T) -> HttpResponse {
+    let tag = etag::etag(value.version());
+    if etag::not_modified(req, value.version()) {
+        return HttpResponse::NotModified()
+            .insert_header((ETAG, tag))
+            .finish();
+    }
+    HttpResponse::Ok().insert_header((ETAG, tag)).json(value)
+}
+
+// The updated resource with its new ETag
+fn updated<T: Serialize + Versioned>(value: T) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header((ETAG, etag::etag(value.version())))
+        .json(value)
+}
+
+// The id of the path is the id of the resource, the body cannot point to another one
+fn check_id(path_id: i32, body_id: i32) -> Result<(), Error> {
+    if path_id == body_id {
+        return Ok(());
+    }
+    Err(Error::with_kind(
+        format!(
+            "The id {} of the body does not match the id {} of the path",
+            body_id, path_id
+        ),
+        422,
+        error::ID_MISMATCH,
+    ))
+}
+
+// A patch may leave out the id, but it cannot change it
+fn check_patch_id(path_id: i32, patch: &Value) -> Result<(), Error> {
+    match patch.get("id") {
+        None => Ok(()),
+        Some(id) if id.as_i64() == Some(path_id as i64) => Ok(()),
+        Some(id) => Err(Error::with_kind(
+            format!(
+                "The id {} of the patch does not match the id {} of the path",
+                id, path_id
+            ),
+            422,
+            error::ID_MISMATCH,
+        )),
+    }
+}
 
 // Define the routes (Only gonna comment the first one, the rest are the same (Mostly))
 #[get("/Projects")]
-pub async fn get_project(auth: BearerAuth) -> HttpResponse {
-    // Call the neccecary function from the repository
-    let result = REPO
-        // Lock the repository to prevent multiple threads from accessing it at the same time
-        .lock()
-        // Await the return of the lock call
-        .await
-        // Call the get_all_projects function from the repository
-        .get_all_projects(&auth.token().to_string())
-        // Await the return of the get_all_projects function
-        .await;
-    // Check if the result is an error
-    let projects = match result {
-        // If the result is Ok, return the projects
-        Ok(projects) => projects,
-        // If the result is an error, print the error and return an unauthorized response
-        Err(e) => {
-            // Print the error and return an unauthorized response (Cannot create a custom HTTP Response so far, so just send 401)
-            println!("{}", e);
-            return HttpResponse::Unauthorized().finish();
-        }
-    };
-    // Return the projects
-    HttpResponse::Ok().json(projects)
+pub async fn get_project(repo: Data<Repository>, req: HttpRequest) -> Result<HttpResponse, Error> {
+    // The auth middleware already checked the token and the permission of the route
+    // Call the neccecary function from the shared repository (No lock needed, every query gets its own connection)
+    // An error is returned as is, it is turned into a JSON response with the matching status code
+    // The list is paged, sorted and filtered by the query parameters (?limit=50&offset=0&sort=name:asc&client_id=5)
+    let projects = repo.get_all_projects(list_query::<Project>(&req)?).await?;
+    // Return the page of projects (The number of all matching projects is in the X-Total-Count header)
+    Ok(page_response(projects))
 }
 
 #[get("/Projects/{id}")]
-pub async fn get_project_by_id(auth: BearerAuth, id: Path<i32>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .get_project(id.into_inner(), &auth.token().to_string())
-        .await;
-    let project = match result {
-        Ok(project) => project,
-        Err(e) => {
-            println!("{}", e);
-            return HttpResponse::Unauthorized().finish();
-        }
-    };
-    HttpResponse::Ok().json(project)
+pub async fn get_project_by_id(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let project = repo.get_project(id.into_inner()).await?;
+    // The ETag is the version of the project, 304 without a body if the client already has it (If-None-Match)
+    Ok(resource(&req, project))
 }
 
 #[post("/Projects")]
-pub async fn create_project(auth: BearerAuth, project: Json<Project>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .add_project(project.into_inner(), &auth.token().to_string())
-        .await;
-    match result {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            println!("{}", e);
-            HttpResponse::Unauthorized().finish()
-        }
-    }
+pub async fn create_project(
+    repo: Data<Repository>,
+    project: Json<NewProject>,
+) -> Result<HttpResponse, Error> {
+    // The body is checked before it reaches the repository (422 with every invalid field)
+    project.validate()?;
+    let project = repo.add_project(project.into_inner()).await?;
+    Ok(created("/Projects", project.id, project))
 }
 
 #[put("/Projects/{id}")]
-pub async fn update_project(auth: BearerAuth, project: Json<Project>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .update_project(project.into_inner(), &auth.token().to_string())
-        .await;
-    match result {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            println!("{}", e);
-            HttpResponse::Unauthorized().finish()
-        }
-    }
+pub async fn update_project(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+    project: Json<Project>,
+) -> Result<HttpResponse, Error> {
+    // The path decides which project is updated, a different id in the body is a mistake of the client
+    check_id(id.into_inner(), project.id)?;
+    project.validate()?;
+    // With If-Match the project is only updated if it still has that version (Otherwise 412)
+    let project = repo
+        .update_project(project.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(updated(project))
+}
+
+#[patch("/Projects/{id}")]
+pub async fn patch_project(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+    patch: Json<Value>,
+) -> Result<HttpResponse, Error> {
+    let id = id.into_inner();
+    check_patch_id(id, &patch)?;
+    // Only the fields of the patch change (JSON Merge Patch), the updated project is returned
+    let project = repo
+        .patch_project(id, patch.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(updated(project))
 }
 
 #[delete("/Projects/{id}")]
-pub async fn delete_project(auth: BearerAuth, id: Path<i32>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .remove_project(id.into_inner(), &auth.token().to_string())
-        .await;
-    match result {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            println!("{}", e);
-            HttpResponse::Unauthorized().finish()
-        }
-    }
+pub async fn delete_project(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    repo.remove_project(id.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+// The status history of a project, oldest first unless sorted otherwise
+#[get("/Projects/{id}/Transitions")]
+pub async fn get_project_transitions(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let transitions = repo
+        .get_project_transitions(id.into_inner(), list_query::<ProjectTransition>(&req)?)
+        .await?;
+    Ok(page_response(transitions))
+}
+
+// Move the project to another status in the name of the user of the token ({"status": "accepted"})
+#[post("/Projects/{id}/Transitions")]
+pub async fn transition_project(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    principal: Principal,
+    req: HttpRequest,
+    transition: Json<Transition>,
+) -> Result<HttpResponse, Error> {
+    let project = repo
+        .transition_project(
+            id.into_inner(),
+            transition.into_inner(),
+            principal.subject,
+            etag::if_match(&req)?,
+        )
+        .await?;
+    Ok(updated(project))
+}
+
+// The costs of a project computed from its project materials and logged hours
+#[get("/Projects/{id}/Costing")]
+pub async fn get_project_costing(
+    repo: Data<Repository>,
+    id: Path<i32>,
+) -> Result<HttpResponse, Error> {
+    let costing = repo.get_project_costing(id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(costing))
+}
+
+// The cost and hours variance of the projects (Same paging, sorting and filters as /Projects)
+#[get("/Reports/BudgetVariance")]
+pub async fn get_budget_variance(
+    repo: Data<Repository>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let report = repo
+        .get_budget_variance(list_query::<Project>(&req)?)
+        .await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(("X-Total-Count", report.total))
+        .json(report))
 }
 
 #[get("/MaterialTypes")]
-pub async fn get_material_types(auth: BearerAuth) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .get_all_material_types(&auth.token().to_string())
-        .await;
-    let material_types = match result {
-        Ok(material_types) => material_types,
-        Err(e) => {
-            println!("{}", e);
-            return HttpResponse::Unauthorized().finish();
-        }
-    };
-    HttpResponse::Ok().json(material_types)
+pub async fn get_material_types(
+    repo: Data<Repository>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let material_types = repo
+        .get_all_material_types(list_query::<MaterialType>(&req)?)
+        .await?;
+    Ok(page_response(material_types))
 }
 
 #[get("/MaterialTypes/{id}")]
-pub async fn get_material_type_by_id(auth: BearerAuth, id: Path<i32>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .get_material_type(id.into_inner(), &auth.token().to_string())
-        .await;
-    let material_type = match result {
-        Ok(material_type) => material_type,
-        Err(e) => {
-            println!("{}", e);
-            return HttpResponse::Unauthorized().finish();
-        }
-    };
-    HttpResponse::Ok().json(material_type)
+pub async fn get_material_type_by_id(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let material_type = repo.get_material_type(id.into_inner()).await?;
+    Ok(resource(&req, material_type))
 }
 
 #[post("/MaterialTypes")]
 pub async fn create_material_type(
-    auth: BearerAuth,
-    material_type: Json<MaterialType>,
-) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .add_material_type(material_type.into_inner(), &auth.token().to_string())
-        .await;
-    match result {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            println!("{}", e);
-            HttpResponse::Unauthorized().finish()
-        }
-    }
+    repo: Data<Repository>,
+    material_type: Json<NewMaterialType>,
+) -> Result<HttpResponse, Error> {
+    material_type.validate()?;
+    let material_type = repo.add_material_type(material_type.into_inner()).await?;
+    Ok(created("/MaterialTypes", material_type.id, material_type))
 }
 
 #[put("/MaterialTypes/{id}")]
 pub async fn update_material_type(
-    auth: BearerAuth,
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
     material_type: Json<MaterialType>,
-) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .update_material_type(material_type.into_inner(), &auth.token().to_string())
-        .await;
-    match result {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            println!("{}", e);
-            HttpResponse::Unauthorized().finish()
-        }
-    }
+) -> Result<HttpResponse, Error> {
+    check_id(id.into_inner(), material_type.id)?;
+    material_type.validate()?;
+    let material_type = repo
+        .update_material_type(material_type.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(updated(material_type))
+}
+
+#[patch("/MaterialTypes/{id}")]
+pub async fn patch_material_type(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+    patch: Json<Value>,
+) -> Result<HttpResponse, Error> {
+    let id = id.into_inner();
+    check_patch_id(id, &patch)?;
+    let material_type = repo
+        .patch_material_type(id, patch.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(updated(material_type))
 }
 
 #[delete("/MaterialTypes/{id}")]
-pub async fn delete_material_type(auth: BearerAuth, id: Path<i32>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .remove_material_type(id.into_inner(), &auth.token().to_string())
-        .await;
-    match result {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            println!("{}", e);
-            HttpResponse::Unauthorized().finish()
-        }
-    }
+pub async fn delete_material_type(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    repo.remove_material_type(id.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(HttpResponse::Ok().finish())
 }
 
 #[get("/Materials")]
-pub async fn get_materials(auth: BearerAuth) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .get_all_materials(&auth.token().to_string())
-        .await;
-    let materials = match result {
-        Ok(materials) => materials,
-        Err(e) => {
-            println!("{}", e);
-            return HttpResponse::Unauthorized().finish();
-        }
-    };
-    HttpResponse::Ok().json(materials)
+pub async fn get_materials(
+    repo: Data<Repository>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let materials = repo
+        .get_all_materials(list_query::<Material>(&req)?)
+        .await?;
+    Ok(page_response(materials))
+}
+
+// The materials whose amount is below their threshold_value (Same paging, sorting and filters as /Materials)
+#[get("/Materials/LowStock")]
+pub async fn get_low_stock_materials(
+    repo: Data<Repository>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let materials = repo
+        .get_low_stock_materials(list_query::<Material>(&req)?)
+        .await?;
+    Ok(page_response(materials))
 }
 
 #[get("/Materials/{id}")]
-pub async fn get_material_by_id(auth: BearerAuth, id: Path<i32>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .get_material(id.into_inner(), &auth.token().to_string())
-        .await;
-    let material = match result {
-        Ok(material) => material,
-        Err(e) => {
-            println!("{}", e);
-            return HttpResponse::Unauthorized().finish();
-        }
-    };
-    HttpResponse::Ok().json(material)
+pub async fn get_material_by_id(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let material = repo.get_material(id.into_inner()).await?;
+    Ok(resource(&req, material))
 }
 
 #[post("/Materials")]
-pub async fn create_material(auth: BearerAuth, material: Json<Material>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .add_material(material.into_inner(), &auth.token().to_string())
-        .await;
-    match result {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            println!("{}", e);
-            HttpResponse::Unauthorized().finish()
-        }
-    }
+pub async fn create_material(
+    repo: Data<Repository>,
+    material: Json<NewMaterial>,
+) -> Result<HttpResponse, Error> {
+    material.validate()?;
+    let material = repo.add_material(material.into_inner()).await?;
+    Ok(created("/Materials", material.id, material))
 }
 
 #[put("/Materials/{id}")]
-pub async fn update_material(auth: BearerAuth, material: Json<Material>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .update_material(material.into_inner(), &auth.token().to_string())
-        .await;
-    match result {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            println!("{}", e);
-            HttpResponse::Unauthorized().finish()
-        }
-    }
+pub async fn update_material(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+    material: Json<Material>,
+) -> Result<HttpResponse, Error> {
+    check_id(id.into_inner(), material.id)?;
+    material.validate()?;
+    let material = repo
+        .update_material(material.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(updated(material))
+}
+
+#[patch("/Materials/{id}")]
+pub async fn patch_material(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+    patch: Json<Value>,
+) -> Result<HttpResponse, Error> {
+    let id = id.into_inner();
+    check_patch_id(id, &patch)?;
+    let material = repo
+        .patch_material(id, patch.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(updated(material))
 }
 
 #[delete("/Materials/{id}")]
-pub async fn delete_material(auth: BearerAuth, id: Path<i32>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .remove_material(id.into_inner(), &auth.token().to_string())
-        .await;
-    match result {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            println!("{}", e);
-            HttpResponse::Unauthorized().finish()
-        }
-    }
+pub async fn delete_material(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    repo.remove_material(id.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+// The stock ledger of a material (/Materials/5/Movements?after=2026-10-01&kind=consume)
+#[get("/Materials/{id}/Movements")]
+pub async fn get_stock_movements(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let movements = repo
+        .get_stock_movements(id.into_inner(), list_query::<StockMovement>(&req)?)
+        .await?;
+    Ok(page_response(movements))
+}
+
+// Book a movement in the name of the user of the token
+#[post("/Materials/{id}/Movements")]
+pub async fn create_stock_movement(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    principal: Principal,
+    movement: Json<NewStockMovement>,
+) -> Result<HttpResponse, Error> {
+    movement.validate()?;
+    let movement = repo
+        .add_stock_movement(
+            id.into_inner(),
+            movement.into_inner(),
+            Booking::new(principal.subject, false),
+        )
+        .await?;
+    // Movements have no version and no route of their own, the Location is the ledger they were booked into
+    Ok(HttpResponse::Created()
+        .insert_header((
+            LOCATION,
+            format!("/Materials/{}/Movements", movement.material_id),
+        ))
+        .json(movement))
+}
+
+#[derive(Deserialize)]
+struct BookingParams {
+    #[serde(default)]
+    backorder: bool,
+}
+
+// The stock movements of a project material request (?backorder=true takes material that is not in stock)
+fn booking(req: &HttpRequest, principal: Principal) -> Result<Booking, Error> {
+    let params: BookingParams = query_params(req)?;
+    Ok(Booking::new(principal.subject, params.backorder))
 }
 
 #[get("/ProjectMaterials")]
-pub async fn get_project_materials(auth: BearerAuth) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .get_all_project_materials(&auth.token().to_string())
-        .await;
-    let project_materials = match result {
-        Ok(project_materials) => project_materials,
-        Err(e) => {
-            println!("{}", e);
-            return HttpResponse::Unauthorized().finish();
-        }
-    };
-    HttpResponse::Ok().json(project_materials)
+pub async fn get_project_materials(
+    repo: Data<Repository>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let project_materials = repo
+        .get_all_project_materials(list_query::<ProjectMaterial>(&req)?)
+        .await?;
+    Ok(page_response(project_materials))
 }
 
 #[get("/ProjectMaterials/{id}")]
-pub async fn get_project_material_by_id(auth: BearerAuth, id: Path<i32>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .get_project_material(id.into_inner(), &auth.token().to_string())
-        .await;
-    let project_material = match result {
-        Ok(project_material) => project_material,
-        Err(e) => {
-            println!("{}", e);
-            return HttpResponse::Unauthorized().finish();
-        }
-    };
-    HttpResponse::Ok().json(project_material)
+pub async fn get_project_material_by_id(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let project_material = repo.get_project_material(id.into_inner()).await?;
+    Ok(resource(&req, project_material))
 }
 
 #[post("/ProjectMaterials")]
 pub async fn create_project_material(
-    auth: BearerAuth,
-    project_material: Json<ProjectMaterial>,
-) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .add_project_material(project_material.into_inner(), &auth.token().to_string())
-        .await;
-    match result {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            println!("{}", e);
-            HttpResponse::Unauthorized().finish()
-        }
-    }
+    repo: Data<Repository>,
+    principal: Principal,
+    req: HttpRequest,
+    project_material: Json<NewProjectMaterial>,
+) -> Result<HttpResponse, Error> {
+    project_material.validate()?;
+    let project_material = repo
+        .add_project_material(project_material.into_inner(), booking(&req, principal)?)
+        .await?;
+    Ok(created(
+        "/ProjectMaterials",
+        project_material.id,
+        project_material,
+    ))
 }
 
 #[put("/ProjectMaterials/{id}")]
 pub async fn update_project_material(
-    auth: BearerAuth,
+    repo: Data<Repository>,
+    id: Path<i32>,
+    principal: Principal,
+    req: HttpRequest,
     project_material: Json<ProjectMaterial>,
-) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .update_project_material(project_material.into_inner(), &auth.token().to_string())
-        .await;
-    match result {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            println!("{}", e);
-            HttpResponse::Unauthorized().finish()
-        }
-    }
+) -> Result<HttpResponse, Error> {
+    check_id(id.into_inner(), project_material.id)?;
+    project_material.validate()?;
+    let project_material = repo
+        .update_project_material(
+            project_material.into_inner(),
+            etag::if_match(&req)?,
+            booking(&req, principal)?,
+        )
+        .await?;
+    Ok(updated(project_material))
+}
+
+#[patch("/ProjectMaterials/{id}")]
+pub async fn patch_project_material(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    principal: Principal,
+    req: HttpRequest,
+    patch: Json<Value>,
+) -> Result<HttpResponse, Error> {
+    let id = id.into_inner();
+    check_patch_id(id, &patch)?;
+    let project_material = repo
+        .patch_project_material(
+            id,
+            patch.into_inner(),
+            etag::if_match(&req)?,
+            booking(&req, principal)?,
+        )
+        .await?;
+    Ok(updated(project_material))
 }
 
 #[delete("/ProjectMaterials/{id}")]
-pub async fn delete_project_material(auth: BearerAuth, id: Path<i32>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .remove_project_material(id.into_inner(), &auth.token().to_string())
-        .await;
-    match result {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            println!("{}", e);
-            HttpResponse::Unauthorized().finish()
-        }
-    }
+pub async fn delete_project_material(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    principal: Principal,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    repo.remove_project_material(
+        id.into_inner(),
+        etag::if_match(&req)?,
+        booking(&req, principal)?,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().finish())
 }
 
 #[get("/Clients")]
-pub async fn get_clients(auth: BearerAuth) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .get_all_clients(&auth.token().to_string())
-        .await;
-    let clients = match result {
-        Ok(clients) => clients,
-        Err(e) => {
-            println!("{}", e);
-            return HttpResponse::Unauthorized().finish();
-        }
-    };
-    HttpResponse::Ok().json(clients)
+pub async fn get_clients(repo: Data<Repository>, req: HttpRequest) -> Result<HttpResponse, Error> {
+    let clients = repo.get_all_clients(list_query::<Client>(&req)?).await?;
+    Ok(page_response(clients))
 }
 
 #[get("/Clients/{id}")]
-pub async fn get_client_by_id(auth: BearerAuth, id: Path<i32>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .get_client(id.into_inner(), &auth.token().to_string())
-        .await;
-    let client = match result {
-        Ok(client) => client,
-        Err(e) => {
-            println!("{}", e);
-            return HttpResponse::Unauthorized().finish();
-        }
-    };
-    HttpResponse::Ok().json(client)
+pub async fn get_client_by_id(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let client = repo.get_client(id.into_inner()).await?;
+    Ok(resource(&req, client))
 }
 
 #[post("/Clients")]
-pub async fn create_client(auth: BearerAuth, client: Json<Client>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .add_client(client.into_inner(), &auth.token().to_string())
-        .await;
-    match result {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            println!("{}", e);
-            HttpResponse::Unauthorized().finish()
-        }
-    }
+pub async fn create_client(
+    repo: Data<Repository>,
+    client: Json<NewClient>,
+) -> Result<HttpResponse, Error> {
+    client.validate()?;
+    let client = repo.add_client(client.into_inner()).await?;
+    Ok(created("/Clients", client.id, client))
 }
 
 #[put("/Clients/{id}")]
-pub async fn update_client(auth: BearerAuth, client: Json<Client>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .update_client(client.into_inner(), &auth.token().to_string())
-        .await;
-    match result {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            println!("{}", e);
-            HttpResponse::Unauthorized().finish()
-        }
-    }
+pub async fn update_client(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+    client: Json<Client>,
+) -> Result<HttpResponse, Error> {
+    check_id(id.into_inner(), client.id)?;
+    client.validate()?;
+    let client = repo
+        .update_client(client.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(updated(client))
+}
+
+#[patch("/Clients/{id}")]
+pub async fn patch_client(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+    patch: Json<Value>,
+) -> Result<HttpResponse, Error> {
+    let id = id.into_inner();
+    check_patch_id(id, &patch)?;
+    let client = repo
+        .patch_client(id, patch.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(updated(client))
 }
 
 #[delete("/Clients/{id}")]
-pub async fn delete_client(auth: BearerAuth, id: Path<i32>) -> HttpResponse {
-    let result = REPO
-        .lock()
-        .await
-        .remove_client(id.into_inner(), &auth.token().to_string())
-        .await;
-    match result {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            println!("{}", e);
-            HttpResponse::Unauthorized().finish()
-        }
+pub async fn delete_client(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    repo.remove_client(id.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[get("/Suppliers")]
+pub async fn get_suppliers(
+    repo: Data<Repository>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let suppliers = repo
+        .get_all_suppliers(list_query::<Supplier>(&req)?)
+        .await?;
+    Ok(page_response(suppliers))
+}
+
+#[get("/Suppliers/{id}")]
+pub async fn get_supplier_by_id(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let supplier = repo.get_supplier(id.into_inner()).await?;
+    Ok(resource(&req, supplier))
+}
+
+#[post("/Suppliers")]
+pub async fn create_supplier(
+    repo: Data<Repository>,
+    supplier: Json<NewSupplier>,
+) -> Result<HttpResponse, Error> {
+    supplier.validate()?;
+    let supplier = repo.add_supplier(supplier.into_inner()).await?;
+    Ok(created("/Suppliers", supplier.id, supplier))
+}
+
+#[put("/Suppliers/{id}")]
+pub async fn update_supplier(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+    supplier: Json<Supplier>,
+) -> Result<HttpResponse, Error> {
+    check_id(id.into_inner(), supplier.id)?;
+    supplier.validate()?;
+    let supplier = repo
+        .update_supplier(supplier.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(updated(supplier))
+}
+
+#[patch("/Suppliers/{id}")]
+pub async fn patch_supplier(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+    patch: Json<Value>,
+) -> Result<HttpResponse, Error> {
+    let id = id.into_inner();
+    check_patch_id(id, &patch)?;
+    let supplier = repo
+        .patch_supplier(id, patch.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(updated(supplier))
+}
+
+#[delete("/Suppliers/{id}")]
+pub async fn delete_supplier(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    repo.remove_supplier(id.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[get("/PurchaseOrders")]
+pub async fn get_purchase_orders(
+    repo: Data<Repository>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let purchase_orders = repo
+        .get_all_purchase_orders(list_query::<PurchaseOrder>(&req)?)
+        .await?;
+    Ok(page_response(purchase_orders))
+}
+
+#[get("/PurchaseOrders/{id}")]
+pub async fn get_purchase_order_by_id(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let purchase_order = repo.get_purchase_order(id.into_inner()).await?;
+    Ok(resource(&req, purchase_order))
+}
+
+// A new purchase order is a draft, its lines are added with POST /PurchaseOrderLines
+#[post("/PurchaseOrders")]
+pub async fn create_purchase_order(
+    repo: Data<Repository>,
+    purchase_order: Json<NewPurchaseOrder>,
+) -> Result<HttpResponse, Error> {
+    purchase_order.validate()?;
+    let purchase_order = repo.add_purchase_order(purchase_order.into_inner()).await?;
+    Ok(created(
+        "/PurchaseOrders",
+        purchase_order.id,
+        purchase_order,
+    ))
+}
+
+// Draft purchase orders for the materials below their threshold_value (One per preferred supplier)
+#[post("/PurchaseOrders/SuggestReorder")]
+pub async fn suggest_reorder(repo: Data<Repository>) -> Result<HttpResponse, Error> {
+    let reorder = repo.suggest_reorder().await?;
+    Ok(HttpResponse::Ok().json(reorder))
+}
+
+#[put("/PurchaseOrders/{id}")]
+pub async fn update_purchase_order(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+    purchase_order: Json<PurchaseOrder>,
+) -> Result<HttpResponse, Error> {
+    check_id(id.into_inner(), purchase_order.id)?;
+    purchase_order.validate()?;
+    let purchase_order = repo
+        .update_purchase_order(purchase_order.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(updated(purchase_order))
+}
+
+#[patch("/PurchaseOrders/{id}")]
+pub async fn patch_purchase_order(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+    patch: Json<Value>,
+) -> Result<HttpResponse, Error> {
+    let id = id.into_inner();
+    check_patch_id(id, &patch)?;
+    let purchase_order = repo
+        .patch_purchase_order(id, patch.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(updated(purchase_order))
+}
+
+#[delete("/PurchaseOrders/{id}")]
+pub async fn delete_purchase_order(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    repo.remove_purchase_order(id.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[get("/PurchaseOrderLines")]
+pub async fn get_purchase_order_lines(
+    repo: Data<Repository>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let lines = repo
+        .get_all_purchase_order_lines(list_query::<PurchaseOrderLine>(&req)?)
+        .await?;
+    Ok(page_response(lines))
+}
+
+#[get("/PurchaseOrderLines/{id}")]
+pub async fn get_purchase_order_line_by_id(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let line = repo.get_purchase_order_line(id.into_inner()).await?;
+    Ok(resource(&req, line))
+}
+
+#[post("/PurchaseOrderLines")]
+pub async fn create_purchase_order_line(
+    repo: Data<Repository>,
+    line: Json<NewPurchaseOrderLine>,
+) -> Result<HttpResponse, Error> {
+    line.validate()?;
+    let line = repo.add_purchase_order_line(line.into_inner()).await?;
+    Ok(created("/PurchaseOrderLines", line.id, line))
+}
+
+#[put("/PurchaseOrderLines/{id}")]
+pub async fn update_purchase_order_line(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+    line: Json<PurchaseOrderLine>,
+) -> Result<HttpResponse, Error> {
+    check_id(id.into_inner(), line.id)?;
+    line.validate()?;
+    let line = repo
+        .update_purchase_order_line(line.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(updated(line))
+}
+
+#[patch("/PurchaseOrderLines/{id}")]
+pub async fn patch_purchase_order_line(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+    patch: Json<Value>,
+) -> Result<HttpResponse, Error> {
+    let id = id.into_inner();
+    check_patch_id(id, &patch)?;
+    let line = repo
+        .patch_purchase_order_line(id, patch.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(updated(line))
+}
+
+#[delete("/PurchaseOrderLines/{id}")]
+pub async fn delete_purchase_order_line(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    repo.remove_purchase_order_line(id.into_inner(), etag::if_match(&req)?)
+        .await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+// A delivery of the line, booked into the stock in the name of the user of the token
+#[post("/PurchaseOrderLines/{id}/Receive")]
+pub async fn receive_purchase_order_line(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    principal: Principal,
+    receipt: Json<Receipt>,
+) -> Result<HttpResponse, Error> {
+    receipt.validate()?;
+    let line = repo
+        .receive_purchase_order_line(
+            id.into_inner(),
+            receipt.quantity,
+            Booking::new(principal.subject, false),
+        )
+        .await?;
+    Ok(updated(line))
+}
+
+#[get("/TimeEntries")]
+pub async fn get_time_entries(
+    repo: Data<Repository>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let entries = repo
+        .get_all_time_entries(list_query::<TimeEntry>(&req)?)
+        .await?;
+    Ok(page_response(entries))
+}
+
+// The logged hours per project (Filtered like the list, e.g. ?after=2026-10-01&user_id=...)
+#[get("/TimeEntries/Summary/Projects")]
+pub async fn get_project_time_summary(
+    repo: Data<Repository>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let summary = repo
+        .get_time_summary(list_query::<TimeEntry>(&req)?, TimeGroup::Project)
+        .await?;
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+// The logged hours per employee (e.g. ?project_id=5)
+#[get("/TimeEntries/Summary/Employees")]
+pub async fn get_employee_time_summary(
+    repo: Data<Repository>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let summary = repo
+        .get_time_summary(list_query::<TimeEntry>(&req)?, TimeGroup::User)
+        .await?;
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+// Start a timer for the user of the token (One at a time)
+#[post("/TimeEntries/Start")]
+pub async fn start_timer(
+    repo: Data<Repository>,
+    principal: Principal,
+    timer: Json<Timer>,
+) -> Result<HttpResponse, Error> {
+    timer.validate()?;
+    let entry = repo
+        .start_timer(timer.into_inner(), principal.subject)
+        .await?;
+    Ok(created("/TimeEntries", entry.id, entry))
+}
+
+// Stop the running timer of the user of the token
+#[post("/TimeEntries/Stop")]
+pub async fn stop_timer(
+    repo: Data<Repository>,
+    principal: Principal,
+) -> Result<HttpResponse, Error> {
+    let entry = repo.stop_timer(principal.subject).await?;
+    Ok(updated(entry))
+}
+
+#[get("/TimeEntries/{id}")]
+pub async fn get_time_entry_by_id(
+    repo: Data<Repository>,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let entry = repo.get_time_entry(id.into_inner()).await?;
+    Ok(resource(&req, entry))
+}
+
+// Log work afterwards, always for the user of the token
+#[post("/TimeEntries")]
+pub async fn create_time_entry(
+    repo: Data<Repository>,
+    principal: Principal,
+    entry: Json<NewTimeEntry>,
+) -> Result<HttpResponse, Error> {
+    entry.validate()?;
+    let entry = repo
+        .add_time_entry(entry.into_inner(), principal.subject)
+        .await?;
+    Ok(created("/TimeEntries", entry.id, entry))
+}
+
+// The user whose entries the request may change (None if the user may change the entries of everyone)
+fn time_owner(authorizer: &Authorizer, principal: Principal) -> Option<String> {
+    if authorizer.allows(&principal, policy::TIME_MANAGE) {
+        return None;
     }
+    Some(principal.subject)
+}
+
+#[put("/TimeEntries/{id}")]
+pub async fn update_time_entry(
+    repo: Data<Repository>,
+    authorizer: Data<Authorizer>,
+    principal: Principal,
+    id: Path<i32>,
+    req: HttpRequest,
+    entry: Json<TimeEntry>,
+) -> Result<HttpResponse, Error> {
+    check_id(id.into_inner(), entry.id)?;
+    entry.validate()?;
+    let entry = repo
+        .update_time_entry(
+            entry.into_inner(),
+            etag::if_match(&req)?,
+            time_owner(&authorizer, principal),
+        )
+        .await?;
+    Ok(updated(entry))
+}
+
+#[patch("/TimeEntries/{id}")]
+pub async fn patch_time_entry(
+    repo: Data<Repository>,
+    authorizer: Data<Authorizer>,
+    principal: Principal,
+    id: Path<i32>,
+    req: HttpRequest,
+    patch: Json<Value>,
+) -> Result<HttpResponse, Error> {
+    let id = id.into_inner();
+    check_patch_id(id, &patch)?;
+    let entry = repo
+        .patch_time_entry(
+            id,
+            patch.into_inner(),
+            etag::if_match(&req)?,
+            time_owner(&authorizer, principal),
+        )
+        .await?;
+    Ok(updated(entry))
+}
+
+#[delete("/TimeEntries/{id}")]
+pub async fn delete_time_entry(
+    repo: Data<Repository>,
+    authorizer: Data<Authorizer>,
+    principal: Principal,
+    id: Path<i32>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    repo.remove_time_entry(
+        id.into_inner(),
+        etag::if_match(&req)?,
+        time_owner(&authorizer, principal),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    q: String,
+    limit: Option<usize>,
+}
+
+// Search clients, projects and materials at once (/Search?q=doe 0171)
+#[get("/Search")]
+pub async fn search(
+    repo: Data<Repository>,
+    authorizer: Data<Authorizer>,
+    principal: Principal,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let params: SearchParams = query_params(&req)?;
+    let mut query = SearchQuery::parse(&params.q, params.limit)?;
+    // Leave out what the user is not allowed to read
+    query.clients = authorizer.allows(&principal, policy::CLIENTS_READ);
+    query.projects = authorizer.allows(&principal, policy::PROJECTS_READ);
+    query.materials = authorizer.allows(&principal, policy::MATERIALS_READ);
+    let results = repo.search(query).await?;
+    Ok(HttpResponse::Ok().json(results))
 }
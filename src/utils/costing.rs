@@ -0,0 +1,197 @@
+/*
+
+    Description: This file contains the costing of the projects. The costs of a project are its project materials
+                 times the costs of their materials plus its logged hours times the labour rate, compared with the
+                 estimated_costs and estimated_hours of the project.
+
+*/
+
+use std::collections::BTreeMap;
+
+use super::config::CostingConfig;
+use super::store::query::Page;
+use crate::models::costing::{
+    BudgetVariance, CostVariance, HoursVariance, LabourCosts, MaterialCosts, ProjectCosting,
+    ProjectVariance,
+};
+use crate::models::error::{self, Error};
+use crate::models::material::Material;
+use crate::models::money::Money;
+use crate::models::project::Project;
+use crate::models::project_material::ProjectMaterial;
+
+#[derive(Debug, Clone)]
+pub struct Costing {
+    labour_rate: Money,
+}
+
+impl Costing {
+    pub fn new(config: &CostingConfig) -> Costing {
+        Costing {
+            labour_rate: config.labour_rate,
+        }
+    }
+
+    // The costs of one project (materials are its project materials with their materials)
+    pub fn project(
+        &self,
+        project: &Project,
+        materials: &[(ProjectMaterial, Material)],
+        logged_minutes: i64,
+    ) -> Result<ProjectCosting, Error> {
+        let mut material_costs = Money::new(0, project.estimated_costs.currency);
+        let mut lines = Vec::new();
+        for (project_material, material) in materials {
+            let costs = material
+                .costs
+                .checked_mul(project_material.amount as i64)
+                .ok_or_else(|| out_of_range(project))?;
+            material_costs = add(project, material_costs, costs)?;
+            lines.push(MaterialCosts {
+                project_material_id: project_material.id,
+                material_id: material.id,
+                name: material.name.clone(),
+                amount: project_material.amount,
+                unit_costs: material.costs,
+                costs,
+            });
+        }
+        let logged_hours = logged_minutes as f64 / 60.0;
+        let labour = LabourCosts {
+            hours: round(logged_hours),
+            rate: self.labour_rate,
+            costs: labour_costs(self.labour_rate, logged_minutes)
+                .ok_or_else(|| out_of_range(project))?,
+        };
+        let total_costs = add(project, material_costs, labour.costs)?;
+        Ok(ProjectCosting {
+            project_id: project.id,
+            name: project.name.clone(),
+            materials: lines,
+            material_costs,
+            total_costs,
+            recorded_costs: project.costs,
+            cost_variance: cost_variance(project.estimated_costs, total_costs)
+                .ok_or_else(|| out_of_range(project))?,
+            hours_variance: hours_variance(project.estimated_hours as f64, logged_hours),
+            labour,
+        })
+    }
+
+    // The variance of every project of the page (logged_minutes maps a project to its minutes)
+    pub fn budget_variance(
+        &self,
+        projects: &Page<Project>,
+        materials: &[(ProjectMaterial, Material)],
+        logged_minutes: &BTreeMap<i32, i64>,
+    ) -> Result<BudgetVariance, Error> {
+        let mut report = BudgetVariance {
+            total: projects.total,
+            ..BudgetVariance::default()
+        };
+        // Estimated and actual costs per currency
+        let mut sums: BTreeMap<String, (Money, Money)> = BTreeMap::new();
+        for project in &projects.items {
+            let project_materials: Vec<(ProjectMaterial, Material)> = materials
+                .iter()
+                .filter(|(project_material, _)| project_material.project_id == project.id)
+                .cloned()
+                .collect();
+            let minutes = logged_minutes.get(&project.id).copied().unwrap_or(0);
+            let costing = match self.project(project, &project_materials, minutes) {
+                Ok(costing) => costing,
+                Err(err) if err.kind == error::CURRENCY_MISMATCH => {
+                    report.mixed_currency.push(project.id);
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            let variance = costing.cost_variance;
+            let zero = Money::new(0, variance.estimated.currency);
+            let (estimated, actual) = sums
+                .entry(variance.estimated.currency.to_string())
+                .or_insert((zero, zero));
+            *estimated = add(project, *estimated, variance.estimated)?;
+            *actual = add(project, *actual, variance.actual)?;
+            report.projects.push(ProjectVariance {
+                project_id: project.id,
+                name: project.name.clone(),
+                cost_variance: variance,
+                hours_variance: costing.hours_variance,
+            });
+        }
+        report.totals = sums
+            .into_values()
+            .filter_map(|(estimated, actual)| cost_variance(estimated, actual))
+            .collect();
+        Ok(report)
+    }
+}
+
+// Add costs to a sum in the currency of the estimate (Nothing is nothing in every currency)
+fn add(project: &Project, sum: Money, costs: Money) -> Result<Money, Error> {
+    if costs.cents == 0 {
+        return Ok(sum);
+    }
+    if costs.currency != sum.currency {
+        return Err(Error::with_kind(
+            format!(
+                "Project {} is estimated in {}, but has costs in {}",
+                project.id, sum.currency, costs.currency
+            ),
+            409,
+            error::CURRENCY_MISMATCH,
+        ));
+    }
+    sum.checked_add(costs).ok_or_else(|| out_of_range(project))
+}
+
+// The rate is per hour, half a cent and more is rounded up (None if it does not fit)
+fn labour_costs(rate: Money, minutes: i64) -> Option<Money> {
+    let cents = rate
+        .cents
+        .checked_mul(minutes)?
+        .checked_add(30)?
+        .div_euclid(60);
+    Some(Money::new(cents, rate.currency))
+}
+
+fn out_of_range(project: &Project) -> Error {
+    Error::new(
+        format!("The costs of project {} are out of range", project.id),
+        500,
+    )
+}
+
+// Both in the same currency (None if the difference does not fit)
+fn cost_variance(estimated: Money, actual: Money) -> Option<CostVariance> {
+    let difference = actual.checked_sub(estimated)?;
+    Some(CostVariance {
+        estimated,
+        actual,
+        difference,
+        percent: percent(difference.cents as f64, estimated.cents as f64),
+    })
+}
+
+fn hours_variance(estimated: f64, logged: f64) -> HoursVariance {
+    HoursVariance {
+        estimated: round(estimated),
+        logged: round(logged),
+        difference: round(logged - estimated),
+        percent: percent(logged - estimated, estimated),
+    }
+}
+
+// The difference in percent of the estimate
+fn percent(difference: f64, estimated: f64) -> Option<f64> {
+    if estimated == 0.0 {
+        return None;
+    }
+    Some(round(difference / estimated * 100.0))
+}
+
+// Two decimal places (The estimated hours are an f32, 1.1 would be 1.100000023841858)
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
@@ -0,0 +1,70 @@
+/*
+
+    Description: This file contains the ETags of the resources and the conditional request headers. The ETag is the
+                 row version of the resource, If-Match protects updates and deletes against overwriting a newer version
+                 and If-None-Match lets a client skip downloading a resource it already has.
+
+*/
+
+use actix_web::{
+    http::header::{IF_MATCH, IF_NONE_MATCH},
+    HttpRequest,
+};
+
+use crate::models::error::Error;
+
+// The ETag of a version (A strong tag, the version changes with every update)
+pub fn etag(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+// The version an update or delete expects (None if there is no If-Match or it is *)
+pub fn if_match(req: &HttpRequest) -> Result<Option<i32>, Error> {
+    let Some(header) = req.headers().get(IF_MATCH) else {
+        return Ok(None);
+    };
+    let header = header
+        .to_str()
+        .map_err(|_| Error::new("Invalid If-Match header".to_string(), 400))?
+        .trim();
+    if header == "*" {
+        return Ok(None);
+    }
+    // If-Match compares strong, so weak tags never match
+    let versions: Vec<i32> = header.split(',').filter_map(version).collect();
+    match versions.as_slice() {
+        [version] => Ok(Some(*version)),
+        [] => Err(Error::new(
+            "If-Match does not contain a version of the resource".to_string(),
+            412,
+        )),
+        _ => Err(Error::new(
+            "If-Match can only contain one version".to_string(),
+            400,
+        )),
+    }
+}
+
+// The client already has this version (If-None-Match compares weak)
+pub fn not_modified(req: &HttpRequest, current: i32) -> bool {
+    let Some(header) = req
+        .headers()
+        .get(IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+    header.trim() == "*"
+        || header
+            .split(',')
+            .any(|tag| version(tag.trim().trim_start_matches("W/")) == Some(current))
+}
+
+// The version of a strong tag
+fn version(tag: &str) -> Option<i32> {
+    tag.trim()
+        .strip_prefix('"')?
+        .strip_suffix('"')?
+        .parse()
+        .ok()
+}
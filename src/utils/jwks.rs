@@ -0,0 +1,175 @@
+/*
+
+    Description: This file contains the local validation of Keycloak access tokens. The signing keys of the realm (JWKS)
+                 are cached and fetched again after the refresh interval or when a token uses an unknown key id
+                 (Key rotation). If Keycloak cannot be reached the old keys keep working.
+
+*/
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::jwk::Jwk;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+
+use super::auth::Principal;
+use crate::models::error::{self, Error};
+
+// Minimum time between two fetches, so unknown key ids and a Keycloak outage do not cause a fetch per request
+// (Never more than the refresh interval)
+const MIN_REFETCH: Duration = Duration::from_secs(10);
+
+// The claims of a Keycloak access token we need (exp, iss and aud are checked by the jsonwebtoken crate)
+#[derive(Deserialize)]
+struct Claims {
+    #[serde(default)]
+    sub: String,
+    #[serde(default)]
+    realm_access: RealmAccess,
+}
+
+#[derive(Deserialize, Default)]
+struct RealmAccess {
+    #[serde(default)]
+    roles: Vec<String>,
+}
+
+#[derive(Default)]
+struct KeyState {
+    keys: HashMap<String, DecodingKey>,
+    // Last successful fetch and last try (Successful or not)
+    fetched_at: Option<Instant>,
+    attempted_at: Option<Instant>,
+}
+
+pub struct Jwks {
+    url: String,
+    issuer: String,
+    audience: String,
+    refresh: Duration,
+    min_refetch: Duration,
+    state: RwLock<KeyState>,
+}
+
+impl Jwks {
+    pub fn new(url: String, issuer: String, audience: String, refresh: Duration) -> Jwks {
+        Jwks {
+            url,
+            issuer,
+            audience,
+            refresh,
+            min_refetch: MIN_REFETCH.min(refresh),
+            state: RwLock::new(KeyState::default()),
+        }
+    }
+
+    // Check the signature, exp, iss and aud of the token and return its user
+    // (401 if the token is invalid, 502 if it cannot be checked because the key is not available)
+    pub async fn validate(&self, token: &str) -> Result<Principal, Error> {
+        let header = decode_header(token).map_err(invalid_token)?;
+        // Only the asymmetric algorithms of Keycloak, a symmetric one would need a shared secret
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(invalid_token("Symmetric algorithms are not accepted"));
+        }
+        let kid = header
+            .kid
+            .ok_or_else(|| invalid_token("The token has no key id"))?;
+        let key = self.key(&kid).await?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[&self.audience]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud"]);
+        // A key that does not fit the algorithm of the token is an invalid token as well
+        let claims = decode::<Claims>(token, &key, &validation)
+            .map_err(invalid_token)?
+            .claims;
+        Ok(Principal {
+            subject: claims.sub,
+            roles: claims.realm_access.roles,
+        })
+    }
+
+    // The key with the id (Fetches the keys again if they are old or the id is unknown)
+    async fn key(&self, kid: &str) -> Result<DecodingKey, Error> {
+        let (key, stale, may_fetch) = {
+            let state = self.state.read().unwrap();
+            (
+                state.keys.get(kid).cloned(),
+                state
+                    .fetched_at
+                    .is_none_or(|fetched| fetched.elapsed() >= self.refresh),
+                state
+                    .attempted_at
+                    .is_none_or(|attempted| attempted.elapsed() >= self.min_refetch),
+            )
+        };
+        if may_fetch && (stale || key.is_none()) {
+            if let Err(err) = self.fetch().await {
+                // Keep serving with the old key while Keycloak is not reachable
+                return key.ok_or(err);
+            }
+            return self
+                .state
+                .read()
+                .unwrap()
+                .keys
+                .get(kid)
+                .cloned()
+                .ok_or_else(|| unknown_key(kid));
+        }
+        key.ok_or_else(|| unknown_key(kid))
+    }
+
+    // Fetch the signing keys of the realm and replace the cached ones
+    async fn fetch(&self) -> Result<(), Error> {
+        self.state.write().unwrap().attempted_at = Some(Instant::now());
+        let response = reqwest::Client::new()
+            .get(&self.url)
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| Error::new(format!("Cannot fetch the signing keys: {}", err), 502))?;
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|err| Error::new(format!("Invalid signing keys: {}", err), 502))?;
+        let keys = parse_keys(&json);
+        let mut state = self.state.write().unwrap();
+        state.keys = keys;
+        state.fetched_at = Some(Instant::now());
+        Ok(())
+    }
+}
+
+// The signing keys of a JWKS document (Encryption keys and key types we do not know are skipped)
+pub fn parse_keys(json: &serde_json::Value) -> HashMap<String, DecodingKey> {
+    let mut keys = HashMap::new();
+    for value in json["keys"].as_array().into_iter().flatten() {
+        if value["use"].as_str() == Some("enc") {
+            continue;
+        }
+        let jwk: Jwk = match serde_json::from_value(value.clone()) {
+            Ok(jwk) => jwk,
+            Err(_) => continue,
+        };
+        if let (Some(kid), Ok(key)) = (jwk.common.key_id.clone(), DecodingKey::from_jwk(&jwk)) {
+            keys.insert(kid, key);
+        }
+    }
+    keys
+}
+
+fn invalid_token(err: impl std::fmt::Display) -> Error {
+    Error::with_kind(format!("Invalid token: {}", err), 401, error::INVALID_TOKEN)
+}
+
+// Not the fault of the token, the keys of Keycloak are not known (yet)
+fn unknown_key(kid: &str) -> Error {
+    Error::new(format!("Unknown signing key {}", kid), 502)
+}
@@ -5,70 +5,119 @@
 
 */
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::Duration;
 
-use crate::models::error::Error;
+use super::auth::{Principal, TokenVerifier, Verification};
+use super::config::{KeycloakConfig, TokenValidation};
+use super::jwks::Jwks;
+use super::token_cache::TokenCache;
+use crate::models::error::{self, Error};
 
-// Constants
-const API_URL: &str = "http://justinrauch.myftp.org:8480";
-const REALM: &str = "WMS";
-const CLIENT_ID: &str = "workshop_client";
-const CLIENT_SECRET: &str = "Ip7GUqM8mRuIHMcq3tOuuHCaejSwSk3S";
-
-// Give keycloak the 'a so that it will hold on the constans for the lifetime of the main struct
-#[derive(Clone)]
-pub struct Keycloak<'a> {
-    last_request: u64,
-    number_of_frequent_requests: u64,
-    // Constants (make public, cannot be changed anyways)
-    pub api_url: &'a str,
-    pub realm: &'a str,
-    pub client_id: &'a str,
-    pub client_secret: &'a str,
+pub struct Keycloak {
+    // Connection settings (Taken from the configuration)
+    pub api_url: String,
+    pub realm: String,
+    pub client_id: String,
+    pub client_secret: String,
+    // Local validation of the tokens (None if every token is introspected)
+    jwks: Option<Jwks>,
+    fallback_to_introspection: bool,
+    // Results of the introspection endpoint (None if disabled)
+    cache: Option<TokenCache>,
 }
 
 // Implement fuctions to the Keycloak struct
-impl<'a> Keycloak<'a> {
-    // Default constructor
-    pub const fn new() -> Keycloak<'a> {
+impl Keycloak {
+    // Create a new keycloak client from the configuration
+    pub fn new(config: &KeycloakConfig) -> Keycloak {
+        let jwks = match config.validation {
+            TokenValidation::Introspection => None,
+            TokenValidation::Jwks => {
+                let realm_url = format!("{}/realms/{}", config.api_url, config.realm);
+                Some(Jwks::new(
+                    format!("{}/protocol/openid-connect/certs", realm_url),
+                    non_empty_or(&config.issuer, &realm_url),
+                    non_empty_or(&config.audience, &config.client_id),
+                    Duration::from_secs(config.jwks_refresh),
+                ))
+            }
+        };
         Keycloak {
-            last_request: 0,
-            number_of_frequent_requests: 0,
-            api_url: API_URL,
-            realm: REALM,
-            client_id: CLIENT_ID,
-            client_secret: CLIENT_SECRET,
+            api_url: config.api_url.clone(),
+            realm: config.realm.clone(),
+            client_id: config.client_id.clone(),
+            client_secret: config.client_secret.clone(),
+            jwks,
+            fallback_to_introspection: config.fallback_to_introspection,
+            cache: TokenCache::new(
+                Duration::from_secs(config.introspection_cache_ttl),
+                config.introspection_cache_size,
+            ),
         }
     }
 
-    // Function to get the groups of a user
-    pub async fn get_groups(&mut self, token: String) -> Result<Vec<String>, Error> {
-        // Check if the last request was less than 3 seconds ago
-        let current_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        if current_time - self.last_request < 3 {
-            self.number_of_frequent_requests += 1;
-            if self.number_of_frequent_requests > 10 {
-                // Too many requests
-                return Err(Error::new("Too many requests".to_string(), 429));
+    // Check the token the configured way
+    pub async fn authenticate(&self, token: &str) -> Result<Principal, Error> {
+        let jwks = match &self.jwks {
+            Some(jwks) => jwks,
+            None => return self.introspect(token).await,
+        };
+        let result = match jwks.validate(token).await {
+            // Only if the token could not be checked, an invalid token stays invalid
+            Err(err) if err.code >= 500 && self.fallback_to_introspection => {
+                self.introspect(token).await
             }
-        } else {
-            // Reset the number of frequent requests
-            self.last_request = current_time;
-            self.number_of_frequent_requests = 0;
+            result => result,
+        };
+        if matches!(&result, Err(err) if err.code == 401) {
+            self.invalidate(token);
         }
+        result
+    }
+
+    // Drop the cached user of a token that Keycloak rejected (E.g. a call with the token was answered with 401)
+    pub fn invalidate(&self, token: &str) {
+        if let Some(cache) = &self.cache {
+            cache.remove(token);
+        }
+    }
+
+    // Function to get the user and the realm roles of a token (Answered from the cache if the token was seen before)
+    pub async fn introspect(&self, token: &str) -> Result<Principal, Error> {
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return Ok(self.introspect_remote(token).await?.0),
+        };
+        if let Some(principal) = cache.get(token) {
+            return Ok(principal);
+        }
+        match self.introspect_remote(token).await {
+            Ok((principal, exp)) => {
+                cache.insert(token, principal.clone(), exp);
+                Ok(principal)
+            }
+            // Inactive (active: false) or unknown, an entry of another request must not answer for it anymore
+            Err(err) => {
+                if err.code == 401 {
+                    cache.remove(token);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    // Ask the introspection endpoint, returns the user and the expiry (exp) of the token
+    async fn introspect_remote(&self, token: &str) -> Result<(Principal, Option<u64>), Error> {
         // Send the request to keycloak
         let response = reqwest::Client::new()
-            .post(&format!(
+            .post(format!(
                 "{}/realms/{}/protocol/openid-connect/token/introspect",
                 &self.api_url, &self.realm
             ))
             .form(&[
-                ("client_id", &self.client_id),
-                ("client_secret", &self.client_secret),
-                ("token", &token.as_ref()),
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+                ("token", token),
             ])
             .timeout(std::time::Duration::from_secs(5))
             .send()
@@ -76,26 +125,64 @@ impl<'a> Keycloak<'a> {
         // Check if the request was successful
         let response = match response {
             Ok(response) => response,
-            Err(_) => return Err(Error::new("Keycloak server error".to_string(), 500)),
+            // Keycloak is not reachable, that is not the fault of the client
+            Err(_) => return Err(Error::new("Keycloak server error".to_string(), 502)),
         };
         // Check if keycloak maybe returned an access error instead of an HTML error
         match response.error_for_status_ref().err() {
-            Some(err) => Err(Error::new(err.to_string(), 500)),
+            Some(err) => Err(Error::new(err.to_string(), 502)),
             None => {
                 // Parse the response
-                let json: serde_json::Value = response.json().await.unwrap();
-                // Check if the token is valid (Contains groups?)
+                let json: serde_json::Value = response
+                    .json()
+                    .await
+                    .map_err(|err| Error::new(err.to_string(), 502))?;
+                // Check if the token is valid (Active and contains groups?)
                 let groups = match json["realm_access"]["roles"].as_array() {
-                    None => return Err(Error::new("Invalid token".to_string(), 401)),
+                    Some(_) if json["active"] == false => {
+                        return Err(Error::with_kind(
+                            "Invalid token".to_string(),
+                            401,
+                            error::INVALID_TOKEN,
+                        ))
+                    }
+                    None => {
+                        return Err(Error::with_kind(
+                            "Invalid token".to_string(),
+                            401,
+                            error::INVALID_TOKEN,
+                        ))
+                    }
                     Some(groups) => groups,
                 };
-                // Return the groups
-                let mut result = Vec::new();
+                // Return the user and the groups
+                let mut roles = Vec::new();
                 for group in groups {
-                    result.push(group.as_str().unwrap().to_string());
+                    if let Some(group) = group.as_str() {
+                        roles.push(group.to_string());
+                    }
                 }
-                Ok(result)
+                let principal = Principal {
+                    subject: json["sub"].as_str().unwrap_or_default().to_string(),
+                    roles,
+                };
+                Ok((principal, json["exp"].as_u64()))
             }
         }
     }
 }
+
+fn non_empty_or(value: &str, default: &str) -> String {
+    if value.is_empty() {
+        default.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+// Keycloak checks the tokens locally or with its introspection endpoint
+impl TokenVerifier for Keycloak {
+    fn verify<'a>(&'a self, token: &'a str) -> Verification<'a> {
+        Box::pin(self.authenticate(token))
+    }
+}
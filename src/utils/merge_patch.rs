@@ -0,0 +1,50 @@
+/*
+
+    Description: This file contains JSON Merge Patch (RFC 7386) for the PATCH endpoints. The fields of the patch replace
+                 the fields of the resource, null removes a field and nested objects are merged the same way.
+
+*/
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::models::error::Error;
+
+// Merge the patch into the target
+pub fn apply(target: &mut Value, patch: &Value) {
+    let Value::Object(fields) = patch else {
+        // Anything but an object replaces the whole target
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+    let Value::Object(target_fields) = target else {
+        return;
+    };
+    for (key, value) in fields {
+        if value.is_null() {
+            target_fields.remove(key);
+        } else {
+            apply(
+                target_fields.entry(key.as_str()).or_insert(Value::Null),
+                value,
+            );
+        }
+    }
+}
+
+// The value with the patch applied (422 if the result is not a valid value anymore, e.g. a removed field)
+pub fn patch<T: Serialize + DeserializeOwned>(value: &T, patch: &Value) -> Result<T, Error> {
+    let mut target = serde_json::to_value(value)
+        .map_err(|err| Error::new(format!("Could not serialize the value: {}", err), 500))?;
+    // A misspelled field would be dropped silently by the model
+    if let (Value::Object(fields), Value::Object(known)) = (patch, &target) {
+        if let Some(field) = fields.keys().find(|field| !known.contains_key(*field)) {
+            return Err(Error::new(format!("Unknown field {}", field), 422));
+        }
+    }
+    apply(&mut target, patch);
+    serde_json::from_value(target).map_err(|err| Error::new(format!("Invalid patch: {}", err), 422))
+}
@@ -1,3 +1,17 @@
+pub mod alerts;
+pub mod auth;
+pub mod body;
+pub mod config;
 pub mod controller;
+pub mod costing;
+pub mod etag;
+pub mod jwks;
 pub mod keycloak;
+pub mod merge_patch;
+pub mod policy;
+pub mod rate_limit;
 pub mod repository;
+pub mod request_id;
+pub mod store;
+pub mod token_cache;
+pub mod validation;
@@ -0,0 +1,236 @@
+/*
+
+    Description: This file contains the permission tables of the server. Every route needs exactly one permission,
+                 the Keycloak realm roles of the caller decide which permissions they have. Routes that are missing
+                 in the table are denied.
+
+*/
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::models::error::Error;
+
+// All permissions
+pub const PROJECTS_READ: &str = "projects:read";
+pub const PROJECTS_WRITE: &str = "projects:write";
+pub const CLIENTS_READ: &str = "clients:read";
+pub const CLIENTS_WRITE: &str = "clients:write";
+pub const MATERIALS_READ: &str = "materials:read";
+pub const MATERIALS_WRITE: &str = "materials:write";
+// Change the stock of a material without changing the material itself
+pub const INVENTORY_ADJUST: &str = "inventory:adjust";
+// Suppliers and purchase orders (Receiving a delivery changes the stock, so it needs inventory:adjust)
+pub const PURCHASING_READ: &str = "purchasing:read";
+pub const PURCHASING_WRITE: &str = "purchasing:write";
+// The costing of the projects (What the workshop pays for materials and labour)
+pub const REPORTS_READ: &str = "reports:read";
+// Time entries, everyone logs their own time (time:manage changes the entries of others)
+pub const TIME_READ: &str = "time:read";
+pub const TIME_WRITE: &str = "time:write";
+pub const TIME_MANAGE: &str = "time:manage";
+
+pub const PERMISSIONS: &[&str] = &[
+    PROJECTS_READ,
+    PROJECTS_WRITE,
+    CLIENTS_READ,
+    CLIENTS_WRITE,
+    MATERIALS_READ,
+    MATERIALS_WRITE,
+    INVENTORY_ADJUST,
+    PURCHASING_READ,
+    PURCHASING_WRITE,
+    REPORTS_READ,
+    TIME_READ,
+    TIME_WRITE,
+    TIME_MANAGE,
+];
+
+// The permissions of every Keycloak realm role (Used if the configuration has no [roles] section)
+pub const DEFAULT_ROLES: &[(&str, &[&str])] = &[
+    ("admin", PERMISSIONS),
+    (
+        "manager",
+        &[
+            PROJECTS_READ,
+            PROJECTS_WRITE,
+            CLIENTS_READ,
+            CLIENTS_WRITE,
+            MATERIALS_READ,
+            PURCHASING_READ,
+            PURCHASING_WRITE,
+            REPORTS_READ,
+            TIME_READ,
+            TIME_WRITE,
+            TIME_MANAGE,
+        ],
+    ),
+    (
+        "warehouse",
+        &[
+            PROJECTS_READ,
+            MATERIALS_READ,
+            MATERIALS_WRITE,
+            INVENTORY_ADJUST,
+            PURCHASING_READ,
+            PURCHASING_WRITE,
+            TIME_READ,
+            TIME_WRITE,
+        ],
+    ),
+    (
+        "worker",
+        &[
+            PROJECTS_READ,
+            MATERIALS_READ,
+            INVENTORY_ADJUST,
+            PURCHASING_READ,
+            TIME_READ,
+            TIME_WRITE,
+        ],
+    ),
+    (
+        "viewer",
+        &[
+            PROJECTS_READ,
+            CLIENTS_READ,
+            MATERIALS_READ,
+            PURCHASING_READ,
+            REPORTS_READ,
+            TIME_READ,
+        ],
+    ),
+];
+
+// The permission every route needs (Method and the pattern the route was registered with)
+pub const ROUTES: &[(&str, &str, &str)] = &[
+    ("GET", "/Projects", PROJECTS_READ),
+    ("GET", "/Projects/{id}", PROJECTS_READ),
+    ("POST", "/Projects", PROJECTS_WRITE),
+    ("PUT", "/Projects/{id}", PROJECTS_WRITE),
+    ("PATCH", "/Projects/{id}", PROJECTS_WRITE),
+    ("DELETE", "/Projects/{id}", PROJECTS_WRITE),
+    ("GET", "/Projects/{id}/Transitions", PROJECTS_READ),
+    ("POST", "/Projects/{id}/Transitions", PROJECTS_WRITE),
+    ("GET", "/Projects/{id}/Costing", REPORTS_READ),
+    ("GET", "/Reports/BudgetVariance", REPORTS_READ),
+    ("GET", "/ProjectMaterials", PROJECTS_READ),
+    ("GET", "/ProjectMaterials/{id}", PROJECTS_READ),
+    ("POST", "/ProjectMaterials", PROJECTS_WRITE),
+    ("PUT", "/ProjectMaterials/{id}", PROJECTS_WRITE),
+    ("PATCH", "/ProjectMaterials/{id}", PROJECTS_WRITE),
+    ("DELETE", "/ProjectMaterials/{id}", PROJECTS_WRITE),
+    ("GET", "/Clients", CLIENTS_READ),
+    ("GET", "/Clients/{id}", CLIENTS_READ),
+    ("POST", "/Clients", CLIENTS_WRITE),
+    ("PUT", "/Clients/{id}", CLIENTS_WRITE),
+    ("PATCH", "/Clients/{id}", CLIENTS_WRITE),
+    ("DELETE", "/Clients/{id}", CLIENTS_WRITE),
+    ("GET", "/Materials", MATERIALS_READ),
+    ("GET", "/Materials/LowStock", MATERIALS_READ),
+    ("GET", "/Materials/{id}", MATERIALS_READ),
+    ("POST", "/Materials", MATERIALS_WRITE),
+    ("PUT", "/Materials/{id}", MATERIALS_WRITE),
+    ("PATCH", "/Materials/{id}", MATERIALS_WRITE),
+    ("DELETE", "/Materials/{id}", MATERIALS_WRITE),
+    ("GET", "/Materials/{id}/Movements", MATERIALS_READ),
+    ("POST", "/Materials/{id}/Movements", INVENTORY_ADJUST),
+    ("GET", "/MaterialTypes", MATERIALS_READ),
+    ("GET", "/MaterialTypes/{id}", MATERIALS_READ),
+    ("POST", "/MaterialTypes", MATERIALS_WRITE),
+    ("PUT", "/MaterialTypes/{id}", MATERIALS_WRITE),
+    ("PATCH", "/MaterialTypes/{id}", MATERIALS_WRITE),
+    ("DELETE", "/MaterialTypes/{id}", MATERIALS_WRITE),
+    ("GET", "/Suppliers", PURCHASING_READ),
+    ("GET", "/Suppliers/{id}", PURCHASING_READ),
+    ("POST", "/Suppliers", PURCHASING_WRITE),
+    ("PUT", "/Suppliers/{id}", PURCHASING_WRITE),
+    ("PATCH", "/Suppliers/{id}", PURCHASING_WRITE),
+    ("DELETE", "/Suppliers/{id}", PURCHASING_WRITE),
+    ("GET", "/PurchaseOrders", PURCHASING_READ),
+    ("POST", "/PurchaseOrders/SuggestReorder", PURCHASING_WRITE),
+    ("GET", "/PurchaseOrders/{id}", PURCHASING_READ),
+    ("POST", "/PurchaseOrders", PURCHASING_WRITE),
+    ("PUT", "/PurchaseOrders/{id}", PURCHASING_WRITE),
+    ("PATCH", "/PurchaseOrders/{id}", PURCHASING_WRITE),
+    ("DELETE", "/PurchaseOrders/{id}", PURCHASING_WRITE),
+    ("GET", "/PurchaseOrderLines", PURCHASING_READ),
+    ("GET", "/PurchaseOrderLines/{id}", PURCHASING_READ),
+    ("POST", "/PurchaseOrderLines", PURCHASING_WRITE),
+    ("PUT", "/PurchaseOrderLines/{id}", PURCHASING_WRITE),
+    ("PATCH", "/PurchaseOrderLines/{id}", PURCHASING_WRITE),
+    ("DELETE", "/PurchaseOrderLines/{id}", PURCHASING_WRITE),
+    ("POST", "/PurchaseOrderLines/{id}/Receive", INVENTORY_ADJUST),
+    ("GET", "/TimeEntries", TIME_READ),
+    ("GET", "/TimeEntries/Summary/Projects", TIME_READ),
+    ("GET", "/TimeEntries/Summary/Employees", TIME_READ),
+    ("POST", "/TimeEntries/Start", TIME_WRITE),
+    ("POST", "/TimeEntries/Stop", TIME_WRITE),
+    ("GET", "/TimeEntries/{id}", TIME_READ),
+    ("POST", "/TimeEntries", TIME_WRITE),
+    // Only the own entries, the entries of others need time:manage as well
+    ("PUT", "/TimeEntries/{id}", TIME_WRITE),
+    ("PATCH", "/TimeEntries/{id}", TIME_WRITE),
+    ("DELETE", "/TimeEntries/{id}", TIME_WRITE),
+    // Only searches the groups the user can read (Clients need clients:read and so on)
+    ("GET", "/Search", PROJECTS_READ),
+];
+
+// The permission a route needs (None if the route is not in the table)
+pub fn required_permission(method: &str, pattern: &str) -> Option<&'static str> {
+    ROUTES
+        .iter()
+        .find(|(m, p, _)| *m == method && *p == pattern)
+        .map(|(_, _, permission)| *permission)
+}
+
+// Make sure the roles of the configuration only use known permissions
+pub fn validate_roles(roles: &BTreeMap<String, Vec<String>>) -> Result<(), Error> {
+    for (role, permissions) in roles {
+        for permission in permissions {
+            if !PERMISSIONS.contains(&permission.as_str()) {
+                return Err(Error::new(
+                    format!("roles.{} has the unknown permission {}", role, permission),
+                    500,
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+// The role table the server runs with
+pub struct Policy {
+    roles: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Policy {
+    // Use the roles of the configuration or the default roles if none are configured
+    pub fn new(roles: &BTreeMap<String, Vec<String>>) -> Policy {
+        let roles = if roles.is_empty() {
+            DEFAULT_ROLES
+                .iter()
+                .map(|(role, permissions)| {
+                    (
+                        role.to_string(),
+                        permissions.iter().map(|p| p.to_string()).collect(),
+                    )
+                })
+                .collect()
+        } else {
+            roles
+                .iter()
+                .map(|(role, permissions)| (role.clone(), permissions.iter().cloned().collect()))
+                .collect()
+        };
+        Policy { roles }
+    }
+
+    // Check if any of the roles has the permission (Unknown roles have no permissions)
+    pub fn allows(&self, roles: &[String], permission: &str) -> bool {
+        roles.iter().any(|role| {
+            self.roles
+                .get(role)
+                .is_some_and(|permissions| permissions.contains(permission))
+        })
+    }
+}
@@ -0,0 +1,203 @@
+/*
+
+    Description: This file contains the rate limiting middleware. Every user (Or client IP if the request has no user)
+                 gets a token bucket per route group, every request takes one token and the buckets refill over time.
+                 The limits are sent back in the X-RateLimit-* headers, a rejected request gets a Retry-After header.
+
+*/
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{Header, HeaderMap, HeaderName, HeaderValue, RETRY_AFTER},
+    middleware::Next,
+    web::Data,
+    ResponseError,
+};
+use actix_web_httpauth::headers::authorization::{Authorization, Bearer};
+use jsonwebtoken::{decode, DecodingKey, Validation};
+use serde::Deserialize;
+
+use super::config::{RateLimitConfig, RateLimitGroup};
+use crate::models::error::Error;
+
+// Remove the buckets that are full again after this many new buckets (Keeps the map from growing forever)
+const CLEANUP_INTERVAL: usize = 1000;
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+// The result of taking a token
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    // Seconds until the next token (If rejected) and until the bucket is full again
+    pub retry_after: u64,
+    pub reset: u64,
+}
+
+pub struct RateLimiter {
+    enabled: bool,
+    // The configured groups and the default group last
+    groups: Vec<RateLimitGroup>,
+    buckets: Mutex<HashMap<(usize, String), Bucket>>,
+    created: Mutex<usize>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> RateLimiter {
+        let mut groups = config.groups.clone();
+        groups.push(RateLimitGroup {
+            name: "default".to_string(),
+            prefix: String::new(),
+            methods: Vec::new(),
+            capacity: config.capacity,
+            refill_per_second: config.refill_per_second,
+        });
+        RateLimiter {
+            enabled: config.enabled,
+            groups,
+            buckets: Mutex::new(HashMap::new()),
+            created: Mutex::new(0),
+        }
+    }
+
+    // The first group that matches the route (The default group matches everything)
+    fn group(&self, method: &str, path: &str) -> usize {
+        self.groups
+            .iter()
+            .position(|group| {
+                in_prefix(path, &group.prefix)
+                    && (group.methods.is_empty() || group.methods.iter().any(|m| m == method))
+            })
+            .unwrap_or(self.groups.len() - 1)
+    }
+
+    // Take a token from the bucket of the key in the group of the route
+    pub fn check(&self, method: &str, path: &str, key: &str) -> Decision {
+        let index = self.group(method, path);
+        let group = &self.groups[index];
+        let capacity = group.capacity as f64;
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if !buckets.contains_key(&(index, key.to_string())) {
+            self.cleanup(&mut buckets, now);
+        }
+        let bucket = buckets.entry((index, key.to_string())).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        // Refill for the time since the last request
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * group.refill_per_second).min(capacity);
+        bucket.updated = now;
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        Decision {
+            allowed,
+            limit: group.capacity,
+            remaining: bucket.tokens.floor() as u32,
+            retry_after: ((1.0 - bucket.tokens).max(0.0) / group.refill_per_second).ceil() as u64,
+            reset: ((capacity - bucket.tokens) / group.refill_per_second).ceil() as u64,
+        }
+    }
+
+    // Drop the buckets that would be full by now, they behave exactly like a new bucket
+    fn cleanup(&self, buckets: &mut HashMap<(usize, String), Bucket>, now: Instant) {
+        let mut created = self.created.lock().unwrap();
+        *created += 1;
+        if *created < CLEANUP_INTERVAL {
+            return;
+        }
+        *created = 0;
+        buckets.retain(|(index, _), bucket| {
+            let group = &self.groups[*index];
+            let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+            bucket.tokens + elapsed * group.refill_per_second < group.capacity as f64
+        });
+    }
+}
+
+// Whole path segments only, /Materials does not match /MaterialTypes
+fn in_prefix(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    prefix.is_empty()
+        || path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+// The user of the token or the address of the client (Runs before the auth middleware, which checks the token)
+fn key(req: &ServiceRequest) -> String {
+    if let Some(subject) = unverified_subject(req) {
+        return format!("user:{}", subject);
+    }
+    match req.peer_addr() {
+        Some(addr) => format!("ip:{}", addr.ip()),
+        None => "ip:unknown".to_string(),
+    }
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    sub: String,
+}
+
+// The sub claim of the bearer token without checking the signature (Only picks the bucket, nothing is trusted)
+fn unverified_subject(req: &ServiceRequest) -> Option<String> {
+    let auth = Authorization::<Bearer>::parse(req).ok()?;
+    let mut validation = Validation::default();
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+    validation.validate_aud = false;
+    validation.required_spec_claims.clear();
+    let claims = decode::<Claims>(
+        auth.as_ref().token(),
+        &DecodingKey::from_secret(&[]),
+        &validation,
+    )
+    .ok()?;
+    Some(claims.claims.sub).filter(|sub| !sub.is_empty())
+}
+
+fn insert_headers(headers: &mut HeaderMap, decision: &Decision) {
+    for (name, value) in [
+        ("x-ratelimit-limit", decision.limit as u64),
+        ("x-ratelimit-remaining", decision.remaining as u64),
+        ("x-ratelimit-reset", decision.reset),
+    ] {
+        headers.insert(HeaderName::from_static(name), HeaderValue::from(value));
+    }
+    if !decision.allowed {
+        headers.insert(RETRY_AFTER, HeaderValue::from(decision.retry_after));
+    }
+}
+
+pub async fn middleware<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, actix_web::Error> {
+    // Without a (enabled) limiter every request passes
+    let limiter = match req.app_data::<Data<RateLimiter>>() {
+        Some(limiter) if limiter.enabled => limiter.clone(),
+        _ => return Ok(next.call(req).await?.map_into_left_body()),
+    };
+    let decision = limiter.check(req.method().as_str(), req.path(), &key(&req));
+    if !decision.allowed {
+        let mut res = Error::new("Too many requests".to_string(), 429).error_response();
+        insert_headers(res.headers_mut(), &decision);
+        return Ok(req.into_response(res).map_into_right_body());
+    }
+    let mut res = next.call(req).await?;
+    insert_headers(res.headers_mut(), &decision);
+    Ok(res.map_into_left_body())
+}
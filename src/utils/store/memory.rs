@@ -0,0 +1,1003 @@
+/*
+
+    Description: This file contains the in-memory backend of the WorkshopStore. Nothing is persisted, it is used for
+                 fast tests and demos.
+
+*/
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+
+use super::migrations::{Direction, Migration};
+use super::query::{ListQuery, Page};
+use super::search::SearchQuery;
+use super::version::{self, Versioned};
+use super::{
+    check_draft, check_receipt, check_status_change, check_time_entry_change, check_transition,
+    insufficient_stock, no_timer, received_status, reorder_lines, stock_changes, timer_running,
+    WorkshopStore,
+};
+use crate::models::client::{Client, NewClient};
+use crate::models::error::{self, Error};
+use crate::models::material::{Material, NewMaterial};
+use crate::models::material_type::{MaterialType, NewMaterialType};
+use crate::models::project::{NewProject, Project};
+use crate::models::project_material::{NewProjectMaterial, ProjectMaterial};
+use crate::models::project_transition::{ProjectTransition, Transition};
+use crate::models::purchase_order::{
+    DraftedOrder, NewPurchaseOrder, PurchaseOrder, PurchaseOrderStatus, Reorder,
+};
+use crate::models::purchase_order_line::{NewPurchaseOrderLine, PurchaseOrderLine};
+use crate::models::search::SearchResults;
+use crate::models::stock_movement::{Booking, NewStockMovement, StockMovement};
+use crate::models::supplier::{NewSupplier, Supplier};
+use crate::models::time_entry::{NewTimeEntry, TimeEntry, TimeGroup, TimeSummary, Timer};
+use crate::models::timestamp;
+
+// All tables of the store, ordered by id like the SQL backends return them
+#[derive(Default)]
+struct Tables {
+    projects: BTreeMap<i32, Project>,
+    clients: BTreeMap<i32, Client>,
+    materials: BTreeMap<i32, Material>,
+    material_types: BTreeMap<i32, MaterialType>,
+    project_materials: BTreeMap<i32, ProjectMaterial>,
+    project_transitions: BTreeMap<i32, ProjectTransition>,
+    stock_movements: BTreeMap<i32, StockMovement>,
+    suppliers: BTreeMap<i32, Supplier>,
+    purchase_orders: BTreeMap<i32, PurchaseOrder>,
+    purchase_order_lines: BTreeMap<i32, PurchaseOrderLine>,
+    time_entries: BTreeMap<i32, TimeEntry>,
+    // The last id of every table, ids are never used twice (Like AUTOINCREMENT)
+    last_ids: BTreeMap<&'static str, i32>,
+}
+
+impl Tables {
+    fn next_id(&mut self, table: &'static str) -> i32 {
+        let id = self.last_ids.entry(table).or_insert(0);
+        *id += 1;
+        *id
+    }
+
+    // Change the stock of the material and book the movement (Nothing is changed if the stock is not enough)
+    fn book(
+        &mut self,
+        material_id: i32,
+        movement: NewStockMovement,
+        booking: &Booking,
+    ) -> Result<StockMovement, Error> {
+        let change = movement.change();
+        let material = self
+            .materials
+            .get_mut(&material_id)
+            .ok_or_else(|| Error::new("Material not found".to_string(), 404))?;
+        let amount = material
+            .amount
+            .checked_add(change)
+            .filter(|amount| *amount >= 0 || change >= 0 || booking.backorder)
+            .ok_or_else(|| insufficient_stock(material.amount, change))?;
+        material.amount = amount;
+        material.version += 1;
+        let id = self.next_id("stock_movement");
+        let movement = movement.booked(id, material_id, booking, amount);
+        insert(&mut self.stock_movements, id, movement)
+    }
+
+    // Add a purchase order as a draft
+    fn add_purchase_order(
+        &mut self,
+        purchase_order: NewPurchaseOrder,
+    ) -> Result<PurchaseOrder, Error> {
+        check_reference(&self.suppliers, purchase_order.supplier_id, "supplier_id")?;
+        let id = self.next_id("purchase_order");
+        let purchase_order = purchase_order.with_id(id, timestamp::now());
+        insert(&mut self.purchase_orders, id, purchase_order)
+    }
+
+    fn add_purchase_order_line(
+        &mut self,
+        line: NewPurchaseOrderLine,
+    ) -> Result<PurchaseOrderLine, Error> {
+        check_reference(&self.materials, line.material_id, "material_id")?;
+        let id = self.next_id("purchase_order_line");
+        insert(&mut self.purchase_order_lines, id, line.with_id(id))
+    }
+
+    // The purchase order a line points to (Like a foreign key, it has to exist)
+    fn purchase_order_of(&self, purchase_order_id: i32) -> Result<PurchaseOrder, Error> {
+        check_reference(
+            &self.purchase_orders,
+            purchase_order_id,
+            "purchase_order_id",
+        )?;
+        Ok(self.purchase_orders[&purchase_order_id].clone())
+    }
+
+    fn add_time_entry(&mut self, entry: TimeEntry) -> Result<TimeEntry, Error> {
+        check_reference(&self.projects, entry.project_id, "project_id")?;
+        let id = self.next_id("time_entry");
+        insert(&mut self.time_entries, id, TimeEntry { id, ..entry })
+    }
+
+    fn running_timer(&self, user_id: &str) -> Option<TimeEntry> {
+        self.time_entries
+            .values()
+            .find(|entry| entry.user_id == user_id && entry.is_running())
+            .cloned()
+    }
+
+    fn lines_of(&self, purchase_order_id: i32) -> Vec<PurchaseOrderLine> {
+        self.purchase_order_lines
+            .values()
+            .filter(|line| line.purchase_order_id == purchase_order_id)
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryStore {
+    tables: Mutex<Tables>,
+}
+
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+
+    fn tables(&self) -> MutexGuard<'_, Tables> {
+        self.tables.lock().unwrap()
+    }
+}
+
+// Helper functions that work on every table (They fail like the SQL backends do)
+fn get<T: Clone>(table: &BTreeMap<i32, T>, id: i32, not_found: &str) -> Result<T, Error> {
+    match table.get(&id) {
+        Some(value) => Ok(value.clone()),
+        None => Err(Error::new(not_found.to_string(), 404)),
+    }
+}
+
+fn insert<T: Clone>(table: &mut BTreeMap<i32, T>, id: i32, value: T) -> Result<T, Error> {
+    if table.contains_key(&id) {
+        return Err(Error::with_kind(
+            format!("Duplicate entry '{}' for key 'PRIMARY'", id),
+            409,
+            error::DUPLICATE_KEY,
+        ));
+    }
+    table.insert(id, value.clone());
+    Ok(value)
+}
+
+fn update<T: Clone + Versioned>(
+    table: &mut BTreeMap<i32, T>,
+    id: i32,
+    mut value: T,
+    not_found: &str,
+) -> Result<T, Error> {
+    match table.get_mut(&id) {
+        Some(entry) => {
+            // The version of the value is ignored, every update counts the stored one up
+            value.set_version(entry.version() + 1);
+            *entry = value.clone();
+            Ok(value)
+        }
+        None => Err(Error::new(not_found.to_string(), 404)),
+    }
+}
+
+fn remove<T>(table: &mut BTreeMap<i32, T>, id: i32, not_found: &str) -> Result<(), Error> {
+    match table.remove(&id) {
+        Some(_) => Ok(()),
+        None => Err(Error::new(not_found.to_string(), 404)),
+    }
+}
+
+// The row exists and has the expected version (Checked first, like the WHERE of the SQL backends)
+fn check_version<T: Versioned>(
+    table: &BTreeMap<i32, T>,
+    id: i32,
+    expected: Option<i32>,
+    not_found: &str,
+) -> Result<(), Error> {
+    match table.get(&id) {
+        Some(row) => version::check(row.version(), expected),
+        None => Err(Error::new(not_found.to_string(), 404)),
+    }
+}
+
+// Like a foreign key, the referenced row has to exist
+fn check_reference<T>(table: &BTreeMap<i32, T>, id: i32, column: &str) -> Result<(), Error> {
+    if !table.contains_key(&id) {
+        return Err(Error::with_kind(
+            format!("{} {} does not exist", column, id),
+            422,
+            error::FOREIGN_KEY_VIOLATION,
+        ));
+    }
+    Ok(())
+}
+
+// A row that is still referenced by another one cannot be removed
+fn check_unreferenced<T>(
+    table: &BTreeMap<i32, T>,
+    references: impl Fn(&T) -> bool,
+    message: &str,
+) -> Result<(), Error> {
+    if table.values().any(references) {
+        return Err(Error::with_kind(
+            message.to_string(),
+            409,
+            error::REFERENCED_BY_OTHERS,
+        ));
+    }
+    Ok(())
+}
+
+impl WorkshopStore for MemoryStore {
+    fn get_all_projects(&self, query: &ListQuery) -> Result<Page<Project>, Error> {
+        Ok(query.apply(self.tables().projects.values()))
+    }
+
+    fn get_project(&self, id: i32) -> Result<Project, Error> {
+        get(&self.tables().projects, id, "Project not found")
+    }
+
+    fn add_project(&self, project: NewProject) -> Result<Project, Error> {
+        let mut tables = self.tables();
+        check_reference(&tables.clients, project.client_id, "client_id")?;
+        let id = tables.next_id("project");
+        insert(&mut tables.projects, id, project.with_id(id))
+    }
+
+    fn update_project(&self, project: Project, expected: Option<i32>) -> Result<Project, Error> {
+        let mut tables = self.tables();
+        check_version(&tables.projects, project.id, expected, "Project not found")?;
+        check_reference(&tables.clients, project.client_id, "client_id")?;
+        // The status is only changed by a transition
+        let status = tables.projects[&project.id].status;
+        update(
+            &mut tables.projects,
+            project.id,
+            Project { status, ..project },
+            "Project not found",
+        )
+    }
+
+    fn remove_project(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        let mut tables = self.tables();
+        check_version(&tables.projects, id, expected, "Project not found")?;
+        check_unreferenced(
+            &tables.project_materials,
+            |row| row.project_id == id,
+            "Project is still used by project materials",
+        )?;
+        check_unreferenced(
+            &tables.time_entries,
+            |row| row.project_id == id,
+            "Project is still used by time entries",
+        )?;
+        check_unreferenced(
+            &tables.project_transitions,
+            |row| row.project_id == id,
+            "Project has transitions",
+        )?;
+        remove(&mut tables.projects, id, "Project not found")
+    }
+
+    fn transition_project(
+        &self,
+        id: i32,
+        transition: Transition,
+        user_id: &str,
+        at: DateTime<Utc>,
+        expected: Option<i32>,
+    ) -> Result<Project, Error> {
+        let mut tables = self.tables();
+        check_version(&tables.projects, id, expected, "Project not found")?;
+        let project = tables.projects[&id].clone();
+        let mut backordered: Vec<i32> = tables
+            .project_materials
+            .values()
+            .filter(|row| row.project_id == id)
+            .filter(|row| {
+                tables
+                    .materials
+                    .get(&row.material_id)
+                    .is_some_and(|material| material.amount < 0)
+            })
+            .map(|row| row.material_id)
+            .collect();
+        backordered.sort_unstable();
+        backordered.dedup();
+        check_transition(&project, transition.status, &backordered)?;
+        let status = transition.status;
+        let transition_id = tables.next_id("project_transition");
+        let row = transition.recorded(transition_id, &project, user_id, at);
+        insert(&mut tables.project_transitions, transition_id, row)?;
+        update(
+            &mut tables.projects,
+            id,
+            Project { status, ..project },
+            "Project not found",
+        )
+    }
+
+    fn get_project_transitions(&self, query: &ListQuery) -> Result<Page<ProjectTransition>, Error> {
+        Ok(query.apply(self.tables().project_transitions.values()))
+    }
+
+    fn get_all_clients(&self, query: &ListQuery) -> Result<Page<Client>, Error> {
+        Ok(query.apply(self.tables().clients.values()))
+    }
+
+    fn get_client(&self, id: i32) -> Result<Client, Error> {
+        get(&self.tables().clients, id, "Client not found")
+    }
+
+    fn add_client(&self, client: NewClient) -> Result<Client, Error> {
+        let mut tables = self.tables();
+        let id = tables.next_id("client");
+        insert(&mut tables.clients, id, client.with_id(id))
+    }
+
+    fn update_client(&self, client: Client, expected: Option<i32>) -> Result<Client, Error> {
+        let mut tables = self.tables();
+        check_version(&tables.clients, client.id, expected, "Client not found")?;
+        update(&mut tables.clients, client.id, client, "Client not found")
+    }
+
+    fn remove_client(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        let mut tables = self.tables();
+        check_version(&tables.clients, id, expected, "Client not found")?;
+        check_unreferenced(
+            &tables.projects,
+            |row| row.client_id == id,
+            "Client is still used by projects",
+        )?;
+        remove(&mut tables.clients, id, "Client not found")
+    }
+
+    fn get_all_materials(&self, query: &ListQuery) -> Result<Page<Material>, Error> {
+        Ok(query.apply(self.tables().materials.values()))
+    }
+
+    fn get_material(&self, id: i32) -> Result<Material, Error> {
+        get(&self.tables().materials, id, "Material not found")
+    }
+
+    fn get_low_stock_materials(&self, query: &ListQuery) -> Result<Page<Material>, Error> {
+        Ok(query.apply(
+            self.tables()
+                .materials
+                .values()
+                .filter(|material| material.is_low_on_stock()),
+        ))
+    }
+
+    fn add_material(&self, material: NewMaterial) -> Result<Material, Error> {
+        let mut tables = self.tables();
+        check_reference(&tables.material_types, material.type_id, "type_id")?;
+        if let Some(supplier_id) = material.preferred_supplier_id {
+            check_reference(&tables.suppliers, supplier_id, "preferred_supplier_id")?;
+        }
+        let id = tables.next_id("material");
+        insert(&mut tables.materials, id, material.with_id(id))
+    }
+
+    fn update_material(
+        &self,
+        mut material: Material,
+        expected: Option<i32>,
+    ) -> Result<Material, Error> {
+        let mut tables = self.tables();
+        check_version(
+            &tables.materials,
+            material.id,
+            expected,
+            "Material not found",
+        )?;
+        check_reference(&tables.material_types, material.type_id, "type_id")?;
+        if let Some(supplier_id) = material.preferred_supplier_id {
+            check_reference(&tables.suppliers, supplier_id, "preferred_supplier_id")?;
+        }
+        // The amount is only changed by stock movements
+        material.amount = tables.materials[&material.id].amount;
+        update(
+            &mut tables.materials,
+            material.id,
+            material,
+            "Material not found",
+        )
+    }
+
+    fn remove_material(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        let mut tables = self.tables();
+        check_version(&tables.materials, id, expected, "Material not found")?;
+        check_unreferenced(
+            &tables.project_materials,
+            |row| row.material_id == id,
+            "Material is still used by project materials",
+        )?;
+        check_unreferenced(
+            &tables.stock_movements,
+            |row| row.material_id == id,
+            "Material has stock movements",
+        )?;
+        check_unreferenced(
+            &tables.purchase_order_lines,
+            |row| row.material_id == id,
+            "Material is still used by purchase order lines",
+        )?;
+        remove(&mut tables.materials, id, "Material not found")
+    }
+
+    fn get_all_material_types(&self, query: &ListQuery) -> Result<Page<MaterialType>, Error> {
+        Ok(query.apply(self.tables().material_types.values()))
+    }
+
+    fn get_material_type(&self, id: i32) -> Result<MaterialType, Error> {
+        get(&self.tables().material_types, id, "Material type not found")
+    }
+
+    fn add_material_type(&self, material_type: NewMaterialType) -> Result<MaterialType, Error> {
+        let mut tables = self.tables();
+        let id = tables.next_id("material_type");
+        insert(&mut tables.material_types, id, material_type.with_id(id))
+    }
+
+    fn update_material_type(
+        &self,
+        material_type: MaterialType,
+        expected: Option<i32>,
+    ) -> Result<MaterialType, Error> {
+        let mut tables = self.tables();
+        check_version(
+            &tables.material_types,
+            material_type.id,
+            expected,
+            "Material type not found",
+        )?;
+        update(
+            &mut tables.material_types,
+            material_type.id,
+            material_type,
+            "Material type not found",
+        )
+    }
+
+    fn remove_material_type(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        let mut tables = self.tables();
+        check_version(
+            &tables.material_types,
+            id,
+            expected,
+            "Material type not found",
+        )?;
+        check_unreferenced(
+            &tables.materials,
+            |row| row.type_id == id,
+            "Material type is still used by materials",
+        )?;
+        remove(&mut tables.material_types, id, "Material type not found")
+    }
+
+    fn get_all_project_materials(&self, query: &ListQuery) -> Result<Page<ProjectMaterial>, Error> {
+        Ok(query.apply(self.tables().project_materials.values()))
+    }
+
+    fn get_project_material(&self, id: i32) -> Result<ProjectMaterial, Error> {
+        get(
+            &self.tables().project_materials,
+            id,
+            "Project material not found",
+        )
+    }
+
+    fn add_project_material(
+        &self,
+        project_material: NewProjectMaterial,
+        booking: &Booking,
+    ) -> Result<ProjectMaterial, Error> {
+        let mut tables = self.tables();
+        check_reference(&tables.projects, project_material.project_id, "project_id")?;
+        check_reference(
+            &tables.materials,
+            project_material.material_id,
+            "material_id",
+        )?;
+        let movement =
+            NewStockMovement::for_project(project_material.project_id, -project_material.amount);
+        tables.book(project_material.material_id, movement, booking)?;
+        let id = tables.next_id("project_material");
+        insert(
+            &mut tables.project_materials,
+            id,
+            project_material.with_id(id),
+        )
+    }
+
+    fn update_project_material(
+        &self,
+        project_material: ProjectMaterial,
+        expected: Option<i32>,
+        booking: &Booking,
+    ) -> Result<ProjectMaterial, Error> {
+        let mut tables = self.tables();
+        check_version(
+            &tables.project_materials,
+            project_material.id,
+            expected,
+            "Project material not found",
+        )?;
+        check_reference(&tables.projects, project_material.project_id, "project_id")?;
+        check_reference(
+            &tables.materials,
+            project_material.material_id,
+            "material_id",
+        )?;
+        let current = tables.project_materials[&project_material.id].clone();
+        for (material_id, movement) in stock_changes(&current, &project_material) {
+            tables.book(material_id, movement, booking)?;
+        }
+        update(
+            &mut tables.project_materials,
+            project_material.id,
+            project_material,
+            "Project material not found",
+        )
+    }
+
+    fn remove_project_material(
+        &self,
+        id: i32,
+        expected: Option<i32>,
+        booking: &Booking,
+    ) -> Result<(), Error> {
+        let mut tables = self.tables();
+        check_version(
+            &tables.project_materials,
+            id,
+            expected,
+            "Project material not found",
+        )?;
+        let current = tables.project_materials[&id].clone();
+        let movement = NewStockMovement::for_project(current.project_id, current.amount);
+        tables.book(current.material_id, movement, booking)?;
+        remove(
+            &mut tables.project_materials,
+            id,
+            "Project material not found",
+        )
+    }
+
+    fn get_costed_materials(
+        &self,
+        project_ids: &[i32],
+    ) -> Result<Vec<(ProjectMaterial, Material)>, Error> {
+        let tables = self.tables();
+        Ok(tables
+            .project_materials
+            .values()
+            .filter(|project_material| project_ids.contains(&project_material.project_id))
+            .filter_map(|project_material| {
+                let material = tables.materials.get(&project_material.material_id)?.clone();
+                Some((project_material.clone(), material))
+            })
+            .collect())
+    }
+
+    fn get_stock_movements(&self, query: &ListQuery) -> Result<Page<StockMovement>, Error> {
+        Ok(query.apply(self.tables().stock_movements.values()))
+    }
+
+    fn add_stock_movement(
+        &self,
+        material_id: i32,
+        movement: NewStockMovement,
+        booking: &Booking,
+    ) -> Result<StockMovement, Error> {
+        self.tables().book(material_id, movement, booking)
+    }
+
+    fn get_all_suppliers(&self, query: &ListQuery) -> Result<Page<Supplier>, Error> {
+        Ok(query.apply(self.tables().suppliers.values()))
+    }
+
+    fn get_supplier(&self, id: i32) -> Result<Supplier, Error> {
+        get(&self.tables().suppliers, id, "Supplier not found")
+    }
+
+    fn add_supplier(&self, supplier: NewSupplier) -> Result<Supplier, Error> {
+        let mut tables = self.tables();
+        let id = tables.next_id("supplier");
+        insert(&mut tables.suppliers, id, supplier.with_id(id))
+    }
+
+    fn update_supplier(
+        &self,
+        supplier: Supplier,
+        expected: Option<i32>,
+    ) -> Result<Supplier, Error> {
+        let mut tables = self.tables();
+        check_version(
+            &tables.suppliers,
+            supplier.id,
+            expected,
+            "Supplier not found",
+        )?;
+        update(
+            &mut tables.suppliers,
+            supplier.id,
+            supplier,
+            "Supplier not found",
+        )
+    }
+
+    fn remove_supplier(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        let mut tables = self.tables();
+        check_version(&tables.suppliers, id, expected, "Supplier not found")?;
+        check_unreferenced(
+            &tables.purchase_orders,
+            |row| row.supplier_id == id,
+            "Supplier is still used by purchase orders",
+        )?;
+        check_unreferenced(
+            &tables.materials,
+            |row| row.preferred_supplier_id == Some(id),
+            "Supplier is still the preferred supplier of materials",
+        )?;
+        remove(&mut tables.suppliers, id, "Supplier not found")
+    }
+
+    fn get_all_purchase_orders(&self, query: &ListQuery) -> Result<Page<PurchaseOrder>, Error> {
+        Ok(query.apply(self.tables().purchase_orders.values()))
+    }
+
+    fn get_purchase_order(&self, id: i32) -> Result<PurchaseOrder, Error> {
+        get(
+            &self.tables().purchase_orders,
+            id,
+            "Purchase order not found",
+        )
+    }
+
+    fn add_purchase_order(&self, purchase_order: NewPurchaseOrder) -> Result<PurchaseOrder, Error> {
+        self.tables().add_purchase_order(purchase_order)
+    }
+
+    fn update_purchase_order(
+        &self,
+        mut purchase_order: PurchaseOrder,
+        expected: Option<i32>,
+    ) -> Result<PurchaseOrder, Error> {
+        let mut tables = self.tables();
+        check_version(
+            &tables.purchase_orders,
+            purchase_order.id,
+            expected,
+            "Purchase order not found",
+        )?;
+        check_reference(&tables.suppliers, purchase_order.supplier_id, "supplier_id")?;
+        let current = tables.purchase_orders[&purchase_order.id].clone();
+        check_status_change(
+            &current,
+            &purchase_order,
+            tables.lines_of(purchase_order.id).len(),
+        )?;
+        // The created_at time is kept
+        purchase_order.created_at = current.created_at;
+        update(
+            &mut tables.purchase_orders,
+            purchase_order.id,
+            purchase_order,
+            "Purchase order not found",
+        )
+    }
+
+    fn remove_purchase_order(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        let mut tables = self.tables();
+        check_version(
+            &tables.purchase_orders,
+            id,
+            expected,
+            "Purchase order not found",
+        )?;
+        check_draft(&tables.purchase_orders[&id])?;
+        tables
+            .purchase_order_lines
+            .retain(|_, line| line.purchase_order_id != id);
+        remove(&mut tables.purchase_orders, id, "Purchase order not found")
+    }
+
+    fn suggest_reorder(&self) -> Result<Reorder, Error> {
+        let mut tables = self.tables();
+        let materials: Vec<Material> = tables.materials.values().cloned().collect();
+        // What is ordered but not delivered yet
+        let mut open = BTreeMap::new();
+        for line in tables.purchase_order_lines.values() {
+            let status = tables.purchase_orders[&line.purchase_order_id].status;
+            if status == PurchaseOrderStatus::Draft || status.is_receivable() {
+                *open.entry(line.material_id).or_insert(0) += line.open_quantity();
+            }
+        }
+        let (suppliers, without_supplier) = reorder_lines(&materials, &open);
+        let mut reorder = Reorder {
+            without_supplier,
+            ..Reorder::default()
+        };
+        for (supplier_id, lines) in suppliers {
+            let purchase_order = tables.add_purchase_order(NewPurchaseOrder {
+                supplier_id,
+                note: "Suggested reorder".to_string(),
+            })?;
+            let lines = lines
+                .into_iter()
+                .map(|line| {
+                    tables.add_purchase_order_line(NewPurchaseOrderLine {
+                        purchase_order_id: purchase_order.id,
+                        ..line
+                    })
+                })
+                .collect::<Result<Vec<PurchaseOrderLine>, Error>>()?;
+            reorder.purchase_orders.push(DraftedOrder {
+                purchase_order,
+                lines,
+            });
+        }
+        Ok(reorder)
+    }
+
+    fn get_all_purchase_order_lines(
+        &self,
+        query: &ListQuery,
+    ) -> Result<Page<PurchaseOrderLine>, Error> {
+        Ok(query.apply(self.tables().purchase_order_lines.values()))
+    }
+
+    fn get_purchase_order_line(&self, id: i32) -> Result<PurchaseOrderLine, Error> {
+        get(
+            &self.tables().purchase_order_lines,
+            id,
+            "Purchase order line not found",
+        )
+    }
+
+    fn add_purchase_order_line(
+        &self,
+        line: NewPurchaseOrderLine,
+    ) -> Result<PurchaseOrderLine, Error> {
+        let mut tables = self.tables();
+        check_draft(&tables.purchase_order_of(line.purchase_order_id)?)?;
+        tables.add_purchase_order_line(line)
+    }
+
+    fn update_purchase_order_line(
+        &self,
+        mut line: PurchaseOrderLine,
+        expected: Option<i32>,
+    ) -> Result<PurchaseOrderLine, Error> {
+        let mut tables = self.tables();
+        check_version(
+            &tables.purchase_order_lines,
+            line.id,
+            expected,
+            "Purchase order line not found",
+        )?;
+        let current = tables.purchase_order_lines[&line.id].clone();
+        check_draft(&tables.purchase_orders[&current.purchase_order_id])?;
+        check_draft(&tables.purchase_order_of(line.purchase_order_id)?)?;
+        check_reference(&tables.materials, line.material_id, "material_id")?;
+        // The received quantity is only changed by receiving the line
+        line.received_quantity = current.received_quantity;
+        update(
+            &mut tables.purchase_order_lines,
+            line.id,
+            line,
+            "Purchase order line not found",
+        )
+    }
+
+    fn remove_purchase_order_line(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        let mut tables = self.tables();
+        check_version(
+            &tables.purchase_order_lines,
+            id,
+            expected,
+            "Purchase order line not found",
+        )?;
+        let current = &tables.purchase_order_lines[&id];
+        check_draft(&tables.purchase_orders[&current.purchase_order_id])?;
+        remove(
+            &mut tables.purchase_order_lines,
+            id,
+            "Purchase order line not found",
+        )
+    }
+
+    fn receive_purchase_order_line(
+        &self,
+        id: i32,
+        quantity: i32,
+        booking: &Booking,
+    ) -> Result<PurchaseOrderLine, Error> {
+        let mut tables = self.tables();
+        let mut line = get(
+            &tables.purchase_order_lines,
+            id,
+            "Purchase order line not found",
+        )?;
+        let mut purchase_order = tables.purchase_orders[&line.purchase_order_id].clone();
+        check_receipt(&purchase_order, &line, quantity)?;
+        let movement = NewStockMovement::for_purchase_order(purchase_order.id, quantity);
+        tables.book(line.material_id, movement, booking)?;
+        line.received_quantity += quantity;
+        let line = update(
+            &mut tables.purchase_order_lines,
+            id,
+            line,
+            "Purchase order line not found",
+        )?;
+        purchase_order.status = received_status(&tables.lines_of(purchase_order.id));
+        update(
+            &mut tables.purchase_orders,
+            purchase_order.id,
+            purchase_order,
+            "Purchase order not found",
+        )?;
+        Ok(line)
+    }
+
+    fn get_all_time_entries(&self, query: &ListQuery) -> Result<Page<TimeEntry>, Error> {
+        Ok(query.apply(self.tables().time_entries.values()))
+    }
+
+    fn get_time_entry(&self, id: i32) -> Result<TimeEntry, Error> {
+        get(&self.tables().time_entries, id, "Time entry not found")
+    }
+
+    fn add_time_entry(&self, entry: NewTimeEntry, user_id: &str) -> Result<TimeEntry, Error> {
+        self.tables().add_time_entry(entry.with_id(0, user_id))
+    }
+
+    fn update_time_entry(
+        &self,
+        entry: TimeEntry,
+        expected: Option<i32>,
+    ) -> Result<TimeEntry, Error> {
+        let mut tables = self.tables();
+        check_version(
+            &tables.time_entries,
+            entry.id,
+            expected,
+            "Time entry not found",
+        )?;
+        check_reference(&tables.projects, entry.project_id, "project_id")?;
+        let entry = check_time_entry_change(&tables.time_entries[&entry.id], entry)?;
+        update(
+            &mut tables.time_entries,
+            entry.id,
+            entry,
+            "Time entry not found",
+        )
+    }
+
+    fn remove_time_entry(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        let mut tables = self.tables();
+        check_version(&tables.time_entries, id, expected, "Time entry not found")?;
+        remove(&mut tables.time_entries, id, "Time entry not found")
+    }
+
+    fn start_timer(
+        &self,
+        timer: Timer,
+        user_id: &str,
+        started_at: DateTime<Utc>,
+    ) -> Result<TimeEntry, Error> {
+        let mut tables = self.tables();
+        if let Some(running) = tables.running_timer(user_id) {
+            return Err(timer_running(&running));
+        }
+        tables.add_time_entry(timer.started(0, user_id, started_at))
+    }
+
+    fn stop_timer(&self, user_id: &str, ended_at: DateTime<Utc>) -> Result<TimeEntry, Error> {
+        let mut tables = self.tables();
+        let running = tables.running_timer(user_id).ok_or_else(no_timer)?;
+        update(
+            &mut tables.time_entries,
+            running.id,
+            running.finished(Some(ended_at)),
+            "Time entry not found",
+        )
+    }
+
+    fn get_time_summary(
+        &self,
+        query: &ListQuery,
+        group: TimeGroup,
+    ) -> Result<Vec<TimeSummary>, Error> {
+        // Every matching entry, not only one page
+        let query = ListQuery {
+            limit: u64::MAX,
+            offset: 0,
+            ..query.clone()
+        };
+        let entries = query.apply(self.tables().time_entries.values()).items;
+        let mut groups: BTreeMap<(Option<i32>, Option<String>), TimeSummary> = BTreeMap::new();
+        for entry in entries.iter().filter(|entry| !entry.is_running()) {
+            let (project_id, user_id) = match group {
+                TimeGroup::Project => (Some(entry.project_id), None),
+                TimeGroup::User => (None, Some(entry.user_id.clone())),
+            };
+            let sum = groups
+                .entry((project_id, user_id.clone()))
+                .or_insert_with(|| TimeSummary::new(project_id, user_id, 0, 0, 0));
+            sum.entries += 1;
+            sum.minutes += entry.minutes as i64;
+            if entry.billable {
+                sum.billable_minutes += entry.minutes as i64;
+            }
+        }
+        // The hours of the summed up minutes
+        Ok(groups
+            .into_values()
+            .map(|sum| {
+                TimeSummary::new(
+                    sum.project_id,
+                    sum.user_id,
+                    sum.entries,
+                    sum.minutes,
+                    sum.billable_minutes,
+                )
+            })
+            .collect())
+    }
+
+    fn get_logged_minutes(&self, project_ids: &[i32]) -> Result<BTreeMap<i32, i64>, Error> {
+        let mut minutes: BTreeMap<i32, i64> = BTreeMap::new();
+        for entry in self
+            .tables()
+            .time_entries
+            .values()
+            .filter(|entry| !entry.is_running() && project_ids.contains(&entry.project_id))
+        {
+            *minutes.entry(entry.project_id).or_default() += entry.minutes as i64;
+        }
+        Ok(minutes)
+    }
+
+    fn search(&self, query: &SearchQuery) -> Result<SearchResults, Error> {
+        let tables = self.tables();
+        let mut results = SearchResults {
+            query: query.text.clone(),
+            ..SearchResults::default()
+        };
+        if query.clients {
+            results.clients = query.rank(tables.clients.values());
+        }
+        if query.projects {
+            results.projects = query.rank(tables.projects.values());
+        }
+        if query.materials {
+            results.materials = query.rank(tables.materials.values());
+        }
+        Ok(results)
+    }
+
+    // The in-memory tables always have the current layout, there is nothing to migrate
+    fn migrations(&self) -> &'static [Migration] {
+        &[]
+    }
+
+    fn applied_migrations(&self) -> Result<Vec<i64>, Error> {
+        Ok(Vec::new())
+    }
+
+    fn apply_migration(&self, _migration: &Migration, _direction: Direction) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+/*
+
+    Description: This file contains the versioned schema migrations of the SQL backends. The SQL files in
+                 migrations/<dialect> are embedded into the binary, the applied versions are stored in the
+                 schema_migrations table of the database.
+
+*/
+
+use clap::Subcommand;
+
+use super::WorkshopStore;
+use crate::models::error::Error;
+
+// One schema change with the SQL to apply and to revert it
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+// Embed the up and down script of a migration
+macro_rules! migration {
+    ($dialect:literal, $version:literal, $name:literal) => {
+        Migration {
+            version: $version,
+            name: $name,
+            up: include_str!(concat!(
+                "../../../migrations/",
+                $dialect,
+                "/",
+                $name,
+                ".up.sql"
+            )),
+            down: include_str!(concat!(
+                "../../../migrations/",
+                $dialect,
+                "/",
+                $name,
+                ".down.sql"
+            )),
+        }
+    };
+}
+
+// The migrations of every dialect (Ordered by version, the versions have to match between the dialects)
+pub const MYSQL: &[Migration] = &[
+    migration!("mysql", 1, "0001_initial_schema"),
+    migration!("mysql", 2, "0002_row_versions"),
+    migration!("mysql", 3, "0003_project_datetimes"),
+    migration!("mysql", 4, "0004_money"),
+    migration!("mysql", 5, "0005_stock_movements"),
+    migration!("mysql", 6, "0006_purchasing"),
+    migration!("mysql", 7, "0007_time_entries"),
+    migration!("mysql", 8, "0008_project_status"),
+];
+pub const SQLITE: &[Migration] = &[
+    migration!("sqlite", 1, "0001_initial_schema"),
+    migration!("sqlite", 2, "0002_row_versions"),
+    migration!("sqlite", 3, "0003_project_datetimes"),
+    migration!("sqlite", 4, "0004_money"),
+    migration!("sqlite", 5, "0005_stock_movements"),
+    migration!("sqlite", 6, "0006_purchasing"),
+    migration!("sqlite", 7, "0007_time_entries"),
+    migration!("sqlite", 8, "0008_project_status"),
+];
+
+// The migrate subcommand
+#[derive(Subcommand, Debug, Clone)]
+pub enum MigrateAction {
+    /// Apply all pending migrations (or up to a version)
+    Up {
+        #[arg(long)]
+        to: Option<i64>,
+    },
+    /// Revert the last applied migration (or down to a version)
+    Down {
+        #[arg(long)]
+        to: Option<i64>,
+    },
+    /// List all migrations and whether they are applied
+    Status,
+}
+
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: &'static str,
+    pub applied: bool,
+}
+
+// The schema version the binary was built for
+pub fn expected_version(store: &dyn WorkshopStore) -> i64 {
+    store.migrations().last().map_or(0, |m| m.version)
+}
+
+// The highest applied version of the database
+pub fn current_version(store: &dyn WorkshopStore) -> Result<i64, Error> {
+    Ok(store.applied_migrations()?.into_iter().max().unwrap_or(0))
+}
+
+pub fn status(store: &dyn WorkshopStore) -> Result<Vec<MigrationStatus>, Error> {
+    let applied = store.applied_migrations()?;
+    Ok(store
+        .migrations()
+        .iter()
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            name: migration.name,
+            applied: applied.contains(&migration.version),
+        })
+        .collect())
+}
+
+// Apply the pending migrations up to the target version, returns the applied versions
+pub fn migrate_up(store: &dyn WorkshopStore, target: Option<i64>) -> Result<Vec<i64>, Error> {
+    let applied = store.applied_migrations()?;
+    let target = target.unwrap_or_else(|| expected_version(store));
+    let mut result = Vec::new();
+    for migration in store.migrations() {
+        if migration.version > target || applied.contains(&migration.version) {
+            continue;
+        }
+        store.apply_migration(migration, Direction::Up)?;
+        result.push(migration.version);
+    }
+    Ok(result)
+}
+
+// Revert the applied migrations above the target version, returns the reverted versions
+pub fn migrate_down(store: &dyn WorkshopStore, target: i64) -> Result<Vec<i64>, Error> {
+    let applied = store.applied_migrations()?;
+    let mut result = Vec::new();
+    for migration in store.migrations().iter().rev() {
+        if migration.version <= target || !applied.contains(&migration.version) {
+            continue;
+        }
+        store.apply_migration(migration, Direction::Down)?;
+        result.push(migration.version);
+    }
+    Ok(result)
+}
+
+// Refuse to serve if the database schema does not match the binary
+pub fn check(store: &dyn WorkshopStore) -> Result<(), Error> {
+    let current = current_version(store)?;
+    let expected = expected_version(store);
+    if current != expected {
+        return Err(Error::new(
+            format!(
+                "Database schema version is {} but {} is required, run `workshop_service migrate up`",
+                current, expected
+            ),
+            500,
+        ));
+    }
+    Ok(())
+}
+
+// Run the migrate subcommand and print the result
+pub fn run(store: &dyn WorkshopStore, action: &MigrateAction) -> Result<(), Error> {
+    match action {
+        MigrateAction::Up { to } => {
+            let applied = migrate_up(store, *to)?;
+            if applied.is_empty() {
+                println!("Database schema is up to date");
+            }
+            for version in applied {
+                println!("Applied migration {}", version);
+            }
+        }
+        MigrateAction::Down { to } => {
+            // Without a target only the last migration is reverted
+            let target = match to {
+                Some(to) => *to,
+                None => (current_version(store)? - 1).max(0),
+            };
+            for version in migrate_down(store, target)? {
+                println!("Reverted migration {}", version);
+            }
+        }
+        MigrateAction::Status => {
+            for migration in status(store)? {
+                let state = if migration.applied {
+                    "applied"
+                } else {
+                    "pending"
+                };
+                println!("{:>4} {} {}", migration.version, migration.name, state);
+            }
+        }
+    }
+    Ok(())
+}
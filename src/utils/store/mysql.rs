@@ -0,0 +1,208 @@
+/*
+
+    Description: This file contains the MySQL backend of the WorkshopStore. It owns the shared connection pool.
+
+*/
+
+use std::time::Duration;
+
+// Import the mysql crate
+use mysql::consts::CapabilityFlags;
+use mysql::prelude::*;
+use mysql::{
+    DriverError, Opts, OptsBuilder, Params, Pool, PoolConstraints, PoolOpts, PooledConn, TxOpts,
+    Value,
+};
+
+use super::migrations::{Migration, MYSQL};
+use super::sql::{Row, SqlConnection, SqlDatabase, SqlStore, SqlValue};
+use crate::models::error::{self, Error};
+use crate::utils::config::DatabaseConfig;
+
+pub type MySqlStore = SqlStore<MySqlDatabase>;
+
+impl MySqlStore {
+    // Create the connection pool from the configuration
+    pub fn open(config: &DatabaseConfig) -> Result<MySqlStore, Error> {
+        Ok(SqlStore::new(MySqlDatabase::open(config)?))
+    }
+}
+
+pub struct MySqlDatabase {
+    pool: Pool,
+    acquire_timeout: Duration,
+}
+
+impl MySqlDatabase {
+    pub fn open(config: &DatabaseConfig) -> Result<MySqlDatabase, Error> {
+        // The url and the pool sizes were already validated when the config was loaded
+        let opts = Opts::from_url(&config.url).map_err(|err| Error::new(err.to_string(), 500))?;
+        let constraints = PoolConstraints::new(config.min_connections, config.max_connections)
+            .ok_or_else(|| Error::new("Invalid database pool size".to_string(), 500))?;
+        // Report the matched instead of the changed rows, so an UPDATE with unchanged values is not a "not found"
+        let opts = OptsBuilder::from_opts(opts)
+            .pool_opts(PoolOpts::default().with_constraints(constraints))
+            .additional_capabilities(CapabilityFlags::CLIENT_FOUND_ROWS);
+        let pool = Pool::new(opts).map_err(|err| Error::new(err.to_string(), 500))?;
+        Ok(MySqlDatabase {
+            pool,
+            acquire_timeout: Duration::from_secs(config.acquire_timeout),
+        })
+    }
+
+    // Get a connection from the pool (Fails if none gets free within the acquire timeout)
+    fn connect(&self) -> Result<PooledConn, Error> {
+        match self.pool.try_get_conn(self.acquire_timeout) {
+            Ok(conn) => Ok(conn),
+            Err(mysql::Error::DriverError(DriverError::Timeout)) => Err(Error::new(
+                "No database connection available".to_string(),
+                503,
+            )),
+            Err(err) => Err(to_error(err)),
+        }
+    }
+}
+
+impl SqlDatabase for MySqlDatabase {
+    fn with_connection<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut dyn SqlConnection) -> Result<T, Error>,
+    {
+        let mut conn = self.connect()?;
+        f(&mut MySqlConnection(&mut conn))
+    }
+
+    fn with_transaction<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut dyn SqlConnection) -> Result<T, Error>,
+    {
+        let mut conn = self.connect()?;
+        // Rolled back when it is dropped without a commit
+        let mut transaction = conn
+            .start_transaction(TxOpts::default())
+            .map_err(to_error)?;
+        let result = f(&mut MySqlConnection(&mut transaction))?;
+        transaction.commit().map_err(to_error)?;
+        Ok(result)
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        MYSQL
+    }
+}
+
+// A pooled connection or a transaction
+struct MySqlConnection<'a, Q: Queryable>(&'a mut Q);
+
+impl<Q: Queryable> SqlConnection for MySqlConnection<'_, Q> {
+    fn query(&mut self, sql: &str, params: Vec<SqlValue>) -> Result<Vec<Row>, Error> {
+        let rows: Vec<mysql::Row> = self.0.exec(sql, to_params(params)).map_err(to_error)?;
+        Ok(rows
+            .into_iter()
+            .map(|row| Row(row.unwrap().into_iter().map(from_value).collect()))
+            .collect())
+    }
+
+    fn execute(&mut self, sql: &str, params: Vec<SqlValue>) -> Result<u64, Error> {
+        let result = self.0.exec_iter(sql, to_params(params)).map_err(to_error)?;
+        Ok(result.affected_rows())
+    }
+
+    fn insert(&mut self, sql: &str, params: Vec<SqlValue>) -> Result<i64, Error> {
+        let result = self.0.exec_iter(sql, to_params(params)).map_err(to_error)?;
+        result
+            .last_insert_id()
+            .map(|id| id as i64)
+            .ok_or_else(|| Error::new("The database did not assign an id".to_string(), 500))
+    }
+
+    fn execute_batch(&mut self, sql: &str) -> Result<(), Error> {
+        // Run the statements one by one so an error in any of them is reported
+        for statement in split_statements(sql) {
+            self.0.query_drop(statement).map_err(to_error)?;
+        }
+        Ok(())
+    }
+}
+
+// Split a script at the semicolons that end a line, comment lines are dropped
+pub fn split_statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    for line in sql.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("--") {
+            continue;
+        }
+        current.push_str(line);
+        current.push('\n');
+        if trimmed.ends_with(';') {
+            statements.push(current.trim().trim_end_matches(';').to_string());
+            current.clear();
+        }
+    }
+    if !current.trim().is_empty() {
+        statements.push(current.trim().to_string());
+    }
+    statements
+}
+
+// Map the server errors the client can cause to their status codes
+fn to_error(err: mysql::Error) -> Error {
+    match &err {
+        mysql::Error::MySqlError(server) => match server.code {
+            // ER_DUP_ENTRY
+            1062 => Error::with_kind(server.message.clone(), 409, error::DUPLICATE_KEY),
+            // ER_ROW_IS_REFERENCED(_2), the row is still used by another one
+            1217 | 1451 => {
+                Error::with_kind(server.message.clone(), 409, error::REFERENCED_BY_OTHERS)
+            }
+            // ER_NO_REFERENCED_ROW(_2), the referenced row does not exist
+            1216 | 1452 => {
+                Error::with_kind(server.message.clone(), 422, error::FOREIGN_KEY_VIOLATION)
+            }
+            _ => Error::new(err.to_string(), 500),
+        },
+        _ => Error::new(err.to_string(), 500),
+    }
+}
+
+fn to_params(params: Vec<SqlValue>) -> Params {
+    if params.is_empty() {
+        return Params::Empty;
+    }
+    Params::Positional(
+        params
+            .into_iter()
+            .map(|value| match value {
+                SqlValue::Null => Value::NULL,
+                SqlValue::Int(value) => Value::Int(value),
+                SqlValue::Real(value) => Value::Double(value),
+                SqlValue::Text(value) => Value::Bytes(value.into_bytes()),
+            })
+            .collect(),
+    )
+}
+
+fn from_value(value: Value) -> SqlValue {
+    match value {
+        Value::NULL => SqlValue::Null,
+        Value::Int(value) => SqlValue::Int(value),
+        Value::UInt(value) => SqlValue::Int(value as i64),
+        Value::Float(value) => SqlValue::Real(value as f64),
+        Value::Double(value) => SqlValue::Real(value),
+        Value::Bytes(value) => SqlValue::Text(String::from_utf8_lossy(&value).into_owned()),
+        // Dates are handed around in the MySQL text format
+        Value::Date(year, month, day, hour, minute, second, _) => SqlValue::Text(format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            year, month, day, hour, minute, second
+        )),
+        Value::Time(negative, days, hours, minutes, seconds, _) => SqlValue::Text(format!(
+            "{}{:02}:{:02}:{:02}",
+            if negative { "-" } else { "" },
+            days * 24 + hours as u32,
+            minutes,
+            seconds
+        )),
+    }
+}
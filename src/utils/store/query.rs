@@ -0,0 +1,661 @@
+/*
+
+    Description: This file contains the pagination, sorting and filtering of the list endpoints. The query parameters
+                 are checked against the fields of the model, the SQL backends turn them into WHERE / ORDER BY / LIMIT
+                 and the in-memory backend applies them to its tables.
+
+*/
+
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Days, Utc};
+
+use super::sql::SqlValue;
+use crate::models::client::Client;
+use crate::models::error::Error;
+use crate::models::material::Material;
+use crate::models::material_type::MaterialType;
+use crate::models::money;
+use crate::models::project::Project;
+use crate::models::project_material::ProjectMaterial;
+use crate::models::project_transition::ProjectTransition;
+use crate::models::purchase_order::PurchaseOrder;
+use crate::models::purchase_order_line::PurchaseOrderLine;
+use crate::models::stock_movement::StockMovement;
+use crate::models::supplier::Supplier;
+use crate::models::time_entry::TimeEntry;
+use crate::models::timestamp;
+
+// Rows per page if the request has no limit and the most rows a request can get
+pub const DEFAULT_LIMIT: u64 = 100;
+pub const MAX_LIMIT: u64 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    Int,
+    Real,
+    Text,
+    // ISO-8601 in the query, a plain date is midnight UTC
+    DateTime,
+    // A decimal amount like 12.50 (The currency is a field of its own)
+    Money,
+}
+
+// The fields of a model that can be filtered and sorted by (The names are the column names as well)
+pub trait Fields {
+    const FIELDS: &'static [(&'static str, FieldType)];
+    fn field(&self, name: &str) -> SqlValue;
+
+    // Filters with their own name that do not follow the field name (None if the key is not one of them)
+    fn named_filter(_key: &str, _value: &str) -> Option<Result<Vec<Filter>, Error>> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    // Text contains the value (Case insensitive)
+    Like,
+}
+
+// The suffix of the query parameter (type_id_lt=3) and the SQL operator
+const OPS: &[(&str, Op, &str)] = &[
+    ("_ne", Op::Ne, "<>"),
+    ("_lt", Op::Lt, "<"),
+    ("_lte", Op::Le, "<="),
+    ("_gt", Op::Gt, ">"),
+    ("_gte", Op::Ge, ">="),
+    ("_like", Op::Like, "LIKE"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    pub field: &'static str,
+    pub op: Op,
+    pub value: SqlValue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+// The page, order and filters a list request asks for
+#[derive(Debug, Clone)]
+pub struct ListQuery {
+    pub limit: u64,
+    pub offset: u64,
+    pub sort: Vec<(&'static str, SortOrder)>,
+    pub filters: Vec<Filter>,
+}
+
+impl Default for ListQuery {
+    fn default() -> ListQuery {
+        ListQuery {
+            limit: DEFAULT_LIMIT,
+            offset: 0,
+            sort: Vec::new(),
+            filters: Vec::new(),
+        }
+    }
+}
+
+// One page of a list and the number of all matching rows
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u64,
+}
+
+impl ListQuery {
+    // Parse the query parameters of a list request (400 for unknown fields and invalid values)
+    // limit=50&offset=100&sort=name:asc,amount:desc&type_id=3&amount_lt=10&name_like=screw
+    pub fn parse<T: Fields>(params: &[(String, String)]) -> Result<ListQuery, Error> {
+        let mut query = ListQuery::default();
+        for (key, value) in params {
+            match key.as_str() {
+                "limit" => {
+                    query.limit = parse_number(key, value)?;
+                    if query.limit == 0 || query.limit > MAX_LIMIT {
+                        return Err(bad_request(format!(
+                            "limit must be between 1 and {}",
+                            MAX_LIMIT
+                        )));
+                    }
+                }
+                "offset" => query.offset = parse_number(key, value)?,
+                "sort" => {
+                    for part in value.split(',').filter(|part| !part.is_empty()) {
+                        let (name, order) = part.split_once(':').unwrap_or((part, "asc"));
+                        let order = match order {
+                            "asc" => SortOrder::Asc,
+                            "desc" => SortOrder::Desc,
+                            _ => {
+                                return Err(bad_request(format!(
+                                    "Invalid sort order {}, use asc or desc",
+                                    order
+                                )))
+                            }
+                        };
+                        query.sort.push((field::<T>(name)?.0, order));
+                    }
+                }
+                _ => match T::named_filter(key, value) {
+                    Some(filters) => query.filters.extend(filters?),
+                    None => query.filters.push(filter::<T>(key, value)?),
+                },
+            }
+        }
+        Ok(query)
+    }
+
+    // The WHERE clause (Empty without filters) and its parameters
+    pub fn where_clause(&self) -> (String, Vec<SqlValue>) {
+        if self.filters.is_empty() {
+            return (String::new(), Vec::new());
+        }
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+        for filter in &self.filters {
+            match filter.op {
+                Op::Eq => conditions.push(format!("{} = ?", filter.field)),
+                Op::Like => conditions.push(format!("{} LIKE ? ESCAPE '!'", filter.field)),
+                op => {
+                    let sql = OPS.iter().find(|(_, o, _)| *o == op).unwrap().2;
+                    conditions.push(format!("{} {} ?", filter.field, sql))
+                }
+            }
+            params.push(match (&filter.op, &filter.value) {
+                (Op::Like, SqlValue::Text(text)) => SqlValue::Text(like_pattern(text)),
+                (_, value) => value.clone(),
+            });
+        }
+        (format!(" WHERE {}", conditions.join(" AND ")), params)
+    }
+
+    // The ORDER BY clause, the id keeps the order of equal rows stable between pages
+    pub fn order_clause(&self) -> String {
+        let mut parts: Vec<String> = self
+            .sort
+            .iter()
+            .map(|(field, order)| match order {
+                SortOrder::Asc => format!("{} ASC", field),
+                SortOrder::Desc => format!("{} DESC", field),
+            })
+            .collect();
+        parts.push("id ASC".to_string());
+        format!(" ORDER BY {}", parts.join(", "))
+    }
+
+    // Filter, sort and page rows in memory like the SQL backends do
+    pub fn apply<'a, T: Fields + Clone + 'a>(&self, rows: impl Iterator<Item = &'a T>) -> Page<T> {
+        let mut rows: Vec<&T> = rows
+            .filter(|row| self.filters.iter().all(|filter| matches(*row, filter)))
+            .collect();
+        // The rows come ordered by id, the stable sort keeps that order for equal rows
+        rows.sort_by(|a, b| {
+            self.sort
+                .iter()
+                .map(|(field, order)| {
+                    let ordering = compare(&a.field(field), &b.field(field));
+                    match order {
+                        SortOrder::Asc => ordering,
+                        SortOrder::Desc => ordering.reverse(),
+                    }
+                })
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+        Page {
+            total: rows.len() as u64,
+            items: rows
+                .into_iter()
+                .skip(self.offset as usize)
+                .take(self.limit as usize)
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+// A LIKE pattern for values containing the text (Used with ESCAPE '!', a backslash would mean something else in MySQL)
+pub fn like_pattern(text: &str) -> String {
+    format!(
+        "%{}%",
+        text.replace('!', "!!")
+            .replace('%', "!%")
+            .replace('_', "!_")
+    )
+}
+
+fn matches<T: Fields>(row: &T, filter: &Filter) -> bool {
+    let value = row.field(filter.field);
+    match filter.op {
+        Op::Eq => compare(&value, &filter.value) == Ordering::Equal,
+        Op::Ne => compare(&value, &filter.value) != Ordering::Equal,
+        Op::Lt => compare(&value, &filter.value) == Ordering::Less,
+        Op::Le => compare(&value, &filter.value) != Ordering::Greater,
+        Op::Gt => compare(&value, &filter.value) == Ordering::Greater,
+        Op::Ge => compare(&value, &filter.value) != Ordering::Less,
+        Op::Like => match (&value, &filter.value) {
+            (SqlValue::Text(text), SqlValue::Text(part)) => {
+                text.to_lowercase().contains(&part.to_lowercase())
+            }
+            _ => false,
+        },
+    }
+}
+
+// Compare two values of the same field (NULL sorts first like in MySQL and SQLite)
+fn compare(a: &SqlValue, b: &SqlValue) -> Ordering {
+    match (a, b) {
+        (SqlValue::Int(a), SqlValue::Int(b)) => a.cmp(b),
+        (SqlValue::Text(a), SqlValue::Text(b)) => a.cmp(b),
+        (SqlValue::Null, SqlValue::Null) => Ordering::Equal,
+        (SqlValue::Null, _) => Ordering::Less,
+        (_, SqlValue::Null) => Ordering::Greater,
+        (a, b) => as_real(a)
+            .partial_cmp(&as_real(b))
+            .unwrap_or(Ordering::Equal),
+    }
+}
+
+fn as_real(value: &SqlValue) -> f64 {
+    match value {
+        SqlValue::Int(value) => *value as f64,
+        SqlValue::Real(value) => *value,
+        _ => 0.0,
+    }
+}
+
+// The field of the model with the name
+fn field<T: Fields>(name: &str) -> Result<(&'static str, FieldType), Error> {
+    T::FIELDS
+        .iter()
+        .find(|(field, _)| *field == name)
+        .copied()
+        .ok_or_else(|| bad_request(format!("Unknown field {}", name)))
+}
+
+// A filter parameter: the field name, optionally followed by the operator suffix
+fn filter<T: Fields>(key: &str, value: &str) -> Result<Filter, Error> {
+    let (name, op) = match field::<T>(key) {
+        Ok(_) => (key, Op::Eq),
+        Err(err) => OPS
+            .iter()
+            .find_map(|(suffix, op, _)| key.strip_suffix(suffix).map(|name| (name, *op)))
+            .ok_or(err)?,
+    };
+    let (field, field_type) = field::<T>(name)?;
+    let value = match field_type {
+        FieldType::Int => SqlValue::Int(parse_number(key, value)?),
+        FieldType::Real => SqlValue::Real(parse_number(key, value)?),
+        FieldType::Text => SqlValue::Text(value.to_string()),
+        FieldType::DateTime => parse_datetime(key, value)?.into(),
+        FieldType::Money => amount(parse_amount(key, value)?),
+    };
+    if op == Op::Like && field_type != FieldType::Text {
+        return Err(bad_request(format!(
+            "{} can only be used on text fields",
+            key
+        )));
+    }
+    Ok(Filter { field, op, value })
+}
+
+fn parse_number<N: std::str::FromStr>(key: &str, value: &str) -> Result<N, Error> {
+    value
+        .parse()
+        .map_err(|_| bad_request(format!("{} is not a valid number: {}", key, value)))
+}
+
+// Amounts are compared as numbers (Like the databases compare their DECIMAL columns with a parameter)
+fn amount(cents: i64) -> SqlValue {
+    SqlValue::Real(cents as f64 / 100.0)
+}
+
+fn parse_amount(key: &str, value: &str) -> Result<i64, Error> {
+    money::parse_amount(value).map_err(|message| bad_request(format!("{}: {}", key, message)))
+}
+
+fn parse_datetime(key: &str, value: &str) -> Result<DateTime<Utc>, Error> {
+    timestamp::parse(value).map_err(|message| bad_request(format!("{}: {}", key, message)))
+}
+
+fn bad_request(message: String) -> Error {
+    Error::new(message, 400)
+}
+
+impl Fields for Project {
+    const FIELDS: &'static [(&'static str, FieldType)] = &[
+        ("id", FieldType::Int),
+        ("name", FieldType::Text),
+        ("client_id", FieldType::Int),
+        ("description", FieldType::Text),
+        ("startpoint", FieldType::DateTime),
+        ("endpoint", FieldType::DateTime),
+        ("estimated_costs", FieldType::Money),
+        ("estimated_costs_currency", FieldType::Text),
+        ("estimated_hours", FieldType::Real),
+        ("costs", FieldType::Money),
+        ("costs_currency", FieldType::Text),
+        ("status", FieldType::Text),
+    ];
+    fn field(&self, name: &str) -> SqlValue {
+        match name {
+            "id" => self.id.into(),
+            "name" => self.name.as_str().into(),
+            "client_id" => self.client_id.into(),
+            "description" => self.description.as_str().into(),
+            "startpoint" => self.startpoint.into(),
+            "endpoint" => self.endpoint.into(),
+            "estimated_costs" => amount(self.estimated_costs.cents),
+            "estimated_costs_currency" => self.estimated_costs.currency.as_str().into(),
+            "estimated_hours" => self.estimated_hours.into(),
+            "costs" => amount(self.costs.cents),
+            "costs_currency" => self.costs.currency.as_str().into(),
+            "status" => self.status.as_str().into(),
+            _ => SqlValue::Null,
+        }
+    }
+
+    // starts_after=2026-10-01, ends_before=... and active_on=2026-10-18 (The whole day in UTC)
+    fn named_filter(key: &str, value: &str) -> Option<Result<Vec<Filter>, Error>> {
+        let (field, op) = match key {
+            "starts_after" => ("startpoint", Op::Gt),
+            "starts_before" => ("startpoint", Op::Lt),
+            "ends_after" => ("endpoint", Op::Gt),
+            "ends_before" => ("endpoint", Op::Lt),
+            "active_on" => return Some(active_on(key, value)),
+            _ => return None,
+        };
+        Some(parse_datetime(key, value).map(|at| {
+            vec![Filter {
+                field,
+                op,
+                value: at.into(),
+            }]
+        }))
+    }
+}
+
+impl Fields for StockMovement {
+    const FIELDS: &'static [(&'static str, FieldType)] = &[
+        ("id", FieldType::Int),
+        ("material_id", FieldType::Int),
+        ("kind", FieldType::Text),
+        ("quantity", FieldType::Int),
+        ("reason", FieldType::Text),
+        ("user_id", FieldType::Text),
+        ("created_at", FieldType::DateTime),
+        ("amount_after", FieldType::Int),
+    ];
+    fn field(&self, name: &str) -> SqlValue {
+        match name {
+            "id" => self.id.into(),
+            "material_id" => self.material_id.into(),
+            "kind" => self.kind.as_str().into(),
+            "quantity" => self.quantity.into(),
+            "reason" => self.reason.as_str().into(),
+            "user_id" => self.user_id.as_str().into(),
+            "created_at" => self.created_at.into(),
+            "amount_after" => self.amount_after.into(),
+            _ => SqlValue::Null,
+        }
+    }
+
+    // after=2026-10-01&before=2026-11-01 (The time the movement was booked)
+    fn named_filter(key: &str, value: &str) -> Option<Result<Vec<Filter>, Error>> {
+        let op = match key {
+            "after" => Op::Gt,
+            "before" => Op::Lt,
+            _ => return None,
+        };
+        Some(parse_datetime(key, value).map(|at| {
+            vec![Filter {
+                field: "created_at",
+                op,
+                value: at.into(),
+            }]
+        }))
+    }
+}
+
+impl Fields for ProjectTransition {
+    const FIELDS: &'static [(&'static str, FieldType)] = &[
+        ("id", FieldType::Int),
+        ("project_id", FieldType::Int),
+        ("from_status", FieldType::Text),
+        ("to_status", FieldType::Text),
+        ("user_id", FieldType::Text),
+        ("note", FieldType::Text),
+        ("created_at", FieldType::DateTime),
+    ];
+    fn field(&self, name: &str) -> SqlValue {
+        match name {
+            "id" => self.id.into(),
+            "project_id" => self.project_id.into(),
+            "from_status" => self.from_status.as_str().into(),
+            "to_status" => self.to_status.as_str().into(),
+            "user_id" => self.user_id.as_str().into(),
+            "note" => self.note.as_str().into(),
+            "created_at" => self.created_at.into(),
+            _ => SqlValue::Null,
+        }
+    }
+}
+
+// Projects that start before the end of the day and end at or after its start
+fn active_on(key: &str, value: &str) -> Result<Vec<Filter>, Error> {
+    let day = parse_datetime(key, value)?
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+        .and_utc();
+    let next_day = day
+        .checked_add_days(Days::new(1))
+        .ok_or_else(|| bad_request(format!("{} is out of range", key)))?;
+    Ok(vec![
+        Filter {
+            field: "startpoint",
+            op: Op::Lt,
+            value: next_day.into(),
+        },
+        Filter {
+            field: "endpoint",
+            op: Op::Ge,
+            value: day.into(),
+        },
+    ])
+}
+
+impl Fields for Client {
+    const FIELDS: &'static [(&'static str, FieldType)] = &[
+        ("id", FieldType::Int),
+        ("firstname", FieldType::Text),
+        ("lastname", FieldType::Text),
+        ("phone", FieldType::Text),
+    ];
+    fn field(&self, name: &str) -> SqlValue {
+        match name {
+            "id" => self.id.into(),
+            "firstname" => self.firstname.as_str().into(),
+            "lastname" => self.lastname.as_str().into(),
+            "phone" => self.phone.as_str().into(),
+            _ => SqlValue::Null,
+        }
+    }
+}
+
+impl Fields for Material {
+    const FIELDS: &'static [(&'static str, FieldType)] = &[
+        ("id", FieldType::Int),
+        ("name", FieldType::Text),
+        ("description", FieldType::Text),
+        ("type_id", FieldType::Int),
+        ("amount", FieldType::Int),
+        ("costs", FieldType::Money),
+        ("costs_currency", FieldType::Text),
+        ("threshold_value", FieldType::Int),
+        ("preferred_supplier_id", FieldType::Int),
+    ];
+    fn field(&self, name: &str) -> SqlValue {
+        match name {
+            "id" => self.id.into(),
+            "name" => self.name.as_str().into(),
+            "description" => self.description.as_str().into(),
+            "type_id" => self.type_id.into(),
+            "amount" => self.amount.into(),
+            "costs" => amount(self.costs.cents),
+            "costs_currency" => self.costs.currency.as_str().into(),
+            "threshold_value" => self.threshold_value.into(),
+            "preferred_supplier_id" => self.preferred_supplier_id.into(),
+            _ => SqlValue::Null,
+        }
+    }
+}
+
+impl Fields for MaterialType {
+    const FIELDS: &'static [(&'static str, FieldType)] = &[
+        ("id", FieldType::Int),
+        ("name", FieldType::Text),
+        ("description", FieldType::Text),
+    ];
+    fn field(&self, name: &str) -> SqlValue {
+        match name {
+            "id" => self.id.into(),
+            "name" => self.name.as_str().into(),
+            "description" => self.description.as_str().into(),
+            _ => SqlValue::Null,
+        }
+    }
+}
+
+impl Fields for ProjectMaterial {
+    const FIELDS: &'static [(&'static str, FieldType)] = &[
+        ("id", FieldType::Int),
+        ("project_id", FieldType::Int),
+        ("material_id", FieldType::Int),
+        ("amount", FieldType::Int),
+    ];
+    fn field(&self, name: &str) -> SqlValue {
+        match name {
+            "id" => self.id.into(),
+            "project_id" => self.project_id.into(),
+            "material_id" => self.material_id.into(),
+            "amount" => self.amount.into(),
+            _ => SqlValue::Null,
+        }
+    }
+}
+
+impl Fields for Supplier {
+    const FIELDS: &'static [(&'static str, FieldType)] = &[
+        ("id", FieldType::Int),
+        ("name", FieldType::Text),
+        ("email", FieldType::Text),
+        ("phone", FieldType::Text),
+    ];
+    fn field(&self, name: &str) -> SqlValue {
+        match name {
+            "id" => self.id.into(),
+            "name" => self.name.as_str().into(),
+            "email" => self.email.as_str().into(),
+            "phone" => self.phone.as_str().into(),
+            _ => SqlValue::Null,
+        }
+    }
+}
+
+impl Fields for PurchaseOrder {
+    const FIELDS: &'static [(&'static str, FieldType)] = &[
+        ("id", FieldType::Int),
+        ("supplier_id", FieldType::Int),
+        ("status", FieldType::Text),
+        ("note", FieldType::Text),
+        ("created_at", FieldType::DateTime),
+    ];
+    fn field(&self, name: &str) -> SqlValue {
+        match name {
+            "id" => self.id.into(),
+            "supplier_id" => self.supplier_id.into(),
+            "status" => self.status.as_str().into(),
+            "note" => self.note.as_str().into(),
+            "created_at" => self.created_at.into(),
+            _ => SqlValue::Null,
+        }
+    }
+}
+
+impl Fields for PurchaseOrderLine {
+    const FIELDS: &'static [(&'static str, FieldType)] = &[
+        ("id", FieldType::Int),
+        ("purchase_order_id", FieldType::Int),
+        ("material_id", FieldType::Int),
+        ("quantity", FieldType::Int),
+        ("received_quantity", FieldType::Int),
+        ("unit_price", FieldType::Money),
+        ("unit_price_currency", FieldType::Text),
+    ];
+    fn field(&self, name: &str) -> SqlValue {
+        match name {
+            "id" => self.id.into(),
+            "purchase_order_id" => self.purchase_order_id.into(),
+            "material_id" => self.material_id.into(),
+            "quantity" => self.quantity.into(),
+            "received_quantity" => self.received_quantity.into(),
+            "unit_price" => amount(self.unit_price.cents),
+            "unit_price_currency" => self.unit_price.currency.as_str().into(),
+            _ => SqlValue::Null,
+        }
+    }
+}
+
+impl Fields for TimeEntry {
+    const FIELDS: &'static [(&'static str, FieldType)] = &[
+        ("id", FieldType::Int),
+        ("project_id", FieldType::Int),
+        ("user_id", FieldType::Text),
+        ("started_at", FieldType::DateTime),
+        ("ended_at", FieldType::DateTime),
+        ("minutes", FieldType::Int),
+        ("note", FieldType::Text),
+        ("billable", FieldType::Int),
+    ];
+    fn field(&self, name: &str) -> SqlValue {
+        match name {
+            "id" => self.id.into(),
+            "project_id" => self.project_id.into(),
+            "user_id" => self.user_id.as_str().into(),
+            "started_at" => self.started_at.into(),
+            "ended_at" => self.ended_at.into(),
+            "minutes" => self.minutes.into(),
+            "note" => self.note.as_str().into(),
+            "billable" => self.billable.into(),
+            _ => SqlValue::Null,
+        }
+    }
+
+    // after=2026-10-01&before=2026-11-01 (The time the work started)
+    fn named_filter(key: &str, value: &str) -> Option<Result<Vec<Filter>, Error>> {
+        let op = match key {
+            "after" => Op::Gt,
+            "before" => Op::Lt,
+            _ => return None,
+        };
+        Some(parse_datetime(key, value).map(|at| {
+            vec![Filter {
+                field: "started_at",
+                op,
+                value: at.into(),
+            }]
+        }))
+    }
+}
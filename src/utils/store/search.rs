@@ -0,0 +1,271 @@
+/*
+
+    Description: This file contains the search across clients, projects and materials. Every word of the search text
+                 has to be found in one of the searched fields, the hits are ranked by how well and where they matched.
+                 The SQL backends fetch the best candidates (Roughly ranked in SQL), the final ranking is the same for
+                 every backend.
+
+*/
+
+use super::query::{like_pattern, Fields};
+use super::sql::SqlValue;
+use crate::models::client::Client;
+use crate::models::error::Error;
+use crate::models::material::Material;
+use crate::models::project::Project;
+use crate::models::search::SearchHit;
+
+// Hits per group if the request has no limit and the most hits per group a request can get
+pub const DEFAULT_LIMIT: usize = 10;
+pub const MAX_LIMIT: usize = 50;
+// Longest search text and the most words of it that are used
+const MAX_LENGTH: usize = 100;
+const MAX_TERMS: usize = 5;
+// Rows per table the SQL backends rank at most
+pub const CANDIDATES: i64 = 200;
+
+// The points of a match, multiplied with the weight of the field
+const EXACT: u32 = 8;
+const PREFIX: u32 = 4;
+const WORD_PREFIX: u32 = 3;
+const CONTAINS: u32 = 1;
+
+// A model that can be searched
+pub trait Searchable: Fields {
+    const KIND: &'static str;
+    // The searched text fields and their weight
+    const SEARCH_FIELDS: &'static [(&'static str, u32)];
+    // Fields that are compared by their digits only (Phone numbers are typed without spaces and dashes)
+    const DIGIT_FIELDS: &'static [&'static str] = &[];
+    fn id(&self) -> i32;
+    fn title(&self) -> String;
+    fn subtitle(&self) -> String;
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    pub text: String,
+    // The lowercase words of the text
+    pub terms: Vec<String>,
+    pub limit: usize,
+    // The groups that are searched (The caller may not be allowed to read all of them)
+    pub clients: bool,
+    pub projects: bool,
+    pub materials: bool,
+}
+
+impl SearchQuery {
+    // Split the search text into words (400 if there is nothing to search for)
+    pub fn parse(text: &str, limit: Option<usize>) -> Result<SearchQuery, Error> {
+        let text = text.trim();
+        if text.is_empty() || text.chars().count() > MAX_LENGTH {
+            return Err(Error::new(
+                format!("q must have between 1 and {} characters", MAX_LENGTH),
+                400,
+            ));
+        }
+        let limit = limit.unwrap_or(DEFAULT_LIMIT);
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(Error::new(
+                format!("limit must be between 1 and {}", MAX_LIMIT),
+                400,
+            ));
+        }
+        let mut terms: Vec<String> = Vec::new();
+        for term in text.split_whitespace().map(str::to_lowercase) {
+            if !terms.contains(&term) && terms.len() < MAX_TERMS {
+                terms.push(term);
+            }
+        }
+        Ok(SearchQuery {
+            text: text.to_string(),
+            terms,
+            limit,
+            clients: true,
+            projects: true,
+            materials: true,
+        })
+    }
+
+    // The WHERE clause that finds the candidates (Every term in one of the fields) and its parameters
+    pub fn where_clause<T: Searchable>(&self) -> (String, Vec<SqlValue>) {
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+        for term in &self.terms {
+            let mut fields = Vec::new();
+            for (field, _) in T::SEARCH_FIELDS {
+                fields.push(format!("LOWER({}) LIKE ? ESCAPE '!'", field));
+                params.push(SqlValue::Text(like_pattern(term)));
+            }
+            let term_digits = digits(term);
+            if !term_digits.is_empty() {
+                for field in T::DIGIT_FIELDS {
+                    fields.push(format!("{} LIKE ?", strip_separators(field)));
+                    params.push(SqlValue::Text(format!("%{}%", term_digits)));
+                }
+            }
+            conditions.push(format!("({})", fields.join(" OR ")));
+        }
+        (format!(" WHERE {}", conditions.join(" AND ")), params)
+    }
+
+    // The ORDER BY clause that puts the likely best rows first (Exact, prefix, word prefix and contains matches)
+    pub fn order_clause<T: Searchable>(&self) -> (String, Vec<SqlValue>) {
+        let mut ranks = Vec::new();
+        let mut params = Vec::new();
+        for term in &self.terms {
+            let prefix = &like_pattern(term)[1..];
+            for (field, weight) in T::SEARCH_FIELDS {
+                ranks.push(format!(
+                    "CASE WHEN LOWER({field}) = ? THEN {} WHEN LOWER({field}) LIKE ? ESCAPE '!' THEN {} WHEN LOWER({field}) LIKE ? ESCAPE '!' THEN {} WHEN LOWER({field}) LIKE ? ESCAPE '!' THEN {} ELSE 0 END",
+                    EXACT * weight,
+                    PREFIX * weight,
+                    WORD_PREFIX * weight,
+                    CONTAINS * weight,
+                ));
+                params.push(SqlValue::Text(term.clone()));
+                params.push(SqlValue::Text(prefix.to_string()));
+                params.push(SqlValue::Text(format!("% {}", prefix)));
+                params.push(SqlValue::Text(like_pattern(term)));
+            }
+        }
+        (format!(" ORDER BY {} DESC, id", ranks.join(" + ")), params)
+    }
+
+    // The hits of the rows that match every term, best first
+    pub fn rank<'a, T: Searchable + 'a>(
+        &self,
+        rows: impl IntoIterator<Item = &'a T>,
+    ) -> Vec<SearchHit> {
+        let mut hits: Vec<SearchHit> = rows.into_iter().filter_map(|row| self.hit(row)).collect();
+        hits.sort_by(|a, b| b.score.cmp(&a.score).then(a.id.cmp(&b.id)));
+        hits.truncate(self.limit);
+        hits
+    }
+
+    fn hit<T: Searchable>(&self, row: &T) -> Option<SearchHit> {
+        let mut score = 0;
+        let mut matched: Vec<String> = Vec::new();
+        for term in &self.terms {
+            let mut best = 0;
+            for (field, weight) in T::SEARCH_FIELDS {
+                let value = text(row, field).to_lowercase();
+                let mut points = points(&value, term);
+                if T::DIGIT_FIELDS.contains(field) && !digits(term).is_empty() {
+                    points = points.max(points_for_digits(&digits(&value), &digits(term)));
+                }
+                if points > 0 && !matched.iter().any(|name| name == field) {
+                    matched.push(field.to_string());
+                }
+                best = best.max(points * weight);
+            }
+            // Every term has to be found somewhere
+            if best == 0 {
+                return None;
+            }
+            score += best;
+        }
+        Some(SearchHit {
+            kind: T::KIND.to_string(),
+            id: row.id(),
+            title: row.title(),
+            subtitle: row.subtitle(),
+            score,
+            matched,
+        })
+    }
+}
+
+// How well the (lowercase) value matches the term
+fn points(value: &str, term: &str) -> u32 {
+    if value == term {
+        EXACT
+    } else if value.starts_with(term) {
+        PREFIX
+    } else if value
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(term))
+    {
+        WORD_PREFIX
+    } else if value.contains(term) {
+        CONTAINS
+    } else {
+        0
+    }
+}
+
+fn points_for_digits(value: &str, term: &str) -> u32 {
+    if value == term {
+        EXACT
+    } else if value.starts_with(term) {
+        PREFIX
+    } else if value.contains(term) {
+        CONTAINS
+    } else {
+        0
+    }
+}
+
+fn digits(value: &str) -> String {
+    value.chars().filter(char::is_ascii_digit).collect()
+}
+
+// The field without the usual separators of phone numbers (Same SQL in MySQL and SQLite)
+fn strip_separators(field: &str) -> String {
+    [" ", "-", "/", "(", ")", ".", "+"]
+        .iter()
+        .fold(field.to_string(), |sql, separator| {
+            format!("REPLACE({}, '{}', '')", sql, separator)
+        })
+}
+
+fn text<T: Fields>(row: &T, field: &str) -> String {
+    match row.field(field) {
+        SqlValue::Text(value) => value,
+        _ => String::new(),
+    }
+}
+
+impl Searchable for Client {
+    const KIND: &'static str = "client";
+    const SEARCH_FIELDS: &'static [(&'static str, u32)] =
+        &[("firstname", 3), ("lastname", 3), ("phone", 2)];
+    const DIGIT_FIELDS: &'static [&'static str] = &["phone"];
+    fn id(&self) -> i32 {
+        self.id
+    }
+    fn title(&self) -> String {
+        format!("{} {}", self.firstname, self.lastname)
+    }
+    fn subtitle(&self) -> String {
+        self.phone.clone()
+    }
+}
+
+impl Searchable for Project {
+    const KIND: &'static str = "project";
+    const SEARCH_FIELDS: &'static [(&'static str, u32)] = &[("name", 3), ("description", 1)];
+    fn id(&self) -> i32 {
+        self.id
+    }
+    fn title(&self) -> String {
+        self.name.clone()
+    }
+    fn subtitle(&self) -> String {
+        self.description.clone()
+    }
+}
+
+impl Searchable for Material {
+    const KIND: &'static str = "material";
+    const SEARCH_FIELDS: &'static [(&'static str, u32)] = &[("name", 3), ("description", 1)];
+    fn id(&self) -> i32 {
+        self.id
+    }
+    fn title(&self) -> String {
+        self.name.clone()
+    }
+    fn subtitle(&self) -> String {
+        self.description.clone()
+    }
+}
@@ -0,0 +1,1560 @@
+/*
+
+    Description: This file contains the SQL implementation of the WorkshopStore trait. The queries are shared by
+                 the MySQL and the SQLite backend, the backends only provide connections and convert the values.
+
+*/
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use super::migrations::{Direction, Migration};
+use super::query::{Fields, ListQuery, Page};
+use super::search::{SearchQuery, Searchable, CANDIDATES};
+use super::version::{self, Versioned};
+use super::{
+    check_draft, check_receipt, check_status_change, check_time_entry_change, check_transition,
+    insufficient_stock, no_timer, received_status, reorder_lines, stock_changes, timer_running,
+    WorkshopStore,
+};
+use crate::models::client::{Client, NewClient};
+use crate::models::error::{self, Error};
+use crate::models::material::{Material, NewMaterial};
+use crate::models::material_type::{MaterialType, NewMaterialType};
+use crate::models::money::{self, Money};
+use crate::models::project::{NewProject, Project, ProjectStatus};
+use crate::models::project_material::{NewProjectMaterial, ProjectMaterial};
+use crate::models::project_transition::{ProjectTransition, Transition};
+use crate::models::purchase_order::{
+    DraftedOrder, NewPurchaseOrder, PurchaseOrder, PurchaseOrderStatus, Reorder,
+};
+use crate::models::purchase_order_line::{NewPurchaseOrderLine, PurchaseOrderLine};
+use crate::models::search::{SearchHit, SearchResults};
+use crate::models::stock_movement::{Booking, MovementKind, NewStockMovement, StockMovement};
+use crate::models::supplier::{NewSupplier, Supplier};
+use crate::models::time_entry::{NewTimeEntry, TimeEntry, TimeGroup, TimeSummary, Timer};
+use crate::models::timestamp;
+
+// Timestamps are stored as UTC text in this format (DATETIME in MySQL, sorts like the time in SQLite)
+pub const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// A database value independent of the backend
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    Null,
+    Int(i64),
+    Real(f64),
+    Text(String),
+}
+
+impl From<i32> for SqlValue {
+    fn from(value: i32) -> SqlValue {
+        SqlValue::Int(value as i64)
+    }
+}
+
+impl From<i64> for SqlValue {
+    fn from(value: i64) -> SqlValue {
+        SqlValue::Int(value)
+    }
+}
+
+impl From<f32> for SqlValue {
+    fn from(value: f32) -> SqlValue {
+        SqlValue::Real(value as f64)
+    }
+}
+
+// Stored as 0 or 1 (MySQL BOOLEAN is a TINYINT)
+impl From<bool> for SqlValue {
+    fn from(value: bool) -> SqlValue {
+        SqlValue::Int(value as i64)
+    }
+}
+
+impl From<String> for SqlValue {
+    fn from(value: String) -> SqlValue {
+        SqlValue::Text(value)
+    }
+}
+
+impl From<&str> for SqlValue {
+    fn from(value: &str) -> SqlValue {
+        SqlValue::Text(value.to_string())
+    }
+}
+
+impl From<DateTime<Utc>> for SqlValue {
+    fn from(value: DateTime<Utc>) -> SqlValue {
+        SqlValue::Text(value.format(DATETIME_FORMAT).to_string())
+    }
+}
+
+// The amount as exact decimal text (The currency has its own column)
+impl From<Money> for SqlValue {
+    fn from(value: Money) -> SqlValue {
+        SqlValue::Text(value.amount())
+    }
+}
+
+impl<T: Into<SqlValue>> From<Option<T>> for SqlValue {
+    fn from(value: Option<T>) -> SqlValue {
+        match value {
+            Some(value) => value.into(),
+            None => SqlValue::Null,
+        }
+    }
+}
+
+// Build a parameter list from values of different types
+macro_rules! sql_params {
+    ($($value:expr),* $(,)?) => {
+        vec![$($crate::utils::store::sql::SqlValue::from($value)),*]
+    };
+}
+
+// A result row, the values are in the order of the selected columns
+pub struct Row(pub Vec<SqlValue>);
+
+impl Row {
+    fn value(&self, index: usize) -> Result<&SqlValue, Error> {
+        self.0
+            .get(index)
+            .ok_or_else(|| Error::new(format!("Missing column {}", index), 500))
+    }
+
+    pub fn int(&self, index: usize) -> Result<i64, Error> {
+        match self.value(index)? {
+            SqlValue::Int(value) => Ok(*value),
+            SqlValue::Real(value) => Ok(*value as i64),
+            SqlValue::Text(value) => value
+                .parse()
+                .map_err(|_| Error::new(format!("Column {} is not a number", index), 500)),
+            SqlValue::Null => Err(Error::new(format!("Column {} is NULL", index), 500)),
+        }
+    }
+
+    pub fn i32(&self, index: usize) -> Result<i32, Error> {
+        i32::try_from(self.int(index)?)
+            .map_err(|_| Error::new(format!("Column {} is out of range", index), 500))
+    }
+
+    pub fn real(&self, index: usize) -> Result<f64, Error> {
+        match self.value(index)? {
+            SqlValue::Int(value) => Ok(*value as f64),
+            SqlValue::Real(value) => Ok(*value),
+            SqlValue::Text(value) => value
+                .parse()
+                .map_err(|_| Error::new(format!("Column {} is not a number", index), 500)),
+            SqlValue::Null => Err(Error::new(format!("Column {} is NULL", index), 500)),
+        }
+    }
+
+    pub fn f32(&self, index: usize) -> Result<f32, Error> {
+        Ok(self.real(index)? as f32)
+    }
+
+    // A column that may be NULL
+    pub fn optional_i32(&self, index: usize) -> Result<Option<i32>, Error> {
+        match self.value(index)? {
+            SqlValue::Null => Ok(None),
+            _ => self.i32(index).map(Some),
+        }
+    }
+
+    pub fn bool(&self, index: usize) -> Result<bool, Error> {
+        Ok(self.int(index)? != 0)
+    }
+
+    pub fn text(&self, index: usize) -> Result<String, Error> {
+        match self.value(index)? {
+            SqlValue::Int(value) => Ok(value.to_string()),
+            SqlValue::Real(value) => Ok(value.to_string()),
+            SqlValue::Text(value) => Ok(value.clone()),
+            SqlValue::Null => Err(Error::new(format!("Column {} is NULL", index), 500)),
+        }
+    }
+
+    pub fn datetime(&self, index: usize) -> Result<DateTime<Utc>, Error> {
+        NaiveDateTime::parse_from_str(&self.text(index)?, DATETIME_FORMAT)
+            .map(|value| value.and_utc())
+            .map_err(|_| Error::new(format!("Column {} is not a timestamp", index), 500))
+    }
+
+    pub fn optional_datetime(&self, index: usize) -> Result<Option<DateTime<Utc>>, Error> {
+        match self.value(index)? {
+            SqlValue::Null => Ok(None),
+            _ => self.datetime(index).map(Some),
+        }
+    }
+
+    // An amount (DECIMAL in MySQL, a number in SQLite) and the column of its currency
+    pub fn money(&self, index: usize, currency: usize) -> Result<Money, Error> {
+        let invalid = || Error::new(format!("Column {} is not an amount", index), 500);
+        let cents = match self.value(index)? {
+            SqlValue::Int(value) => value.checked_mul(100).ok_or_else(invalid)?,
+            SqlValue::Real(value) => (value * 100.0).round() as i64,
+            SqlValue::Text(value) => money::parse_amount(value).map_err(|_| invalid())?,
+            SqlValue::Null => return Err(Error::new(format!("Column {} is NULL", index), 500)),
+        };
+        let currency = self
+            .text(currency)?
+            .parse()
+            .map_err(|_| Error::new(format!("Column {} is not a currency", currency), 500))?;
+        Ok(Money::new(cents, currency))
+    }
+}
+
+// A connection (or transaction) of one of the SQL backends
+pub trait SqlConnection {
+    fn query(&mut self, sql: &str, params: Vec<SqlValue>) -> Result<Vec<Row>, Error>;
+    // Run a statement that does not return rows, returns the number of affected rows
+    fn execute(&mut self, sql: &str, params: Vec<SqlValue>) -> Result<u64, Error>;
+    // Run an INSERT, returns the id the database assigned to the new row
+    fn insert(&mut self, sql: &str, params: Vec<SqlValue>) -> Result<i64, Error>;
+    // Run a script of several statements without parameters (Used by the migrations)
+    fn execute_batch(&mut self, sql: &str) -> Result<(), Error>;
+}
+
+// A SQL database that hands out connections
+pub trait SqlDatabase: Send + Sync {
+    // Run the function with a connection of the database
+    fn with_connection<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut dyn SqlConnection) -> Result<T, Error>;
+
+    // Run the function in a transaction (Committed if it succeeds, rolled back if it fails)
+    fn with_transaction<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut dyn SqlConnection) -> Result<T, Error>;
+
+    // The migrations written in the SQL dialect of the database
+    fn migrations(&self) -> &'static [Migration];
+
+    // Whether CREATE / ALTER / DROP can be rolled back (MySQL commits them right away)
+    fn transactional_ddl(&self) -> bool {
+        false
+    }
+}
+
+// Map a result row to a model (The columns are selected in the order of COLUMNS)
+pub trait FromRow: Sized {
+    const TABLE: &'static str;
+    const COLUMNS: &'static str;
+    const NOT_FOUND: &'static str;
+    fn from_row(row: &Row) -> Result<Self, Error>;
+}
+
+impl FromRow for Project {
+    const TABLE: &'static str = "project";
+    const COLUMNS: &'static str = "id, name, client_id, description, startpoint, endpoint, estimated_costs, estimated_hours, costs, version, estimated_costs_currency, costs_currency, status";
+    const NOT_FOUND: &'static str = "Project not found";
+    fn from_row(row: &Row) -> Result<Project, Error> {
+        let status = row.text(12)?;
+        Ok(Project {
+            id: row.i32(0)?,
+            name: row.text(1)?,
+            client_id: row.i32(2)?,
+            description: row.text(3)?,
+            startpoint: row.datetime(4)?,
+            endpoint: row.datetime(5)?,
+            estimated_costs: row.money(6, 10)?,
+            estimated_hours: row.f32(7)?,
+            costs: row.money(8, 11)?,
+            status: ProjectStatus::parse(&status)
+                .ok_or_else(|| Error::new(format!("Unknown project status {}", status), 500))?,
+            version: row.i32(9)?,
+        })
+    }
+}
+
+impl FromRow for ProjectTransition {
+    const TABLE: &'static str = "project_transition";
+    const COLUMNS: &'static str =
+        "id, project_id, from_status, to_status, user_id, note, created_at";
+    const NOT_FOUND: &'static str = "Project transition not found";
+    fn from_row(row: &Row) -> Result<ProjectTransition, Error> {
+        let status = |index: usize| -> Result<ProjectStatus, Error> {
+            let status = row.text(index)?;
+            ProjectStatus::parse(&status)
+                .ok_or_else(|| Error::new(format!("Unknown project status {}", status), 500))
+        };
+        Ok(ProjectTransition {
+            id: row.i32(0)?,
+            project_id: row.i32(1)?,
+            from_status: status(2)?,
+            to_status: status(3)?,
+            user_id: row.text(4)?,
+            note: row.text(5)?,
+            created_at: row.datetime(6)?,
+        })
+    }
+}
+
+impl FromRow for Client {
+    const TABLE: &'static str = "client";
+    const COLUMNS: &'static str = "id, firstname, lastname, phone, version";
+    const NOT_FOUND: &'static str = "Client not found";
+    fn from_row(row: &Row) -> Result<Client, Error> {
+        Ok(Client {
+            id: row.i32(0)?,
+            firstname: row.text(1)?,
+            lastname: row.text(2)?,
+            phone: row.text(3)?,
+            version: row.i32(4)?,
+        })
+    }
+}
+
+impl FromRow for Material {
+    const TABLE: &'static str = "material";
+    const COLUMNS: &'static str = "id, name, description, type_id, amount, costs, threshold_value, version, costs_currency, preferred_supplier_id";
+    const NOT_FOUND: &'static str = "Material not found";
+    fn from_row(row: &Row) -> Result<Material, Error> {
+        Ok(Material {
+            id: row.i32(0)?,
+            name: row.text(1)?,
+            description: row.text(2)?,
+            type_id: row.i32(3)?,
+            amount: row.i32(4)?,
+            costs: row.money(5, 8)?,
+            threshold_value: row.i32(6)?,
+            preferred_supplier_id: row.optional_i32(9)?,
+            version: row.i32(7)?,
+        })
+    }
+}
+
+impl FromRow for StockMovement {
+    const TABLE: &'static str = "stock_movement";
+    const COLUMNS: &'static str =
+        "id, material_id, kind, quantity, reason, user_id, created_at, amount_after";
+    const NOT_FOUND: &'static str = "Stock movement not found";
+    fn from_row(row: &Row) -> Result<StockMovement, Error> {
+        let kind = row.text(2)?;
+        Ok(StockMovement {
+            id: row.i32(0)?,
+            material_id: row.i32(1)?,
+            kind: MovementKind::parse(&kind)
+                .ok_or_else(|| Error::new(format!("Unknown movement kind {}", kind), 500))?,
+            quantity: row.i32(3)?,
+            reason: row.text(4)?,
+            user_id: row.text(5)?,
+            created_at: row.datetime(6)?,
+            amount_after: row.i32(7)?,
+        })
+    }
+}
+
+impl FromRow for MaterialType {
+    const TABLE: &'static str = "material_type";
+    const COLUMNS: &'static str = "id, name, description, version";
+    const NOT_FOUND: &'static str = "Material type not found";
+    fn from_row(row: &Row) -> Result<MaterialType, Error> {
+        Ok(MaterialType {
+            id: row.i32(0)?,
+            name: row.text(1)?,
+            description: row.text(2)?,
+            version: row.i32(3)?,
+        })
+    }
+}
+
+impl FromRow for ProjectMaterial {
+    const TABLE: &'static str = "project_material";
+    const COLUMNS: &'static str = "id, project_id, material_id, amount, version";
+    const NOT_FOUND: &'static str = "Project material not found";
+    fn from_row(row: &Row) -> Result<ProjectMaterial, Error> {
+        Ok(ProjectMaterial {
+            id: row.i32(0)?,
+            project_id: row.i32(1)?,
+            material_id: row.i32(2)?,
+            amount: row.i32(3)?,
+            version: row.i32(4)?,
+        })
+    }
+}
+
+impl FromRow for Supplier {
+    const TABLE: &'static str = "supplier";
+    const COLUMNS: &'static str = "id, name, email, phone, version";
+    const NOT_FOUND: &'static str = "Supplier not found";
+    fn from_row(row: &Row) -> Result<Supplier, Error> {
+        Ok(Supplier {
+            id: row.i32(0)?,
+            name: row.text(1)?,
+            email: row.text(2)?,
+            phone: row.text(3)?,
+            version: row.i32(4)?,
+        })
+    }
+}
+
+impl FromRow for PurchaseOrder {
+    const TABLE: &'static str = "purchase_order";
+    const COLUMNS: &'static str = "id, supplier_id, status, note, created_at, version";
+    const NOT_FOUND: &'static str = "Purchase order not found";
+    fn from_row(row: &Row) -> Result<PurchaseOrder, Error> {
+        let status = row.text(2)?;
+        Ok(PurchaseOrder {
+            id: row.i32(0)?,
+            supplier_id: row.i32(1)?,
+            status: PurchaseOrderStatus::parse(&status).ok_or_else(|| {
+                Error::new(format!("Unknown purchase order status {}", status), 500)
+            })?,
+            note: row.text(3)?,
+            created_at: row.datetime(4)?,
+            version: row.i32(5)?,
+        })
+    }
+}
+
+impl FromRow for PurchaseOrderLine {
+    const TABLE: &'static str = "purchase_order_line";
+    const COLUMNS: &'static str = "id, purchase_order_id, material_id, quantity, received_quantity, unit_price, version, unit_price_currency";
+    const NOT_FOUND: &'static str = "Purchase order line not found";
+    fn from_row(row: &Row) -> Result<PurchaseOrderLine, Error> {
+        Ok(PurchaseOrderLine {
+            id: row.i32(0)?,
+            purchase_order_id: row.i32(1)?,
+            material_id: row.i32(2)?,
+            quantity: row.i32(3)?,
+            received_quantity: row.i32(4)?,
+            unit_price: row.money(5, 7)?,
+            version: row.i32(6)?,
+        })
+    }
+}
+
+impl FromRow for TimeEntry {
+    const TABLE: &'static str = "time_entry";
+    const COLUMNS: &'static str =
+        "id, project_id, user_id, started_at, ended_at, minutes, note, billable, version";
+    const NOT_FOUND: &'static str = "Time entry not found";
+    fn from_row(row: &Row) -> Result<TimeEntry, Error> {
+        Ok(TimeEntry {
+            id: row.i32(0)?,
+            project_id: row.i32(1)?,
+            user_id: row.text(2)?,
+            started_at: row.datetime(3)?,
+            ended_at: row.optional_datetime(4)?,
+            minutes: row.i32(5)?,
+            note: row.text(6)?,
+            billable: row.bool(7)?,
+            version: row.i32(8)?,
+        })
+    }
+}
+
+// Select one page of the rows that match the filters and count all of them
+pub fn select_page<T: FromRow + Fields>(
+    conn: &mut dyn SqlConnection,
+    query: &ListQuery,
+) -> Result<Page<T>, Error> {
+    select_page_where(conn, query, None)
+}
+
+// Like select_page, the rows also have to meet the condition (A comparison of columns without parameters)
+pub fn select_page_where<T: FromRow + Fields>(
+    conn: &mut dyn SqlConnection,
+    query: &ListQuery,
+    condition: Option<&str>,
+) -> Result<Page<T>, Error> {
+    // The field names of the query were checked against the fields of the model
+    let (mut conditions, params) = query.where_clause();
+    if let Some(condition) = condition {
+        let keyword = if conditions.is_empty() {
+            " WHERE"
+        } else {
+            " AND"
+        };
+        conditions = format!("{}{} {}", conditions, keyword, condition);
+    }
+    let count = format!("SELECT COUNT(*) FROM {}{}", T::TABLE, conditions);
+    let total = match conn.query(&count, params.clone())?.first() {
+        Some(row) => row.int(0)? as u64,
+        None => 0,
+    };
+    let select = format!(
+        "SELECT {} FROM {}{}{} LIMIT ? OFFSET ?",
+        T::COLUMNS,
+        T::TABLE,
+        conditions,
+        query.order_clause()
+    );
+    let mut params = params;
+    params.push(SqlValue::Int(query.limit as i64));
+    params.push(SqlValue::Int(query.offset as i64));
+    let items = conn
+        .query(&select, params)?
+        .iter()
+        .map(T::from_row)
+        .collect::<Result<Vec<T>, Error>>()?;
+    Ok(Page { items, total })
+}
+
+// Select one row of a table by its id
+pub fn select_by_id<T: FromRow>(conn: &mut dyn SqlConnection, id: i32) -> Result<T, Error> {
+    let query = format!("SELECT {} FROM {} WHERE id = ?", T::COLUMNS, T::TABLE);
+    match conn.query(&query, sql_params![id])?.first() {
+        Some(row) => T::from_row(row),
+        None => Err(Error::new(T::NOT_FOUND.to_string(), 404)),
+    }
+}
+
+// Select the rows of a table that meet the condition, ordered by id (For the rows a change depends on)
+fn select_where<T: FromRow>(
+    conn: &mut dyn SqlConnection,
+    condition: &str,
+    params: Vec<SqlValue>,
+) -> Result<Vec<T>, Error> {
+    let query = format!(
+        "SELECT {} FROM {} WHERE {} ORDER BY id",
+        T::COLUMNS,
+        T::TABLE,
+        condition
+    );
+    conn.query(&query, params)?
+        .iter()
+        .map(T::from_row)
+        .collect()
+}
+
+// Select the rows of a table whose column is one of the ids, ordered by id
+fn select_in<T: FromRow>(
+    conn: &mut dyn SqlConnection,
+    column: &str,
+    ids: &[i32],
+) -> Result<Vec<T>, Error> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders = vec!["?"; ids.len()].join(", ");
+    select_where(
+        conn,
+        &format!("{} IN ({})", column, placeholders),
+        ids.iter().map(|id| SqlValue::from(*id)).collect(),
+    )
+}
+
+// A row another row points to (422 like a foreign key violation if it does not exist)
+fn select_reference<T: FromRow>(
+    conn: &mut dyn SqlConnection,
+    id: i32,
+    column: &str,
+) -> Result<T, Error> {
+    select_by_id(conn, id).map_err(|err| {
+        if err.code != 404 {
+            return err;
+        }
+        Error::with_kind(
+            format!("{} {} does not exist", column, id),
+            422,
+            error::FOREIGN_KEY_VIOLATION,
+        )
+    })
+}
+
+// Rank the rows that contain every term of the search (Only the first candidates, ordered by id)
+pub fn search_table<T: FromRow + Searchable>(
+    conn: &mut dyn SqlConnection,
+    query: &SearchQuery,
+) -> Result<Vec<SearchHit>, Error> {
+    let (conditions, mut params) = query.where_clause::<T>();
+    // The best candidates first, otherwise a good match with a high id could be cut off by the limit
+    let (order, order_params) = query.order_clause::<T>();
+    let select = format!(
+        "SELECT {} FROM {}{}{} LIMIT ?",
+        T::COLUMNS,
+        T::TABLE,
+        conditions,
+        order
+    );
+    params.extend(order_params);
+    params.push(SqlValue::Int(CANDIDATES));
+    let rows = conn
+        .query(&select, params)?
+        .iter()
+        .map(T::from_row)
+        .collect::<Result<Vec<T>, Error>>()?;
+    Ok(query.rank(&rows))
+}
+
+// The id the database assigned to a new row
+fn row_id(id: i64) -> Result<i32, Error> {
+    i32::try_from(id).map_err(|_| Error::new(format!("Id {} is out of range", id), 500))
+}
+
+// Change the stock of the material and book the movement (Run it in a transaction)
+fn book_movement(
+    conn: &mut dyn SqlConnection,
+    material_id: i32,
+    movement: NewStockMovement,
+    booking: &Booking,
+) -> Result<StockMovement, Error> {
+    let change = movement.change();
+    let mut query =
+        "UPDATE material SET version = version + 1, amount = amount + ? WHERE id = ?".to_string();
+    let mut params = sql_params![change, material_id];
+    if change < 0 && !booking.backorder {
+        // Only if the stock is enough, concurrent movements cannot both take the last pieces
+        query.push_str(" AND amount + ? >= 0");
+        params.push(change.into());
+    }
+    let affected = conn.execute(&query, params)?;
+    let material: Material = select_by_id(conn, material_id)?;
+    if affected == 0 {
+        return Err(insufficient_stock(material.amount, change));
+    }
+    let id = conn.insert(
+        "INSERT INTO stock_movement (material_id, kind, quantity, reason, user_id, created_at, amount_after) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        sql_params![
+            material_id,
+            movement.kind.as_str(),
+            movement.quantity,
+            movement.reason,
+            booking.user_id.as_str(),
+            booking.created_at,
+            material.amount,
+        ],
+    )?;
+    select_by_id(conn, row_id(id)?)
+}
+
+// Insert a purchase order as a draft (Run it in a transaction if lines are added as well)
+fn insert_purchase_order(
+    conn: &mut dyn SqlConnection,
+    purchase_order: NewPurchaseOrder,
+) -> Result<PurchaseOrder, Error> {
+    let id = conn.insert(
+        "INSERT INTO purchase_order (supplier_id, status, note, created_at) VALUES (?, ?, ?, ?)",
+        sql_params![
+            purchase_order.supplier_id,
+            PurchaseOrderStatus::Draft.as_str(),
+            purchase_order.note,
+            timestamp::now(),
+        ],
+    )?;
+    select_by_id(conn, row_id(id)?)
+}
+
+fn insert_purchase_order_line(
+    conn: &mut dyn SqlConnection,
+    line: NewPurchaseOrderLine,
+) -> Result<PurchaseOrderLine, Error> {
+    let id = conn.insert(
+        "INSERT INTO purchase_order_line (purchase_order_id, material_id, quantity, unit_price, unit_price_currency) VALUES (?, ?, ?, ?, ?)",
+        sql_params![
+            line.purchase_order_id,
+            line.material_id,
+            line.quantity,
+            line.unit_price,
+            line.unit_price.currency.as_str(),
+        ],
+    )?;
+    select_by_id(conn, row_id(id)?)
+}
+
+// The lines of a purchase order
+fn lines_of(
+    conn: &mut dyn SqlConnection,
+    purchase_order_id: i32,
+) -> Result<Vec<PurchaseOrderLine>, Error> {
+    select_where(
+        conn,
+        "purchase_order_id = ?",
+        sql_params![purchase_order_id],
+    )
+}
+
+// The materials of the project materials of a project that are backordered (Negative stock)
+fn backordered(conn: &mut dyn SqlConnection, project_id: i32) -> Result<Vec<i32>, Error> {
+    conn.query(
+        "SELECT DISTINCT m.id FROM project_material pm JOIN material m ON m.id = pm.material_id WHERE pm.project_id = ? AND m.amount < 0 ORDER BY m.id",
+        sql_params![project_id],
+    )?
+    .iter()
+    .map(|row| row.i32(0))
+    .collect()
+}
+
+fn insert_time_entry(conn: &mut dyn SqlConnection, entry: TimeEntry) -> Result<TimeEntry, Error> {
+    let id = conn.insert(
+        "INSERT INTO time_entry (project_id, user_id, started_at, ended_at, minutes, note, billable) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        sql_params![
+            entry.project_id,
+            entry.user_id,
+            entry.started_at,
+            entry.ended_at,
+            entry.minutes,
+            entry.note,
+            entry.billable,
+        ],
+    )?;
+    select_by_id(conn, row_id(id)?)
+}
+
+// The running timer of a user (None if it has none)
+fn running_timer(conn: &mut dyn SqlConnection, user_id: &str) -> Result<Option<TimeEntry>, Error> {
+    let entries: Vec<TimeEntry> = select_where(
+        conn,
+        "user_id = ? AND ended_at IS NULL",
+        sql_params![user_id],
+    )?;
+    Ok(entries.into_iter().next())
+}
+
+fn update_time_entry(
+    conn: &mut dyn SqlConnection,
+    entry: TimeEntry,
+    expected: i32,
+) -> Result<TimeEntry, Error> {
+    let (query, params) = with_version(
+        "UPDATE time_entry SET version = version + 1, project_id = ?, started_at = ?, ended_at = ?, minutes = ?, note = ?, billable = ? WHERE id = ?",
+        sql_params![
+            entry.project_id,
+            entry.started_at,
+            entry.ended_at,
+            entry.minutes,
+            entry.note,
+            entry.billable,
+            entry.id,
+        ],
+        Some(expected),
+    );
+    let affected = conn.execute(&query, params)?;
+    changed::<TimeEntry>(conn, entry.id, affected)?;
+    select_by_id(conn, entry.id)
+}
+
+// Delete one row of a table by its id (Only if it has the expected version)
+pub fn delete_by_id<T: FromRow + Versioned>(
+    conn: &mut dyn SqlConnection,
+    id: i32,
+    expected: Option<i32>,
+) -> Result<(), Error> {
+    let query = format!("DELETE FROM {} WHERE id = ?", T::TABLE);
+    let (query, params) = with_version(&query, sql_params![id], expected);
+    let affected = conn.execute(&query, params)?;
+    changed::<T>(conn, id, affected)
+}
+
+// Only touch the row if it still has the expected version
+fn with_version(
+    query: &str,
+    mut params: Vec<SqlValue>,
+    expected: Option<i32>,
+) -> (String, Vec<SqlValue>) {
+    match expected {
+        Some(version) => {
+            params.push(SqlValue::Int(version as i64));
+            (format!("{} AND version = ?", query), params)
+        }
+        None => (query.to_string(), params),
+    }
+}
+
+// A statement on one row that did not touch any row means the row does not exist or has another version
+fn changed<T: FromRow + Versioned>(
+    conn: &mut dyn SqlConnection,
+    id: i32,
+    affected: u64,
+) -> Result<(), Error> {
+    if affected == 0 {
+        let row: T = select_by_id(conn, id)?;
+        return Err(version::conflict(row.version()));
+    }
+    Ok(())
+}
+
+// The WorkshopStore on top of one of the SQL backends
+pub struct SqlStore<D: SqlDatabase> {
+    db: D,
+}
+
+impl<D: SqlDatabase> SqlStore<D> {
+    pub fn new(db: D) -> SqlStore<D> {
+        SqlStore { db }
+    }
+
+    // Run an INSERT and read the new row back (With the id the database assigned)
+    fn insert<T: FromRow>(&self, query: &str, params: Vec<SqlValue>) -> Result<T, Error> {
+        self.db.with_connection(|conn| {
+            let id = conn.insert(query, params)?;
+            select_by_id(conn, row_id(id)?)
+        })
+    }
+
+    // Run an UPDATE of one row and read it back (Fails with the not found error of the table if the row does not
+    // exist and with 412 if it does not have the expected version)
+    fn update<T: FromRow + Versioned>(
+        &self,
+        id: i32,
+        expected: Option<i32>,
+        query: &str,
+        params: Vec<SqlValue>,
+    ) -> Result<T, Error> {
+        self.db.with_connection(|conn| {
+            let (query, params) = with_version(query, params, expected);
+            let affected = conn.execute(&query, params)?;
+            changed::<T>(conn, id, affected)?;
+            select_by_id(conn, id)
+        })
+    }
+}
+
+impl<D: SqlDatabase> WorkshopStore for SqlStore<D> {
+    fn get_all_projects(&self, query: &ListQuery) -> Result<Page<Project>, Error> {
+        self.db.with_connection(|conn| select_page(conn, query))
+    }
+
+    fn get_project(&self, id: i32) -> Result<Project, Error> {
+        self.db.with_connection(|conn| select_by_id(conn, id))
+    }
+
+    fn add_project(&self, project: NewProject) -> Result<Project, Error> {
+        self.insert(
+            "INSERT INTO project (name, client_id, description, startpoint, endpoint, estimated_costs, estimated_costs_currency, estimated_hours, costs, costs_currency, status) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            sql_params![
+                project.name,
+                project.client_id,
+                project.description,
+                project.startpoint,
+                project.endpoint,
+                project.estimated_costs,
+                project.estimated_costs.currency.as_str(),
+                project.estimated_hours,
+                project.costs,
+                project.costs.currency.as_str(),
+                ProjectStatus::Inquiry.as_str(),
+            ],
+        )
+    }
+
+    fn update_project(&self, project: Project, expected: Option<i32>) -> Result<Project, Error> {
+        self.update::<Project>(
+            project.id,
+            expected,
+            "UPDATE project SET version = version + 1, name = ?, client_id = ?, description = ?, startpoint = ?, endpoint = ?, estimated_costs = ?, estimated_costs_currency = ?, estimated_hours = ?, costs = ?, costs_currency = ? WHERE id = ?",
+            sql_params![
+                project.name,
+                project.client_id,
+                project.description,
+                project.startpoint,
+                project.endpoint,
+                project.estimated_costs,
+                project.estimated_costs.currency.as_str(),
+                project.estimated_hours,
+                project.costs,
+                project.costs.currency.as_str(),
+                project.id,
+            ],
+        )
+    }
+
+    fn remove_project(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        self.db
+            .with_connection(|conn| delete_by_id::<Project>(conn, id, expected))
+    }
+
+    fn transition_project(
+        &self,
+        id: i32,
+        transition: Transition,
+        user_id: &str,
+        at: DateTime<Utc>,
+        expected: Option<i32>,
+    ) -> Result<Project, Error> {
+        self.db.with_transaction(|conn| {
+            let project: Project = select_by_id(conn, id)?;
+            version::check(project.version, expected)?;
+            check_transition(&project, transition.status, &backordered(conn, id)?)?;
+            let (query, params) = with_version(
+                "UPDATE project SET version = version + 1, status = ? WHERE id = ?",
+                sql_params![transition.status.as_str(), id],
+                Some(project.version),
+            );
+            let affected = conn.execute(&query, params)?;
+            changed::<Project>(conn, id, affected)?;
+            let row = transition.recorded(0, &project, user_id, at);
+            conn.insert(
+                "INSERT INTO project_transition (project_id, from_status, to_status, user_id, note, created_at) VALUES (?, ?, ?, ?, ?, ?)",
+                sql_params![
+                    row.project_id,
+                    row.from_status.as_str(),
+                    row.to_status.as_str(),
+                    row.user_id,
+                    row.note,
+                    row.created_at,
+                ],
+            )?;
+            select_by_id(conn, id)
+        })
+    }
+
+    fn get_project_transitions(&self, query: &ListQuery) -> Result<Page<ProjectTransition>, Error> {
+        self.db.with_connection(|conn| select_page(conn, query))
+    }
+
+    fn get_all_clients(&self, query: &ListQuery) -> Result<Page<Client>, Error> {
+        self.db.with_connection(|conn| select_page(conn, query))
+    }
+
+    fn get_client(&self, id: i32) -> Result<Client, Error> {
+        self.db.with_connection(|conn| select_by_id(conn, id))
+    }
+
+    fn add_client(&self, client: NewClient) -> Result<Client, Error> {
+        self.insert(
+            "INSERT INTO client (firstname, lastname, phone) VALUES (?, ?, ?)",
+            sql_params![client.firstname, client.lastname, client.phone],
+        )
+    }
+
+    fn update_client(&self, client: Client, expected: Option<i32>) -> Result<Client, Error> {
+        self.update::<Client>(
+            client.id,
+            expected,
+            "UPDATE client SET version = version + 1, firstname = ?, lastname = ?, phone = ? WHERE id = ?",
+            sql_params![client.firstname, client.lastname, client.phone, client.id],
+        )
+    }
+
+    fn remove_client(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        self.db
+            .with_connection(|conn| delete_by_id::<Client>(conn, id, expected))
+    }
+
+    fn get_all_materials(&self, query: &ListQuery) -> Result<Page<Material>, Error> {
+        self.db.with_connection(|conn| select_page(conn, query))
+    }
+
+    fn get_material(&self, id: i32) -> Result<Material, Error> {
+        self.db.with_connection(|conn| select_by_id(conn, id))
+    }
+
+    fn get_low_stock_materials(&self, query: &ListQuery) -> Result<Page<Material>, Error> {
+        self.db.with_connection(|conn| {
+            select_page_where(conn, query, Some("amount < threshold_value"))
+        })
+    }
+
+    fn add_material(&self, material: NewMaterial) -> Result<Material, Error> {
+        self.insert(
+            "INSERT INTO material (name, description, type_id, amount, costs, costs_currency, threshold_value, preferred_supplier_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            sql_params![
+                material.name,
+                material.description,
+                material.type_id,
+                material.amount,
+                material.costs,
+                material.costs.currency.as_str(),
+                material.threshold_value,
+                material.preferred_supplier_id,
+            ],
+        )
+    }
+
+    fn update_material(
+        &self,
+        material: Material,
+        expected: Option<i32>,
+    ) -> Result<Material, Error> {
+        self.update::<Material>(
+            material.id,
+            expected,
+            // The amount is only changed by stock movements
+            "UPDATE material SET version = version + 1, name = ?, description = ?, type_id = ?, costs = ?, costs_currency = ?, threshold_value = ?, preferred_supplier_id = ? WHERE id = ?",
+            sql_params![
+                material.name,
+                material.description,
+                material.type_id,
+                material.costs,
+                material.costs.currency.as_str(),
+                material.threshold_value,
+                material.preferred_supplier_id,
+                material.id,
+            ],
+        )
+    }
+
+    fn remove_material(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        self.db
+            .with_connection(|conn| delete_by_id::<Material>(conn, id, expected))
+    }
+
+    fn get_all_material_types(&self, query: &ListQuery) -> Result<Page<MaterialType>, Error> {
+        self.db.with_connection(|conn| select_page(conn, query))
+    }
+
+    fn get_material_type(&self, id: i32) -> Result<MaterialType, Error> {
+        self.db.with_connection(|conn| select_by_id(conn, id))
+    }
+
+    fn add_material_type(&self, material_type: NewMaterialType) -> Result<MaterialType, Error> {
+        self.insert(
+            "INSERT INTO material_type (name, description) VALUES (?, ?)",
+            sql_params![material_type.name, material_type.description],
+        )
+    }
+
+    fn update_material_type(
+        &self,
+        material_type: MaterialType,
+        expected: Option<i32>,
+    ) -> Result<MaterialType, Error> {
+        self.update::<MaterialType>(
+            material_type.id,
+            expected,
+            "UPDATE material_type SET version = version + 1, name = ?, description = ? WHERE id = ?",
+            sql_params![
+                material_type.name,
+                material_type.description,
+                material_type.id
+            ],
+        )
+    }
+
+    fn remove_material_type(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        self.db
+            .with_connection(|conn| delete_by_id::<MaterialType>(conn, id, expected))
+    }
+
+    fn get_all_project_materials(&self, query: &ListQuery) -> Result<Page<ProjectMaterial>, Error> {
+        self.db.with_connection(|conn| select_page(conn, query))
+    }
+
+    fn get_project_material(&self, id: i32) -> Result<ProjectMaterial, Error> {
+        self.db.with_connection(|conn| select_by_id(conn, id))
+    }
+
+    fn add_project_material(
+        &self,
+        project_material: NewProjectMaterial,
+        booking: &Booking,
+    ) -> Result<ProjectMaterial, Error> {
+        self.db.with_transaction(|conn| {
+            let id = conn.insert(
+                "INSERT INTO project_material (project_id, material_id, amount) VALUES (?, ?, ?)",
+                sql_params![
+                    project_material.project_id,
+                    project_material.material_id,
+                    project_material.amount,
+                ],
+            )?;
+            let movement = NewStockMovement::for_project(
+                project_material.project_id,
+                -project_material.amount,
+            );
+            book_movement(conn, project_material.material_id, movement, booking)?;
+            select_by_id(conn, row_id(id)?)
+        })
+    }
+
+    fn update_project_material(
+        &self,
+        project_material: ProjectMaterial,
+        expected: Option<i32>,
+        booking: &Booking,
+    ) -> Result<ProjectMaterial, Error> {
+        self.db.with_transaction(|conn| {
+            let current: ProjectMaterial = select_by_id(conn, project_material.id)?;
+            version::check(current.version, expected)?;
+            // Only the version that was read, the stock changes are computed from it
+            let (query, params) = with_version(
+                "UPDATE project_material SET version = version + 1, project_id = ?, material_id = ?, amount = ? WHERE id = ?",
+                sql_params![
+                    project_material.project_id,
+                    project_material.material_id,
+                    project_material.amount,
+                    project_material.id,
+                ],
+                Some(current.version),
+            );
+            let affected = conn.execute(&query, params)?;
+            changed::<ProjectMaterial>(conn, project_material.id, affected)?;
+            for (material_id, movement) in stock_changes(&current, &project_material) {
+                book_movement(conn, material_id, movement, booking)?;
+            }
+            select_by_id(conn, project_material.id)
+        })
+    }
+
+    fn remove_project_material(
+        &self,
+        id: i32,
+        expected: Option<i32>,
+        booking: &Booking,
+    ) -> Result<(), Error> {
+        self.db.with_transaction(|conn| {
+            let current: ProjectMaterial = select_by_id(conn, id)?;
+            version::check(current.version, expected)?;
+            delete_by_id::<ProjectMaterial>(conn, id, Some(current.version))?;
+            let movement = NewStockMovement::for_project(current.project_id, current.amount);
+            book_movement(conn, current.material_id, movement, booking)?;
+            Ok(())
+        })
+    }
+
+    fn get_costed_materials(
+        &self,
+        project_ids: &[i32],
+    ) -> Result<Vec<(ProjectMaterial, Material)>, Error> {
+        self.db.with_transaction(|conn| {
+            let project_materials: Vec<ProjectMaterial> =
+                select_in(conn, "project_id", project_ids)?;
+            let material_ids: Vec<i32> = project_materials
+                .iter()
+                .map(|project_material| project_material.material_id)
+                .collect::<BTreeSet<i32>>()
+                .into_iter()
+                .collect();
+            let materials: BTreeMap<i32, Material> = select_in(conn, "id", &material_ids)?
+                .into_iter()
+                .map(|material: Material| (material.id, material))
+                .collect();
+            // The foreign key keeps the material of every project material
+            Ok(project_materials
+                .into_iter()
+                .filter_map(|project_material| {
+                    let material = materials.get(&project_material.material_id)?.clone();
+                    Some((project_material, material))
+                })
+                .collect())
+        })
+    }
+
+    fn get_stock_movements(&self, query: &ListQuery) -> Result<Page<StockMovement>, Error> {
+        self.db.with_connection(|conn| select_page(conn, query))
+    }
+
+    fn add_stock_movement(
+        &self,
+        material_id: i32,
+        movement: NewStockMovement,
+        booking: &Booking,
+    ) -> Result<StockMovement, Error> {
+        self.db
+            .with_transaction(|conn| book_movement(conn, material_id, movement, booking))
+    }
+
+    fn get_all_suppliers(&self, query: &ListQuery) -> Result<Page<Supplier>, Error> {
+        self.db.with_connection(|conn| select_page(conn, query))
+    }
+
+    fn get_supplier(&self, id: i32) -> Result<Supplier, Error> {
+        self.db.with_connection(|conn| select_by_id(conn, id))
+    }
+
+    fn add_supplier(&self, supplier: NewSupplier) -> Result<Supplier, Error> {
+        self.insert(
+            "INSERT INTO supplier (name, email, phone) VALUES (?, ?, ?)",
+            sql_params![supplier.name, supplier.email, supplier.phone],
+        )
+    }
+
+    fn update_supplier(
+        &self,
+        supplier: Supplier,
+        expected: Option<i32>,
+    ) -> Result<Supplier, Error> {
+        self.update::<Supplier>(
+            supplier.id,
+            expected,
+            "UPDATE supplier SET version = version + 1, name = ?, email = ?, phone = ? WHERE id = ?",
+            sql_params![supplier.name, supplier.email, supplier.phone, supplier.id],
+        )
+    }
+
+    fn remove_supplier(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        self.db
+            .with_connection(|conn| delete_by_id::<Supplier>(conn, id, expected))
+    }
+
+    fn get_all_purchase_orders(&self, query: &ListQuery) -> Result<Page<PurchaseOrder>, Error> {
+        self.db.with_connection(|conn| select_page(conn, query))
+    }
+
+    fn get_purchase_order(&self, id: i32) -> Result<PurchaseOrder, Error> {
+        self.db.with_connection(|conn| select_by_id(conn, id))
+    }
+
+    fn add_purchase_order(&self, purchase_order: NewPurchaseOrder) -> Result<PurchaseOrder, Error> {
+        self.db
+            .with_connection(|conn| insert_purchase_order(conn, purchase_order))
+    }
+
+    fn update_purchase_order(
+        &self,
+        purchase_order: PurchaseOrder,
+        expected: Option<i32>,
+    ) -> Result<PurchaseOrder, Error> {
+        self.db.with_transaction(|conn| {
+            let current: PurchaseOrder = select_by_id(conn, purchase_order.id)?;
+            version::check(current.version, expected)?;
+            let lines = lines_of(conn, current.id)?;
+            check_status_change(&current, &purchase_order, lines.len())?;
+            // Only the version that was checked (The created_at time is kept)
+            let (query, params) = with_version(
+                "UPDATE purchase_order SET version = version + 1, supplier_id = ?, status = ?, note = ? WHERE id = ?",
+                sql_params![
+                    purchase_order.supplier_id,
+                    purchase_order.status.as_str(),
+                    purchase_order.note,
+                    purchase_order.id,
+                ],
+                Some(current.version),
+            );
+            let affected = conn.execute(&query, params)?;
+            changed::<PurchaseOrder>(conn, purchase_order.id, affected)?;
+            select_by_id(conn, purchase_order.id)
+        })
+    }
+
+    fn remove_purchase_order(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        self.db.with_transaction(|conn| {
+            let current: PurchaseOrder = select_by_id(conn, id)?;
+            version::check(current.version, expected)?;
+            check_draft(&current)?;
+            conn.execute(
+                "DELETE FROM purchase_order_line WHERE purchase_order_id = ?",
+                sql_params![id],
+            )?;
+            delete_by_id::<PurchaseOrder>(conn, id, Some(current.version))
+        })
+    }
+
+    fn suggest_reorder(&self) -> Result<Reorder, Error> {
+        self.db.with_transaction(|conn| {
+            let materials: Vec<Material> =
+                select_where(conn, "amount < threshold_value", Vec::new())?;
+            // What is ordered but not delivered yet
+            let open = conn
+                .query(
+                    "SELECT l.material_id, SUM(l.quantity - l.received_quantity) FROM purchase_order_line l JOIN purchase_order o ON o.id = l.purchase_order_id WHERE o.status IN (?, ?, ?) GROUP BY l.material_id",
+                    sql_params![
+                        PurchaseOrderStatus::Draft.as_str(),
+                        PurchaseOrderStatus::Sent.as_str(),
+                        PurchaseOrderStatus::PartiallyReceived.as_str(),
+                    ],
+                )?
+                .iter()
+                .map(|row| Ok((row.i32(0)?, row.i32(1)?)))
+                .collect::<Result<BTreeMap<i32, i32>, Error>>()?;
+            let (suppliers, without_supplier) = reorder_lines(&materials, &open);
+            let mut reorder = Reorder {
+                without_supplier,
+                ..Reorder::default()
+            };
+            for (supplier_id, lines) in suppliers {
+                let purchase_order = insert_purchase_order(
+                    conn,
+                    NewPurchaseOrder {
+                        supplier_id,
+                        note: "Suggested reorder".to_string(),
+                    },
+                )?;
+                let lines = lines
+                    .into_iter()
+                    .map(|line| {
+                        insert_purchase_order_line(
+                            conn,
+                            NewPurchaseOrderLine {
+                                purchase_order_id: purchase_order.id,
+                                ..line
+                            },
+                        )
+                    })
+                    .collect::<Result<Vec<PurchaseOrderLine>, Error>>()?;
+                reorder.purchase_orders.push(DraftedOrder {
+                    purchase_order,
+                    lines,
+                });
+            }
+            Ok(reorder)
+        })
+    }
+
+    fn get_all_purchase_order_lines(
+        &self,
+        query: &ListQuery,
+    ) -> Result<Page<PurchaseOrderLine>, Error> {
+        self.db.with_connection(|conn| select_page(conn, query))
+    }
+
+    fn get_purchase_order_line(&self, id: i32) -> Result<PurchaseOrderLine, Error> {
+        self.db.with_connection(|conn| select_by_id(conn, id))
+    }
+
+    fn add_purchase_order_line(
+        &self,
+        line: NewPurchaseOrderLine,
+    ) -> Result<PurchaseOrderLine, Error> {
+        self.db.with_transaction(|conn| {
+            let purchase_order: PurchaseOrder =
+                select_reference(conn, line.purchase_order_id, "purchase_order_id")?;
+            check_draft(&purchase_order)?;
+            insert_purchase_order_line(conn, line)
+        })
+    }
+
+    fn update_purchase_order_line(
+        &self,
+        line: PurchaseOrderLine,
+        expected: Option<i32>,
+    ) -> Result<PurchaseOrderLine, Error> {
+        self.db.with_transaction(|conn| {
+            let current: PurchaseOrderLine = select_by_id(conn, line.id)?;
+            version::check(current.version, expected)?;
+            let purchase_order: PurchaseOrder = select_by_id(conn, current.purchase_order_id)?;
+            check_draft(&purchase_order)?;
+            if line.purchase_order_id != current.purchase_order_id {
+                let purchase_order: PurchaseOrder =
+                    select_reference(conn, line.purchase_order_id, "purchase_order_id")?;
+                check_draft(&purchase_order)?;
+            }
+            // The received quantity is only changed by receiving the line
+            let (query, params) = with_version(
+                "UPDATE purchase_order_line SET version = version + 1, purchase_order_id = ?, material_id = ?, quantity = ?, unit_price = ?, unit_price_currency = ? WHERE id = ?",
+                sql_params![
+                    line.purchase_order_id,
+                    line.material_id,
+                    line.quantity,
+                    line.unit_price,
+                    line.unit_price.currency.as_str(),
+                    line.id,
+                ],
+                Some(current.version),
+            );
+            let affected = conn.execute(&query, params)?;
+            changed::<PurchaseOrderLine>(conn, line.id, affected)?;
+            select_by_id(conn, line.id)
+        })
+    }
+
+    fn remove_purchase_order_line(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        self.db.with_transaction(|conn| {
+            let current: PurchaseOrderLine = select_by_id(conn, id)?;
+            version::check(current.version, expected)?;
+            let purchase_order: PurchaseOrder = select_by_id(conn, current.purchase_order_id)?;
+            check_draft(&purchase_order)?;
+            delete_by_id::<PurchaseOrderLine>(conn, id, Some(current.version))
+        })
+    }
+
+    fn receive_purchase_order_line(
+        &self,
+        id: i32,
+        quantity: i32,
+        booking: &Booking,
+    ) -> Result<PurchaseOrderLine, Error> {
+        self.db.with_transaction(|conn| {
+            let current: PurchaseOrderLine = select_by_id(conn, id)?;
+            let purchase_order: PurchaseOrder = select_by_id(conn, current.purchase_order_id)?;
+            check_receipt(&purchase_order, &current, quantity)?;
+            // Only the version that was checked, concurrent deliveries cannot both take the open quantity
+            let (query, params) = with_version(
+                "UPDATE purchase_order_line SET version = version + 1, received_quantity = received_quantity + ? WHERE id = ?",
+                sql_params![quantity, id],
+                Some(current.version),
+            );
+            let affected = conn.execute(&query, params)?;
+            changed::<PurchaseOrderLine>(conn, id, affected)?;
+            let movement = NewStockMovement::for_purchase_order(purchase_order.id, quantity);
+            book_movement(conn, current.material_id, movement, booking)?;
+            let status = received_status(&lines_of(conn, purchase_order.id)?);
+            conn.execute(
+                "UPDATE purchase_order SET version = version + 1, status = ? WHERE id = ?",
+                sql_params![status.as_str(), purchase_order.id],
+            )?;
+            select_by_id(conn, id)
+        })
+    }
+
+    fn get_all_time_entries(&self, query: &ListQuery) -> Result<Page<TimeEntry>, Error> {
+        self.db.with_connection(|conn| select_page(conn, query))
+    }
+
+    fn get_time_entry(&self, id: i32) -> Result<TimeEntry, Error> {
+        self.db.with_connection(|conn| select_by_id(conn, id))
+    }
+
+    fn add_time_entry(&self, entry: NewTimeEntry, user_id: &str) -> Result<TimeEntry, Error> {
+        self.db
+            .with_connection(|conn| insert_time_entry(conn, entry.with_id(0, user_id)))
+    }
+
+    fn update_time_entry(
+        &self,
+        entry: TimeEntry,
+        expected: Option<i32>,
+    ) -> Result<TimeEntry, Error> {
+        self.db.with_transaction(|conn| {
+            let current: TimeEntry = select_by_id(conn, entry.id)?;
+            version::check(current.version, expected)?;
+            let entry = check_time_entry_change(&current, entry)?;
+            update_time_entry(conn, entry, current.version)
+        })
+    }
+
+    fn remove_time_entry(&self, id: i32, expected: Option<i32>) -> Result<(), Error> {
+        self.db
+            .with_connection(|conn| delete_by_id::<TimeEntry>(conn, id, expected))
+    }
+
+    fn start_timer(
+        &self,
+        timer: Timer,
+        user_id: &str,
+        started_at: DateTime<Utc>,
+    ) -> Result<TimeEntry, Error> {
+        self.db.with_transaction(|conn| {
+            if let Some(running) = running_timer(conn, user_id)? {
+                return Err(timer_running(&running));
+            }
+            insert_time_entry(conn, timer.started(0, user_id, started_at))
+        })
+    }
+
+    fn stop_timer(&self, user_id: &str, ended_at: DateTime<Utc>) -> Result<TimeEntry, Error> {
+        self.db.with_transaction(|conn| {
+            let running = running_timer(conn, user_id)?.ok_or_else(no_timer)?;
+            let version = running.version;
+            update_time_entry(conn, running.finished(Some(ended_at)), version)
+        })
+    }
+
+    fn get_time_summary(
+        &self,
+        query: &ListQuery,
+        group: TimeGroup,
+    ) -> Result<Vec<TimeSummary>, Error> {
+        let column = match group {
+            TimeGroup::Project => "project_id",
+            TimeGroup::User => "user_id",
+        };
+        // The field names of the query were checked against the fields of the time entry
+        let (conditions, params) = query.where_clause();
+        let keyword = if conditions.is_empty() {
+            " WHERE"
+        } else {
+            " AND"
+        };
+        let select = format!(
+            "SELECT {column}, COUNT(*), SUM(minutes), SUM(CASE WHEN billable <> 0 THEN minutes ELSE 0 END) FROM time_entry{conditions}{keyword} ended_at IS NOT NULL GROUP BY {column} ORDER BY {column}"
+        );
+        self.db.with_connection(|conn| {
+            conn.query(&select, params)?
+                .iter()
+                .map(|row| {
+                    let (project_id, user_id) = match group {
+                        TimeGroup::Project => (Some(row.i32(0)?), None),
+                        TimeGroup::User => (None, Some(row.text(0)?)),
+                    };
+                    Ok(TimeSummary::new(
+                        project_id,
+                        user_id,
+                        row.int(1)?,
+                        row.int(2)?,
+                        row.int(3)?,
+                    ))
+                })
+                .collect()
+        })
+    }
+
+    fn get_logged_minutes(&self, project_ids: &[i32]) -> Result<BTreeMap<i32, i64>, Error> {
+        if project_ids.is_empty() {
+            return Ok(BTreeMap::new());
+        }
+        let placeholders = vec!["?"; project_ids.len()].join(", ");
+        let select = format!(
+            "SELECT project_id, SUM(minutes) FROM time_entry WHERE ended_at IS NOT NULL AND project_id IN ({}) GROUP BY project_id",
+            placeholders
+        );
+        let params = project_ids.iter().map(|id| SqlValue::from(*id)).collect();
+        self.db.with_connection(|conn| {
+            conn.query(&select, params)?
+                .iter()
+                .map(|row| Ok((row.i32(0)?, row.int(1)?)))
+                .collect()
+        })
+    }
+
+    fn search(&self, query: &SearchQuery) -> Result<SearchResults, Error> {
+        self.db.with_connection(|conn| {
+            let mut results = SearchResults {
+                query: query.text.clone(),
+                ..SearchResults::default()
+            };
+            if query.clients {
+                results.clients = search_table::<Client>(conn, query)?;
+            }
+            if query.projects {
+                results.projects = search_table::<Project>(conn, query)?;
+            }
+            if query.materials {
+                results.materials = search_table::<Material>(conn, query)?;
+            }
+            Ok(results)
+        })
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        self.db.migrations()
+    }
+
+    fn applied_migrations(&self) -> Result<Vec<i64>, Error> {
+        self.db.with_connection(|conn| {
+            create_migrations_table(conn)?;
+            conn.query(
+                "SELECT version FROM schema_migrations ORDER BY version",
+                Vec::new(),
+            )?
+            .iter()
+            .map(|row| row.int(0))
+            .collect()
+        })
+    }
+
+    fn apply_migration(&self, migration: &Migration, direction: Direction) -> Result<(), Error> {
+        let apply = |conn: &mut dyn SqlConnection| -> Result<(), Error> {
+            create_migrations_table(conn)?;
+            match direction {
+                Direction::Up => {
+                    conn.execute_batch(migration.up)?;
+                    conn.execute(
+                        "INSERT INTO schema_migrations (version, name) VALUES (?, ?)",
+                        sql_params![migration.version, migration.name],
+                    )?;
+                }
+                Direction::Down => {
+                    conn.execute_batch(migration.down)?;
+                    conn.execute(
+                        "DELETE FROM schema_migrations WHERE version = ?",
+                        sql_params![migration.version],
+                    )?;
+                }
+            }
+            Ok(())
+        };
+        // The script and its row in schema_migrations are applied together or not at all (If the database can)
+        if self.db.transactional_ddl() {
+            self.db.with_transaction(apply)
+        } else {
+            self.db.with_connection(apply)
+        }
+    }
+}
+
+// The table that remembers the applied migrations (Same syntax in MySQL and SQLite)
+fn create_migrations_table(conn: &mut dyn SqlConnection) -> Result<(), Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version BIGINT NOT NULL PRIMARY KEY,
+            name VARCHAR(255) NOT NULL,
+            applied_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+}
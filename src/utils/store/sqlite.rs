@@ -0,0 +1,166 @@
+/*
+
+    Description: This file contains the SQLite backend of the WorkshopStore. It is meant for small workshops that
+                 do not run a MySQL server, all requests share one connection to the database file.
+
+*/
+
+use std::sync::Mutex;
+
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{ffi, params_from_iter, Connection, ErrorCode};
+
+use super::migrations::{Migration, SQLITE};
+use super::sql::{Row, SqlConnection, SqlDatabase, SqlStore, SqlValue};
+use crate::models::error::{self, Error};
+use crate::utils::config::DatabaseConfig;
+
+pub type SqliteStore = SqlStore<SqliteDatabase>;
+
+impl SqliteStore {
+    // Open (or create) the database file of the configuration
+    pub fn open(config: &DatabaseConfig) -> Result<SqliteStore, Error> {
+        Ok(SqlStore::new(SqliteDatabase::open(&config.url)?))
+    }
+}
+
+pub struct SqliteDatabase {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteDatabase {
+    // sqlite://path/to/file.db or sqlite://:memory:
+    pub fn open(url: &str) -> Result<SqliteDatabase, Error> {
+        let path = url.trim_start_matches("sqlite://");
+        let conn = Connection::open(path).map_err(to_error)?;
+        // SQLite only checks the references if it is asked to
+        conn.execute_batch("PRAGMA foreign_keys = ON")
+            .map_err(to_error)?;
+        Ok(SqliteDatabase {
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+impl SqlDatabase for SqliteDatabase {
+    fn with_connection<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut dyn SqlConnection) -> Result<T, Error>,
+    {
+        let conn = self.conn.lock().unwrap();
+        f(&mut SqliteConnection(&conn))
+    }
+
+    fn with_transaction<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut dyn SqlConnection) -> Result<T, Error>,
+    {
+        let mut conn = self.conn.lock().unwrap();
+        // Rolled back when it is dropped without a commit
+        let transaction = conn.transaction().map_err(to_error)?;
+        let result = f(&mut SqliteConnection(&transaction))?;
+        transaction.commit().map_err(to_error)?;
+        Ok(result)
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        SQLITE
+    }
+
+    fn transactional_ddl(&self) -> bool {
+        true
+    }
+}
+
+// The shared connection or a transaction on it
+struct SqliteConnection<'a>(&'a Connection);
+
+impl SqlConnection for SqliteConnection<'_> {
+    fn query(&mut self, sql: &str, params: Vec<SqlValue>) -> Result<Vec<Row>, Error> {
+        let mut statement = self.0.prepare_cached(sql).map_err(to_error)?;
+        let columns = statement.column_count();
+        let mut rows = statement
+            .query(params_from_iter(params.into_iter().map(to_value)))
+            .map_err(to_error)?;
+        let mut result = Vec::new();
+        while let Some(row) = rows.next().map_err(to_error)? {
+            let mut values = Vec::with_capacity(columns);
+            for index in 0..columns {
+                values.push(from_value(row.get_ref(index).map_err(to_error)?));
+            }
+            result.push(Row(values));
+        }
+        Ok(result)
+    }
+
+    fn execute(&mut self, sql: &str, params: Vec<SqlValue>) -> Result<u64, Error> {
+        let mut statement = self.0.prepare_cached(sql).map_err(to_error)?;
+        let affected = statement
+            .execute(params_from_iter(params.into_iter().map(to_value)))
+            .map_err(|err| to_statement_error(sql, err))?;
+        Ok(affected as u64)
+    }
+
+    fn insert(&mut self, sql: &str, params: Vec<SqlValue>) -> Result<i64, Error> {
+        self.execute(sql, params)?;
+        Ok(self.0.last_insert_rowid())
+    }
+
+    fn execute_batch(&mut self, sql: &str) -> Result<(), Error> {
+        self.0.execute_batch(sql).map_err(to_error)
+    }
+}
+
+fn to_error(err: rusqlite::Error) -> Error {
+    Error::new(err.to_string(), 500)
+}
+
+// Map the constraint violations the client can cause to their status codes
+fn to_statement_error(sql: &str, err: rusqlite::Error) -> Error {
+    let (code, message) = match &err {
+        rusqlite::Error::SqliteFailure(failure, message)
+            if failure.code == ErrorCode::ConstraintViolation =>
+        {
+            (
+                failure.extended_code,
+                message.clone().unwrap_or_else(|| err.to_string()),
+            )
+        }
+        _ => return to_error(err),
+    };
+    match code {
+        ffi::SQLITE_CONSTRAINT_PRIMARYKEY | ffi::SQLITE_CONSTRAINT_UNIQUE => {
+            Error::with_kind(message, 409, error::DUPLICATE_KEY)
+        }
+        // SQLite does not tell which side of the reference failed, a DELETE can only fail because of other rows
+        ffi::SQLITE_CONSTRAINT_FOREIGNKEY
+            if sql.trim_start().to_uppercase().starts_with("DELETE") =>
+        {
+            Error::with_kind(message, 409, error::REFERENCED_BY_OTHERS)
+        }
+        ffi::SQLITE_CONSTRAINT_FOREIGNKEY => {
+            Error::with_kind(message, 422, error::FOREIGN_KEY_VIOLATION)
+        }
+        _ => Error::new(message, 500),
+    }
+}
+
+fn to_value(value: SqlValue) -> Value {
+    match value {
+        SqlValue::Null => Value::Null,
+        SqlValue::Int(value) => Value::Integer(value),
+        SqlValue::Real(value) => Value::Real(value),
+        SqlValue::Text(value) => Value::Text(value),
+    }
+}
+
+fn from_value(value: ValueRef) -> SqlValue {
+    match value {
+        ValueRef::Null => SqlValue::Null,
+        ValueRef::Integer(value) => SqlValue::Int(value),
+        ValueRef::Real(value) => SqlValue::Real(value),
+        ValueRef::Text(value) | ValueRef::Blob(value) => {
+            SqlValue::Text(String::from_utf8_lossy(value).into_owned())
+        }
+    }
+}
@@ -0,0 +1,69 @@
+/*
+
+    Description: This file contains the row versions for the optimistic concurrency. Every update counts the version
+                 up, an update or delete that expects another version fails instead of overwriting the newer row.
+
+*/
+
+use crate::models::client::Client;
+use crate::models::error::Error;
+use crate::models::material::Material;
+use crate::models::material_type::MaterialType;
+use crate::models::project::Project;
+use crate::models::project_material::ProjectMaterial;
+use crate::models::purchase_order::PurchaseOrder;
+use crate::models::purchase_order_line::PurchaseOrderLine;
+use crate::models::supplier::Supplier;
+use crate::models::time_entry::TimeEntry;
+
+// A model with a row version
+pub trait Versioned {
+    fn version(&self) -> i32;
+    fn set_version(&mut self, version: i32);
+}
+
+// The row has the expected version (None expects any version)
+pub fn check(current: i32, expected: Option<i32>) -> Result<(), Error> {
+    match expected {
+        Some(expected) if expected != current => Err(conflict(current)),
+        _ => Ok(()),
+    }
+}
+
+// Somebody else changed the row since the client read it
+pub fn conflict(current: i32) -> Error {
+    Error::new(
+        format!(
+            "The resource was changed in the meantime (Current version {})",
+            current
+        ),
+        412,
+    )
+}
+
+macro_rules! versioned {
+    ($($model:ty),*) => {
+        $(
+            impl Versioned for $model {
+                fn version(&self) -> i32 {
+                    self.version
+                }
+                fn set_version(&mut self, version: i32) {
+                    self.version = version;
+                }
+            }
+        )*
+    };
+}
+
+versioned!(
+    Client,
+    Material,
+    MaterialType,
+    Project,
+    ProjectMaterial,
+    PurchaseOrder,
+    PurchaseOrderLine,
+    Supplier,
+    TimeEntry
+);
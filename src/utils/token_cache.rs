@@ -0,0 +1,80 @@
+/*
+
+    Description: This file contains the cache of the introspection results. The tokens are only kept as SHA-256 hash,
+                 an entry lives until the token expires but never longer than the TTL, the least recently used entries
+                 are dropped when the cache is full.
+
+*/
+
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use lru::LruCache;
+use sha2::{Digest, Sha256};
+
+use super::auth::Principal;
+
+type TokenHash = [u8; 32];
+
+pub struct TokenCache {
+    ttl: Duration,
+    entries: Mutex<LruCache<TokenHash, (Principal, Instant)>>,
+}
+
+impl TokenCache {
+    // None if the cache is disabled (TTL or size of 0)
+    pub fn new(ttl: Duration, size: usize) -> Option<TokenCache> {
+        if ttl.is_zero() {
+            return None;
+        }
+        Some(TokenCache {
+            ttl,
+            entries: Mutex::new(LruCache::new(NonZeroUsize::new(size)?)),
+        })
+    }
+
+    // The cached user of the token (Expired entries are dropped)
+    pub fn get(&self, token: &str) -> Option<Principal> {
+        let hash = hash(token);
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(&hash) {
+            Some((principal, expires_at)) if Instant::now() < *expires_at => {
+                Some(principal.clone())
+            }
+            Some(_) => {
+                entries.pop(&hash);
+                None
+            }
+            None => None,
+        }
+    }
+
+    // Remember the user of the token until exp (Unix time of the token) or the TTL, whatever comes first
+    pub fn insert(&self, token: &str, principal: Principal, exp: Option<u64>) {
+        let mut lifetime = self.ttl;
+        if let Some(exp) = exp {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            lifetime = lifetime.min(Duration::from_secs(exp.saturating_sub(now)));
+        }
+        if lifetime.is_zero() {
+            return;
+        }
+        self.entries
+            .lock()
+            .unwrap()
+            .put(hash(token), (principal, Instant::now() + lifetime));
+    }
+
+    // Forget the token (Keycloak rejected it, so the cached user must not be used anymore)
+    pub fn remove(&self, token: &str) {
+        self.entries.lock().unwrap().pop(&hash(token));
+    }
+}
+
+fn hash(token: &str) -> TokenHash {
+    Sha256::digest(token.as_bytes()).into()
+}
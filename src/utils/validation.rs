@@ -0,0 +1,360 @@
+/*
+
+    Description: This file contains the validation of the request bodies. Every model lists its rules, the controller
+                 checks a body before it is handed to the repository and answers 422 with all invalid fields.
+
+*/
+
+use std::fmt::Display;
+
+use crate::models::client::{Client, NewClient};
+use crate::models::error::{Error, FieldError};
+use crate::models::material::{Material, NewMaterial};
+use crate::models::material_type::{MaterialType, NewMaterialType};
+use crate::models::money::{Money, MAX_CENTS};
+use crate::models::project::{NewProject, Project};
+use crate::models::project_material::{NewProjectMaterial, ProjectMaterial};
+use crate::models::purchase_order::{NewPurchaseOrder, PurchaseOrder};
+use crate::models::purchase_order_line::{NewPurchaseOrderLine, PurchaseOrderLine, Receipt};
+use crate::models::stock_movement::{MovementKind, NewStockMovement};
+use crate::models::supplier::{NewSupplier, Supplier};
+use crate::models::time_entry::{self, NewTimeEntry, TimeEntry, Timer};
+
+// Longest text of the name columns (VARCHAR(255) in MySQL)
+pub const MAX_NAME_LENGTH: usize = 255;
+// Digits of a phone number (Without the separators)
+const MIN_PHONE_DIGITS: usize = 5;
+const MAX_PHONE_DIGITS: usize = 20;
+// Longest time entry in minutes (Work over midnight is one entry, a forgotten timer is not)
+pub const MAX_ENTRY_MINUTES: i32 = 24 * 60;
+
+// A model with validation rules
+pub trait Validate {
+    fn rules(&self, v: &mut Validator);
+
+    // Run all rules (422 with every invalid field, not only the first one)
+    fn validate(&self) -> Result<(), Error> {
+        let mut validator = Validator::default();
+        self.rules(&mut validator);
+        validator.finish()
+    }
+}
+
+// Collects the errors of the rules
+#[derive(Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    // A rule that does not fit the others (e.g. one field compared with another)
+    pub fn check(&mut self, field: &str, valid: bool, message: &str) -> &mut Validator {
+        // One error per field is enough
+        if !valid && !self.errors.iter().any(|error| error.field == field) {
+            self.errors.push(FieldError {
+                field: field.to_string(),
+                message: message.to_string(),
+            });
+        }
+        self
+    }
+
+    // Text that is not blank and not too long
+    pub fn name(&mut self, field: &str, value: &str) -> &mut Validator {
+        self.check(field, !value.trim().is_empty(), "must not be empty")
+            .check(
+                field,
+                value.chars().count() <= MAX_NAME_LENGTH,
+                &format!("must not be longer than {} characters", MAX_NAME_LENGTH),
+            )
+    }
+
+    pub fn min<T: PartialOrd + Display>(
+        &mut self,
+        field: &str,
+        value: T,
+        min: T,
+    ) -> &mut Validator {
+        let message = format!("must be at least {}", min);
+        self.check(field, value >= min, &message)
+    }
+
+    // A number of hours, finite and not negative
+    pub fn not_negative(&mut self, field: &str, value: f32) -> &mut Validator {
+        self.check(field, value.is_finite(), "must be a number")
+            .min(field, value, 0.0)
+    }
+
+    // Costs that are not negative and fit into the DECIMAL columns
+    pub fn money(&mut self, field: &str, value: &Money) -> &mut Validator {
+        self.check(field, value.cents >= 0, "must be at least 0")
+            .check(
+                field,
+                value.cents <= MAX_CENTS,
+                &format!(
+                    "must not be more than {}",
+                    Money::new(MAX_CENTS, value.currency)
+                ),
+            )
+    }
+
+    // The id of a referenced row (Whether it exists is checked by the database)
+    pub fn reference(&mut self, field: &str, id: i32) -> &mut Validator {
+        self.check(field, id > 0, "must be a valid id")
+    }
+
+    // Empty or a phone number like +49 (89) 123-456
+    pub fn phone(&mut self, field: &str, value: &str) -> &mut Validator {
+        let value = value.trim();
+        if value.is_empty() {
+            return self;
+        }
+        let digits = value.chars().filter(char::is_ascii_digit).count();
+        let characters = value.chars().enumerate().all(|(index, c)| {
+            c.is_ascii_digit() || " -/().".contains(c) || (c == '+' && index == 0)
+        });
+        self.check(
+            field,
+            characters && (MIN_PHONE_DIGITS..=MAX_PHONE_DIGITS).contains(&digits),
+            &format!(
+                "must be a phone number with {} to {} digits",
+                MIN_PHONE_DIGITS, MAX_PHONE_DIGITS
+            ),
+        )
+    }
+
+    // Empty or a mail address
+    pub fn email(&mut self, field: &str, value: &str) -> &mut Validator {
+        self.check(
+            field,
+            value.is_empty() || mail_address(value),
+            "must be a mail address like name@example.com",
+        )
+    }
+
+    fn finish(self) -> Result<(), Error> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        let fields: Vec<&str> = self
+            .errors
+            .iter()
+            .map(|error| error.field.as_str())
+            .collect();
+        Err(Error::with_fields(
+            format!("Invalid fields: {}", fields.join(", ")),
+            self.errors,
+        ))
+    }
+}
+
+// name@host without spaces or the characters that end an address in SMTP
+pub fn mail_address(address: &str) -> bool {
+    match address.split_once('@') {
+        Some((name, host)) => {
+            !name.is_empty()
+                && !host.is_empty()
+                && !address
+                    .chars()
+                    .any(|c| c.is_whitespace() || c.is_control() || c == '<' || c == '>')
+        }
+        None => false,
+    }
+}
+
+impl Validate for Client {
+    fn rules(&self, v: &mut Validator) {
+        v.name("firstname", &self.firstname)
+            .name("lastname", &self.lastname)
+            .phone("phone", &self.phone);
+    }
+}
+
+impl Validate for MaterialType {
+    fn rules(&self, v: &mut Validator) {
+        v.name("name", &self.name);
+    }
+}
+
+impl Validate for Material {
+    fn rules(&self, v: &mut Validator) {
+        // The stored amount is only changed by stock movements (Negative if material was backordered)
+        v.name("name", &self.name)
+            .reference("type_id", self.type_id)
+            .money("costs", &self.costs)
+            .min("threshold_value", self.threshold_value, 0);
+        if let Some(supplier_id) = self.preferred_supplier_id {
+            v.reference("preferred_supplier_id", supplier_id);
+        }
+    }
+}
+
+impl Validate for Project {
+    fn rules(&self, v: &mut Validator) {
+        v.name("name", &self.name)
+            .reference("client_id", self.client_id)
+            .check(
+                "endpoint",
+                self.endpoint >= self.startpoint,
+                "must not be before startpoint",
+            )
+            .money("estimated_costs", &self.estimated_costs)
+            .not_negative("estimated_hours", self.estimated_hours)
+            .money("costs", &self.costs);
+    }
+}
+
+impl Validate for ProjectMaterial {
+    fn rules(&self, v: &mut Validator) {
+        v.reference("project_id", self.project_id)
+            .reference("material_id", self.material_id)
+            .min("amount", self.amount, 1);
+    }
+}
+
+impl Validate for Supplier {
+    fn rules(&self, v: &mut Validator) {
+        v.name("name", &self.name)
+            .email("email", &self.email)
+            .phone("phone", &self.phone);
+    }
+}
+
+impl Validate for PurchaseOrder {
+    fn rules(&self, v: &mut Validator) {
+        v.reference("supplier_id", self.supplier_id);
+    }
+}
+
+// A new purchase order has no status yet (It starts as a draft)
+impl Validate for NewPurchaseOrder {
+    fn rules(&self, v: &mut Validator) {
+        v.reference("supplier_id", self.supplier_id);
+    }
+}
+
+impl Validate for PurchaseOrderLine {
+    fn rules(&self, v: &mut Validator) {
+        v.reference("purchase_order_id", self.purchase_order_id)
+            .reference("material_id", self.material_id)
+            .min("quantity", self.quantity, 1)
+            .money("unit_price", &self.unit_price);
+    }
+}
+
+impl Validate for Receipt {
+    fn rules(&self, v: &mut Validator) {
+        v.min("quantity", self.quantity, 1);
+    }
+}
+
+impl Validate for NewStockMovement {
+    fn rules(&self, v: &mut Validator) {
+        // The kind gives the direction, only an adjust can lower or raise the stock
+        if self.kind == MovementKind::Adjust {
+            v.check("quantity", self.quantity != 0, "must not be 0")
+                .check(
+                    "reason",
+                    !self.reason.trim().is_empty(),
+                    "must say why the stock is adjusted",
+                );
+        } else {
+            v.min("quantity", self.quantity, 1);
+        }
+        v.check(
+            "reason",
+            self.reason.chars().count() <= MAX_NAME_LENGTH,
+            &format!("must not be longer than {} characters", MAX_NAME_LENGTH),
+        );
+    }
+}
+
+impl Validate for TimeEntry {
+    fn rules(&self, v: &mut Validator) {
+        v.reference("project_id", self.project_id);
+        if let Some(ended_at) = self.ended_at {
+            let minutes = time_entry::minutes(self.started_at, ended_at);
+            v.check(
+                "ended_at",
+                ended_at > self.started_at,
+                "must be after started_at",
+            )
+            .check(
+                "ended_at",
+                minutes <= MAX_ENTRY_MINUTES,
+                &format!(
+                    "must not be more than {} minutes after started_at",
+                    MAX_ENTRY_MINUTES
+                ),
+            );
+        }
+    }
+}
+
+// A submitted entry has either an end or a duration (A running entry is started with the timer)
+impl Validate for NewTimeEntry {
+    fn rules(&self, v: &mut Validator) {
+        let end = match (self.ended_at, self.duration) {
+            (Some(_), None) => true,
+            (None, Some(duration)) => (1..=MAX_ENTRY_MINUTES).contains(&duration),
+            _ => false,
+        };
+        v.check(
+            "ended_at",
+            self.ended_at.is_some() || self.duration.is_some(),
+            "must be set if there is no duration",
+        )
+        .check(
+            "duration",
+            self.ended_at.is_none() || self.duration.is_none(),
+            "must not be set together with ended_at",
+        )
+        .check(
+            "duration",
+            end || self.duration.is_none() || self.ended_at.is_some(),
+            &format!("must be between 1 and {}", MAX_ENTRY_MINUTES),
+        );
+        // The end of an invalid duration would only repeat the error
+        if end {
+            self.clone().with_id(0, "").rules(v);
+        } else {
+            v.reference("project_id", self.project_id);
+        }
+    }
+}
+
+impl Validate for Timer {
+    fn rules(&self, v: &mut Validator) {
+        v.reference("project_id", self.project_id);
+    }
+}
+
+// A new row has the same rules as a stored one
+macro_rules! validate_new {
+    ($($new:ty),*) => {
+        $(
+            impl Validate for $new {
+                fn rules(&self, v: &mut Validator) {
+                    self.clone().with_id(0).rules(v);
+                }
+            }
+        )*
+    };
+}
+
+validate_new!(
+    NewClient,
+    NewMaterialType,
+    NewProject,
+    NewProjectMaterial,
+    NewPurchaseOrderLine,
+    NewSupplier
+);
+
+// A new material starts with the stock that is there
+impl Validate for NewMaterial {
+    fn rules(&self, v: &mut Validator) {
+        self.clone().with_id(0).rules(v);
+        v.min("amount", self.amount, 0);
+    }
+}